@@ -2,12 +2,14 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+pub mod state_diff;
 pub mod state_trait;
 pub mod tracer;
 
 use cfx_types::AddressWithSpace;
 use std::collections::HashSet;
 
+pub use state_diff::{AccountDiff, AccountSnapshot, StateDiff};
 pub use state_trait::StateTrait;
 
 /// Mode of dealing with null accounts.
@@ -0,0 +1,31 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{AddressWithSpace, H256, U256};
+use std::collections::BTreeMap;
+
+/// The basic fields of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+}
+
+/// The change of an account. A missing or removed account has no snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccountDiff {
+    pub address: AddressWithSpace,
+    pub before: Option<AccountSnapshot>,
+    pub after: Option<AccountSnapshot>,
+    /// The changed storage slots, with their values before and after.
+    pub storage: BTreeMap<Vec<u8>, (U256, U256)>,
+}
+
+/// The changes of the accounts, e.g. made by a transaction. Accounts touched
+/// but left unchanged are not included.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StateDiff {
+    pub accounts: BTreeMap<AddressWithSpace, AccountDiff>,
+}
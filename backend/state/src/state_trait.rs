@@ -42,8 +42,15 @@ pub trait StateOpsTrait {
 
     fn clean_account(&mut self, address: &AddressWithSpace) -> DbResult<()>;
 
-    fn inc_nonce(&mut self, address: &AddressWithSpace, account_start_nonce: &U256)
-        -> DbResult<()>;
+    /// Increments the nonce of an account. With `eip2681`, fails with
+    /// `NonceOverflow` if the account is a contract whose nonce has reached
+    /// 2^64-1.
+    fn inc_nonce(
+        &mut self,
+        address: &AddressWithSpace,
+        account_start_nonce: &U256,
+        eip2681: bool,
+    ) -> DbResult<()>;
 
     fn set_nonce(&mut self, address: &AddressWithSpace, nonce: &U256) -> DbResult<()>;
 
@@ -81,6 +88,9 @@ pub trait StateOpsTrait {
 
     fn storage_at(&self, address: &AddressWithSpace, key: &[u8]) -> DbResult<U256>;
 
+    /// Number of non-zero storage entries of the account.
+    fn storage_entry_count(&self, address: &AddressWithSpace) -> DbResult<u64>;
+
     fn set_storage(
         &mut self,
         address: &AddressWithSpace,
@@ -91,6 +101,16 @@ pub trait StateOpsTrait {
     fn set_system_storage(&mut self, key: Vec<u8>, value: U256) -> DbResult<()>;
 
     fn get_system_storage(&self, key: &[u8]) -> DbResult<U256>;
+
+    /// EIP-1153 transient storage, which lives until the end of the
+    /// transaction.
+    fn transient_storage_at(&self, address: &AddressWithSpace, key: &[u8]) -> U256;
+
+    fn set_transient_storage(&mut self, address: &AddressWithSpace, key: Vec<u8>, value: U256);
+
+    /// Wipes the transient storage of all the accounts, called at the end of
+    /// every transaction.
+    fn clear_transient_storage(&mut self);
 }
 
 pub trait AsStateOpsTrait: StateOpsTrait {
@@ -113,9 +133,13 @@ pub trait CheckpointTrait: StateOpsTrait {
 
     /// Revert to the last checkpoint and discard it.
     fn revert_to_checkpoint(&mut self);
+
+    /// The changes of the accounts since the last checkpoint, empty if there
+    /// is no checkpoint.
+    fn diff_since_checkpoint(&self) -> DbResult<StateDiff>;
 }
 
-use super::CleanupMode;
+use super::{CleanupMode, StateDiff};
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
 use cfx_statedb::Result as DbResult;
 use cfx_types::{AddressWithSpace, H256, U256};
@@ -78,6 +78,37 @@ where
     }
 }
 
+impl<R, S, T> StateTracer for (R, S, T)
+where
+    R: StateTracer,
+    S: StateTracer,
+    T: StateTracer,
+{
+    fn trace_internal_transfer(&mut self, from: AddressPocket, to: AddressPocket, value: U256) {
+        self.0.trace_internal_transfer(from, to, value);
+        self.1.trace_internal_transfer(from, to, value);
+        self.2.trace_internal_transfer(from, to, value);
+    }
+
+    fn checkpoint(&mut self) {
+        self.0.checkpoint();
+        self.1.checkpoint();
+        self.2.checkpoint();
+    }
+
+    fn discard_checkpoint(&mut self) {
+        self.0.discard_checkpoint();
+        self.1.discard_checkpoint();
+        self.2.discard_checkpoint();
+    }
+
+    fn revert_to_checkpoint(&mut self) {
+        self.0.revert_to_checkpoint();
+        self.1.revert_to_checkpoint();
+        self.2.revert_to_checkpoint();
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AddressPocket {
     Balance(AddressWithSpace),
@@ -22,5 +22,15 @@ error_chain! {
             description("incomplete database")
             display("incomplete database: address={:?}", address)
         }
+
+        NonceOverflow(address: Address) {
+            description("nonce overflow")
+            display("nonce overflow: address={:?}", address)
+        }
+
+        NotInitialized {
+            description("database not initialized")
+            display("database not initialized: total issued tokens are not set")
+        }
     }
 }
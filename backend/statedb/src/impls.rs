@@ -6,7 +6,7 @@ use std::marker::PhantomData;
 use super::*;
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
 
-use cfx_storage::{StorageKeyWrapper, StorageTrait};
+use cfx_storage::{PrefixIter, StorageKeyWrapper, StorageTrait};
 use primitives::{OwnedStateKey, StateKey};
 
 // Use generic type for better test-ability.
@@ -26,21 +26,53 @@ impl<'a> StateDb<'a> {
         });
         StateDb { storage }
     }
+
+    /// The single write path of `set_raw`, which records the entry when
+    /// `debug_record` is given.
+    fn set_owned(
+        &mut self,
+        key: OwnedStateKey,
+        value: Box<[u8]>,
+        debug_record: Option<&mut ComputeEpochDebugRecord>,
+    ) -> Result<()> {
+        self.storage.set(key, value).map_err(Into::into)
+    }
 }
 
 impl<'a> StateDbTrait for StateDb<'a> {
     fn get_raw(&self, key: StateKey) -> Result<Option<Box<[u8]>>> {
         self.storage.get(key.into_owned()).map_err(Into::into)
     }
+
+    fn get_raw_batch(&self, keys: &[StateKey]) -> Result<Vec<Option<Box<[u8]>>>> {
+        self.storage
+            .get_batch(keys.iter().map(|key| key.into_owned()).collect())
+            .map_err(Into::into)
+    }
     fn set_raw(
         &mut self,
         key: StateKey,
         value: Box<[u8]>,
         debug_record: Option<&mut ComputeEpochDebugRecord>,
     ) -> Result<()> {
-        self.storage
-            .set(key.into_owned(), value)
-            .map_err(Into::into)
+        self.set_owned(key.into_owned(), value, debug_record)
+    }
+
+    fn set_raw_batch(
+        &mut self,
+        entries: Vec<(OwnedStateKey, Box<[u8]>)>,
+        debug_record: Option<&mut ComputeEpochDebugRecord>,
+    ) -> Result<()> {
+        match debug_record {
+            // Each entry must be recorded, so write them one by one.
+            Some(debug_record) => {
+                for (key, value) in entries {
+                    self.set_owned(key, value, Some(&mut *debug_record))?;
+                }
+                Ok(())
+            }
+            None => self.storage.set_batch(entries).map_err(Into::into),
+        }
     }
 
     fn delete(
@@ -51,6 +83,10 @@ impl<'a> StateDbTrait for StateDb<'a> {
         self.storage.delete(key.into_owned()).map_err(Into::into)
     }
 
+    fn scan_prefix<'b>(&'b self, prefix: &[u8]) -> Result<PrefixIter<'b>> {
+        self.storage.scan_prefix(prefix).map_err(Into::into)
+    }
+
     fn commit(
         &mut self,
         epoch_id: EpochId,
@@ -58,4 +94,12 @@ impl<'a> StateDbTrait for StateDb<'a> {
     ) -> Result<()> {
         self.storage.commit(epoch_id).map_err(Into::into)
     }
+
+    fn rollback_to_epoch(&mut self, epoch_id: &EpochId) -> Result<()> {
+        self.storage.rollback_to_epoch(epoch_id).map_err(Into::into)
+    }
+
+    fn prune_before(&mut self, epoch_id: &EpochId) -> Result<()> {
+        self.storage.prune_before(epoch_id).map_err(Into::into)
+    }
 }
@@ -17,7 +17,8 @@ mod statedb_ext;
 mod tests;
 
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
-use primitives::{EpochId, StateKey};
+use cfx_storage::PrefixIter;
+use primitives::{EpochId, OwnedStateKey, StateKey};
 
 pub use self::{
     error::{Error, ErrorKind, Result},
@@ -28,6 +29,9 @@ pub use self::{
 pub trait StateDbTrait {
     fn get_raw(&self, key: StateKey) -> Result<Option<Box<[u8]>>>;
 
+    /// Gets several raw values with a single read from the storage.
+    fn get_raw_batch(&self, keys: &[StateKey]) -> Result<Vec<Option<Box<[u8]>>>>;
+
     fn set_raw(
         &mut self,
         key: StateKey,
@@ -35,15 +39,38 @@ pub trait StateDbTrait {
         debug_record: Option<&mut ComputeEpochDebugRecord>,
     ) -> Result<()>;
 
+    /// Sets several raw values with a single write to the storage.
+    fn set_raw_batch(
+        &mut self,
+        entries: Vec<(OwnedStateKey, Box<[u8]>)>,
+        debug_record: Option<&mut ComputeEpochDebugRecord>,
+    ) -> Result<()>;
+
     fn delete(
         &mut self,
         key: StateKey,
         debug_record: Option<&mut ComputeEpochDebugRecord>,
     ) -> Result<()>;
 
+    /// Iterates the raw keys starting with `prefix` and their values, if the
+    /// storage supports prefix scans.
+    fn scan_prefix<'a>(&'a self, _prefix: &[u8]) -> Result<PrefixIter<'a>> {
+        bail!(cfx_storage::Error::from(
+            cfx_storage::ErrorKind::PrefixScanUnsupported
+        ))
+    }
+
     fn commit(
         &mut self,
         epoch_id: EpochId,
         debug_record: Option<&mut ComputeEpochDebugRecord>,
     ) -> Result<()>;
+
+    /// Restores the state committed with `epoch_id`, if the storage keeps
+    /// the committed epochs.
+    fn rollback_to_epoch(&mut self, epoch_id: &EpochId) -> Result<()>;
+
+    /// Forgets the epochs committed before `epoch_id`, which can no longer
+    /// be rolled back to.
+    fn prune_before(&mut self, epoch_id: &EpochId) -> Result<()>;
 }
@@ -8,7 +8,10 @@ use crate::StateDbTrait;
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
 use cfx_parameters::internal_contract_addresses::STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS;
 use cfx_types::{AddressSpaceUtil, AddressWithSpace, H256, U256};
-use primitives::{is_default::IsDefault, Account, CodeInfo, StateKey};
+use primitives::{
+    encode_state_key, is_default::IsDefault, Account, CodeInfo, StateKey, StorageLayout,
+    StorageValue,
+};
 
 use super::Result;
 
@@ -57,9 +60,80 @@ pub trait StateDbExt: StateDbTrait {
         }
     }
 
+    /// Loads several accounts with a single read from the storage.
+    fn get_accounts(&self, addresses: &[AddressWithSpace]) -> Result<Vec<Option<Account>>> {
+        let keys: Vec<_> = addresses.iter().map(StateKey::new_account_key).collect();
+        self.get_raw_batch(&keys)?
+            .into_iter()
+            .zip(addresses)
+            .map(|(raw, address)| match raw {
+                None => Ok(None),
+                Some(raw) => Ok(Some(Account::new_from_rlp(
+                    address.address,
+                    &Rlp::new(&raw),
+                )?)),
+            })
+            .collect()
+    }
+
+    /// Returns every storage slot of `address` in the database with its
+    /// value, ordered by key.
+    fn iterate_storage(&self, address: &AddressWithSpace) -> Result<Vec<(Vec<u8>, U256)>> {
+        let prefix = encode_state_key(&StateKey::new_storage_key(address, &[]).into_owned());
+        let mut slots = self
+            .scan_prefix(&prefix)?
+            .map(|(key, raw)| {
+                let value = ::rlp::decode::<StorageValue>(&raw)?.value;
+                Ok((key[prefix.len()..].to_vec(), value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        slots.sort();
+        Ok(slots)
+    }
+
     fn get_code(&self, address: &AddressWithSpace, code_hash: &H256) -> Result<Option<CodeInfo>> {
         self.get::<CodeInfo>(StateKey::new_code_key(&address))
     }
+
+    fn get_storage_layout(&self, address: &AddressWithSpace) -> Result<Option<StorageLayout>> {
+        match self.get_raw(StateKey::new_storage_layout_key(address))? {
+            None => Ok(None),
+            Some(raw) => Ok(Some(StorageLayout::from_bytes(&raw)?)),
+        }
+    }
+
+    fn set_storage_layout(
+        &mut self,
+        address: &AddressWithSpace,
+        layout: &StorageLayout,
+        debug_record: Option<&mut ComputeEpochDebugRecord>,
+    ) -> Result<()> {
+        self.set_raw(
+            StateKey::new_storage_layout_key(address),
+            layout.to_bytes().into_boxed_slice(),
+            debug_record,
+        )
+    }
+
+    /// Number of non-zero storage entries of `address` in the database.
+    fn get_storage_entry_count(&self, address: &AddressWithSpace) -> Result<u64> {
+        let key = StateKey::new_storage_entry_count_key(address);
+        Ok(self.get::<u64>(key)?.unwrap_or_default())
+    }
+
+    fn set_storage_entry_count(
+        &mut self,
+        address: &AddressWithSpace,
+        count: u64,
+        debug_record: Option<&mut ComputeEpochDebugRecord>,
+    ) -> Result<()> {
+        self.set::<u64>(
+            StateKey::new_storage_entry_count_key(address),
+            &count,
+            debug_record,
+        )
+    }
+
     fn get_total_issued_tokens(&self) -> Result<U256> {
         let address = STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS.with_evm_space();
         let total_issued_tokens_key = StateKey::new_storage_key(&address, TOTAL_TOKENS_KEY);
@@ -67,6 +141,13 @@ pub trait StateDbExt: StateDbTrait {
         Ok(total_issued_tokens_opt.unwrap_or_default())
     }
 
+    /// Whether the total issued tokens have ever been committed.
+    fn is_initialized(&self) -> Result<bool> {
+        let address = STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS.with_evm_space();
+        let total_issued_tokens_key = StateKey::new_storage_key(&address, TOTAL_TOKENS_KEY);
+        Ok(self.get_raw(total_issued_tokens_key)?.is_some())
+    }
+
     fn set_total_issued_tokens(
         &mut self,
         total_issued_tokens: &U256,
@@ -74,8 +155,107 @@ pub trait StateDbExt: StateDbTrait {
     ) -> Result<()> {
         let address = STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS.with_evm_space();
         let total_issued_tokens_key = StateKey::new_storage_key(&address, TOTAL_TOKENS_KEY);
-        self.set::<U256>(total_issued_tokens_key, total_issued_tokens, debug_record)
+        // Written even if zero, `is_initialized` relies on the key.
+        self.set_raw(
+            total_issued_tokens_key,
+            Vec::<u8>::from(::rlp::encode(total_issued_tokens)).into_boxed_slice(),
+            debug_record,
+        )
     }
 }
 
 impl<T: StateDbTrait> StateDbExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::StateDbExt;
+    use crate::StateDb;
+    use cfx_storage::InMemoryDb;
+    use cfx_types::{Address, AddressSpaceUtil, H256, U256};
+    use primitives::{Account, StateKey, StorageValue};
+    use std::time::Instant;
+
+    fn new_db_with_accounts(count: u64) -> (StateDb<'static>, Vec<Account>) {
+        let mut db = StateDb::new(InMemoryDb::new());
+        let accounts: Vec<_> = (1..=count)
+            .map(|i| {
+                let address = Address::from_low_u64_be(i).with_evm_space();
+                Account::new_empty_with_balance(&address, &U256::from(i), &U256::zero())
+            })
+            .collect();
+        for account in &accounts {
+            db.set::<Account>(StateKey::new_account_key(account.address()), account, None)
+                .unwrap();
+        }
+        (db, accounts)
+    }
+
+    #[test]
+    fn test_get_accounts() {
+        let (db, accounts) = new_db_with_accounts(3);
+        let missing = Address::from_low_u64_be(100).with_evm_space();
+        let addresses = vec![*accounts[2].address(), missing, *accounts[0].address()];
+
+        assert_eq!(
+            db.get_accounts(&addresses).unwrap(),
+            vec![Some(accounts[2].clone()), None, Some(accounts[0].clone())]
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_get_accounts() {
+        let (db, accounts) = new_db_with_accounts(10_000);
+        let addresses: Vec<_> = accounts.iter().map(|account| *account.address()).collect();
+
+        let start = Instant::now();
+        let single: Vec<_> = addresses
+            .iter()
+            .map(|address| db.get_account(address).unwrap())
+            .collect();
+        let single_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let batch = db.get_accounts(&addresses).unwrap();
+        let batch_elapsed = start.elapsed();
+
+        assert_eq!(single, batch);
+        println!(
+            "get_account x {}: {:?}, get_accounts: {:?}",
+            addresses.len(),
+            single_elapsed,
+            batch_elapsed
+        );
+    }
+
+    #[test]
+    fn test_iterate_storage() {
+        let mut db = StateDb::new(InMemoryDb::new());
+        let contract = Address::from_low_u64_be(1).with_evm_space();
+        let other = Address::from_low_u64_be(2).with_evm_space();
+        let slots: Vec<_> = (0..3u64)
+            .map(|i| {
+                (
+                    H256::from_low_u64_be(i).as_bytes().to_vec(),
+                    U256::from(i + 10),
+                )
+            })
+            .collect();
+        for (key, value) in &slots {
+            db.set::<StorageValue>(
+                StateKey::new_storage_key(&contract, key),
+                &StorageValue { value: *value },
+                None,
+            )
+            .unwrap();
+        }
+        db.set::<StorageValue>(
+            StateKey::new_storage_key(&other, &slots[0].0),
+            &StorageValue { value: 1.into() },
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(db.iterate_storage(&contract).unwrap(), slots);
+    }
+}
@@ -1,17 +1,76 @@
 use std::{collections::HashMap, sync::RwLock};
 
-use crate::StorageTrait;
+use crate::{ErrorKind, PrefixIter, StorageTrait};
+use primitives::EpochId;
 
 type Bytes = Vec<u8>;
+/// The values of some keys before a set of changes, `None` for the keys which
+/// were absent.
+type UndoLog = HashMap<Bytes, Option<Box<[u8]>>>;
+
 #[derive(Default)]
 pub struct InMemoryDb {
     inner: RwLock<HashMap<Bytes, Box<[u8]>>>,
+    /// The committed epochs in commit order, each with the undo log of the
+    /// epochs committed after it.
+    committed_epochs: Vec<(EpochId, UndoLog)>,
+    /// The undo log of the changes since the last commit, only kept once an
+    /// epoch is committed.
+    uncommitted_changes: UndoLog,
+}
+
+/// A copy of the content of an `InMemoryDb`, see `InMemoryDb::snapshot`.
+#[derive(Clone)]
+pub struct InMemoryDbSnapshot {
+    inner: HashMap<Bytes, Box<[u8]>>,
 }
 
 impl InMemoryDb {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Copies the current content, which `restore` can bring back later.
+    pub fn snapshot(&self) -> InMemoryDbSnapshot {
+        InMemoryDbSnapshot {
+            inner: self.inner.read().unwrap().clone(),
+        }
+    }
+
+    /// Replaces the content with `snapshot`.
+    pub fn restore(&mut self, snapshot: InMemoryDbSnapshot) {
+        let keys: Vec<Bytes> = self
+            .inner
+            .get_mut()
+            .unwrap()
+            .keys()
+            .chain(snapshot.inner.keys())
+            .cloned()
+            .collect();
+        for key in &keys {
+            self.record_change(key);
+        }
+        *self.inner.get_mut().unwrap() = snapshot.inner;
+    }
+
+    /// Keeps the value of `key` before its first change since the last
+    /// commit, so that the change can be rolled back.
+    fn record_change(&mut self, key: &[u8]) {
+        if self.committed_epochs.is_empty() || self.uncommitted_changes.contains_key(key) {
+            return;
+        }
+        let value = self.inner.get_mut().unwrap().get(key).cloned();
+        self.uncommitted_changes.insert(key.to_vec(), value);
+    }
+
+    fn undo(inner: &mut HashMap<Bytes, Box<[u8]>>, undo_log: UndoLog) {
+        for (key, value) in undo_log {
+            match value {
+                Some(value) => inner.insert(key, value),
+                None => inner.remove(&key),
+            };
+        }
+    }
 }
 
 impl StorageTrait for InMemoryDb {
@@ -21,17 +80,140 @@ impl StorageTrait for InMemoryDb {
         Ok(self.inner.read().unwrap().get(&key).cloned())
     }
 
+    fn get_batch(&self, keys: Vec<Self::StorageKey>) -> crate::Result<Vec<Option<Box<[u8]>>>> {
+        let inner = self.inner.read().unwrap();
+        Ok(keys.iter().map(|key| inner.get(key).cloned()).collect())
+    }
+
     fn set(&mut self, access_key: Self::StorageKey, value: Box<[u8]>) -> crate::Result<()> {
+        self.record_change(&access_key);
         self.inner.get_mut().unwrap().insert(access_key, value);
         Ok(())
     }
 
+    fn set_batch(&mut self, entries: Vec<(Self::StorageKey, Box<[u8]>)>) -> crate::Result<()> {
+        for (access_key, _) in &entries {
+            self.record_change(access_key);
+        }
+        self.inner.write().unwrap().extend(entries);
+        Ok(())
+    }
+
     fn delete(&mut self, access_key: Self::StorageKey) -> crate::Result<()> {
+        self.record_change(&access_key);
         self.inner.get_mut().unwrap().remove(&access_key);
         Ok(())
     }
 
-    fn commit(&mut self, _epoch: primitives::EpochId) -> crate::Result<()> {
+    fn scan_prefix<'a>(&'a self, prefix: &[u8]) -> crate::Result<PrefixIter<'a>> {
+        // The entries are copied out, the lock can't outlive this call.
+        let inner = self.inner.read().unwrap();
+        let entries: Vec<_> = inner
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Ok(Box::new(entries.into_iter()))
+    }
+
+    fn commit(&mut self, epoch: EpochId) -> crate::Result<()> {
+        let changes = std::mem::take(&mut self.uncommitted_changes);
+        if let Some((_, undo_log)) = self.committed_epochs.last_mut() {
+            *undo_log = changes;
+        }
+        self.committed_epochs.push((epoch, UndoLog::new()));
         Ok(())
     }
+
+    fn rollback_to_epoch(&mut self, epoch: &EpochId) -> crate::Result<()> {
+        let index = match self.committed_epochs.iter().rposition(|(e, _)| e == epoch) {
+            Some(index) => index,
+            None => bail!(ErrorKind::UnknownEpoch(*epoch)),
+        };
+        let inner = self.inner.get_mut().unwrap();
+        Self::undo(inner, std::mem::take(&mut self.uncommitted_changes));
+        for (_, undo_log) in self.committed_epochs.drain(index..).rev() {
+            Self::undo(inner, undo_log);
+        }
+        self.committed_epochs.push((*epoch, UndoLog::new()));
+        Ok(())
+    }
+
+    fn prune_before(&mut self, epoch: &EpochId) -> crate::Result<()> {
+        let index = match self.committed_epochs.iter().rposition(|(e, _)| e == epoch) {
+            Some(index) => index,
+            None => bail!(ErrorKind::UnknownEpoch(*epoch)),
+        };
+        self.committed_epochs.drain(..index);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemoryDb;
+    use crate::StorageTrait;
+    use primitives::EpochId;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut db = InMemoryDb::new();
+        db.set(b"a".to_vec(), vec![1].into()).unwrap();
+        let snapshot = db.snapshot();
+
+        db.set(b"a".to_vec(), vec![2].into()).unwrap();
+        db.set(b"b".to_vec(), vec![3].into()).unwrap();
+        db.restore(snapshot.clone());
+        assert_eq!(db.get(b"a".to_vec()).unwrap(), Some(vec![1].into()));
+        assert_eq!(db.get(b"b".to_vec()).unwrap(), None);
+
+        // A snapshot can be restored repeatedly.
+        db.delete(b"a".to_vec()).unwrap();
+        db.restore(snapshot);
+        assert_eq!(db.get(b"a".to_vec()).unwrap(), Some(vec![1].into()));
+    }
+
+    #[test]
+    fn test_rollback_to_epoch() {
+        let epochs: Vec<EpochId> = (1..=3).map(EpochId::from_low_u64_be).collect();
+        let mut db = InMemoryDb::new();
+        db.set(b"a".to_vec(), vec![0].into()).unwrap();
+        db.commit(epochs[0]).unwrap();
+        db.set(b"a".to_vec(), vec![1].into()).unwrap();
+        db.set(b"b".to_vec(), vec![1].into()).unwrap();
+        db.commit(epochs[1]).unwrap();
+        db.delete(b"a".to_vec()).unwrap();
+        db.commit(epochs[2]).unwrap();
+        db.set(b"c".to_vec(), vec![2].into()).unwrap();
+
+        db.rollback_to_epoch(&epochs[1]).unwrap();
+        assert_eq!(db.get(b"a".to_vec()).unwrap(), Some(vec![1].into()));
+        assert_eq!(db.get(b"c".to_vec()).unwrap(), None);
+
+        // An epoch can be rolled back to repeatedly.
+        db.set_batch(vec![(b"b".to_vec(), vec![3].into())]).unwrap();
+        db.rollback_to_epoch(&epochs[1]).unwrap();
+        assert_eq!(db.get(b"b".to_vec()).unwrap(), Some(vec![1].into()));
+        db.rollback_to_epoch(&epochs[0]).unwrap();
+        assert_eq!(db.get(b"a".to_vec()).unwrap(), Some(vec![0].into()));
+        assert_eq!(db.get(b"b".to_vec()).unwrap(), None);
+        assert!(db.rollback_to_epoch(&epochs[1]).is_err());
+    }
+
+    #[test]
+    fn test_prune_before() {
+        let epochs: Vec<EpochId> = (1..=3).map(EpochId::from_low_u64_be).collect();
+        let mut db = InMemoryDb::new();
+        for (i, epoch) in epochs.iter().enumerate() {
+            db.set(b"a".to_vec(), vec![i as u8].into()).unwrap();
+            db.commit(*epoch).unwrap();
+        }
+
+        db.prune_before(&epochs[1]).unwrap();
+        assert_eq!(db.committed_epochs.len(), 2);
+        assert!(db.rollback_to_epoch(&epochs[0]).is_err());
+        db.rollback_to_epoch(&epochs[1]).unwrap();
+        assert_eq!(db.get(b"a".to_vec()).unwrap(), Some(vec![1].into()));
+        assert!(db.prune_before(&epochs[2]).is_err());
+    }
 }
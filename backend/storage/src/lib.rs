@@ -7,7 +7,7 @@ extern crate error_chain;
 
 mod in_memory;
 
-pub use in_memory::InMemoryDb;
+pub use in_memory::{InMemoryDb, InMemoryDbSnapshot};
 
 error_chain! {
     links {
@@ -17,17 +17,59 @@ error_chain! {
     }
 
     errors {
+        UnknownEpoch(epoch: EpochId) {
+            description("unknown epoch")
+            display("unknown epoch: epoch {:?} is not committed", epoch)
+        }
+
+        PrefixScanUnsupported {
+            description("prefix scan unsupported")
+            display("prefix scan unsupported: the storage can't iterate its keys")
+        }
     }
 }
 
+/// The raw keys and values returned by a prefix scan.
+pub type PrefixIter<'a> = Box<dyn Iterator<Item = (Vec<u8>, Box<[u8]>)> + 'a>;
+
 pub trait StorageTrait {
     type StorageKey;
 
     // Actions.
     fn get(&self, key: Self::StorageKey) -> Result<Option<Box<[u8]>>>;
+    /// Gets several keys at once, backends may override it to read the
+    /// entries under a single lock.
+    fn get_batch(&self, keys: Vec<Self::StorageKey>) -> Result<Vec<Option<Box<[u8]>>>> {
+        keys.into_iter().map(|key| self.get(key)).collect()
+    }
     fn set(&mut self, access_key: Self::StorageKey, value: Box<[u8]>) -> Result<()>;
+    /// Sets several keys at once, backends may override it to write the
+    /// entries under a single lock.
+    fn set_batch(&mut self, entries: Vec<(Self::StorageKey, Box<[u8]>)>) -> Result<()> {
+        for (access_key, value) in entries {
+            self.set(access_key, value)?;
+        }
+        Ok(())
+    }
     fn delete(&mut self, access_key: Self::StorageKey) -> Result<()>;
+    /// Iterates the raw keys starting with `prefix` and their values, in no
+    /// particular order. Backends not able to iterate their keys don't
+    /// support it.
+    fn scan_prefix<'a>(&'a self, _prefix: &[u8]) -> Result<PrefixIter<'a>> {
+        bail!(ErrorKind::PrefixScanUnsupported)
+    }
     fn commit(&mut self, epoch: EpochId) -> Result<()>;
+    /// Restores the content committed with `epoch`, forgetting the epochs
+    /// committed after it. Backends not keeping the committed epochs know
+    /// none of them.
+    fn rollback_to_epoch(&mut self, epoch: &EpochId) -> Result<()> {
+        bail!(ErrorKind::UnknownEpoch(*epoch))
+    }
+    /// Forgets the epochs committed before `epoch`, which can no longer be
+    /// rolled back to.
+    fn prune_before(&mut self, _epoch: &EpochId) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub struct StorageKeyWrapper<T, Key> {
@@ -46,15 +88,41 @@ where
         self.inner.get(key.into())
     }
 
+    fn get_batch(&self, keys: Vec<Self::StorageKey>) -> Result<Vec<Option<Box<[u8]>>>> {
+        self.inner
+            .get_batch(keys.into_iter().map(Into::into).collect())
+    }
+
     fn set(&mut self, access_key: Self::StorageKey, value: Box<[u8]>) -> Result<()> {
         self.inner.set(access_key.into(), value)
     }
 
+    fn set_batch(&mut self, entries: Vec<(Self::StorageKey, Box<[u8]>)>) -> Result<()> {
+        self.inner.set_batch(
+            entries
+                .into_iter()
+                .map(|(access_key, value)| (access_key.into(), value))
+                .collect(),
+        )
+    }
+
     fn delete(&mut self, access_key: Self::StorageKey) -> Result<()> {
         self.inner.delete(access_key.into())
     }
 
+    fn scan_prefix<'a>(&'a self, prefix: &[u8]) -> Result<PrefixIter<'a>> {
+        self.inner.scan_prefix(prefix)
+    }
+
     fn commit(&mut self, epoch: EpochId) -> Result<()> {
         self.inner.commit(epoch)
     }
+
+    fn rollback_to_epoch(&mut self, epoch: &EpochId) -> Result<()> {
+        self.inner.rollback_to_epoch(epoch)
+    }
+
+    fn prune_before(&mut self, epoch: &EpochId) -> Result<()> {
+        self.inner.prune_before(epoch)
+    }
 }
@@ -1,19 +1,10 @@
 extern crate cfx_evm;
 
-use std::marker::PhantomData;
-
 use cfx_evm::{
-    new_machine_with_builtin, CommonParams, Env, ExecutionOutcome, State, TXExecutor,
-    TransactOptions, VmFactory,
+    new_machine_with_builtin, Action, Address, AddressSpaceUtil, CleanupMode, CommonParams,
+    Eip155Transaction, Env, Generator, InMemoryDb, Random, SignedTransaction, State, StateDb,
+    StateOpsTrait, TXExecutor, TransactOptions, Transaction, VmFactory, U256,
 };
-use cfx_state::state_trait::StateOpsTrait;
-use cfx_statedb::StateDb;
-use cfx_storage::InMemoryDb;
-use cfx_storage::StorageKeyWrapper;
-use cfx_types::{Address, AddressSpaceUtil, U256};
-use cfxkey::Generator;
-use cfxkey::Random;
-use primitives::{Action, Eip155Transaction, SignedTransaction, Transaction};
 
 fn main() {
     // 1. Prepare for context
@@ -50,7 +41,7 @@ fn main() {
         .add_balance(
             &sender_with_space,
             &U256::from(1_000_000_000),
-            cfx_state::CleanupMode::NoEmpty,
+            CleanupMode::NoEmpty,
             U256::zero(),
         )
         .expect("no db error");
@@ -24,6 +24,7 @@ mod blake2f;
 
 use std::{
     cmp::{max, min},
+    collections::BTreeMap,
     convert::TryInto,
     io::{self, Cursor, Read},
     mem::size_of,
@@ -81,7 +82,7 @@ impl Linear {
     }
 }
 
-/// A special pricing model for modular exponentiation.
+/// A special pricing model for modular exponentiation, of EIP-198.
 pub(crate) struct ModexpPricer {
     divisor: usize,
 }
@@ -92,6 +93,10 @@ impl ModexpPricer {
     }
 }
 
+/// The pricing model for modular exponentiation of EIP-2565, with a minimum
+/// cost of 200.
+pub(crate) struct Eip2565ModexpPricer;
+
 impl Pricer for Linear {
     fn cost(&self, input: &[u8]) -> U256 {
         U256::from(self.base) + U256::from(self.word) * U256::from((input.len() + 31) / 32)
@@ -120,10 +125,62 @@ impl Pricer for AltBn128PairingPricer {
 
 impl Pricer for ModexpPricer {
     fn cost(&self, input: &[u8]) -> U256 {
+        let (base_len, exp_len, mod_len) = ModexpPricer::read_lengths(input);
+
+        if mod_len.is_zero() && base_len.is_zero() {
+            return U256::zero();
+        }
+
+        let (base_len, exp_len, mod_len) =
+            match ModexpPricer::checked_lengths(base_len, exp_len, mod_len) {
+                Some(lengths) => lengths,
+                None => return U256::max_value(),
+            };
+
+        let m = max(mod_len, base_len);
+        let exp_low = Self::read_exp_low(input, base_len, exp_len);
+        let adjusted_exp_len = Self::adjusted_exp_len(exp_len, exp_low);
+
+        let (gas, overflow) = Self::mult_complexity(m).overflowing_mul(max(adjusted_exp_len, 1));
+        if overflow {
+            return U256::max_value();
+        }
+        (gas / self.divisor as u64).into()
+    }
+}
+
+impl Pricer for Eip2565ModexpPricer {
+    fn cost(&self, input: &[u8]) -> U256 {
+        const MIN_GAS: u64 = 200;
+
+        let (base_len, exp_len, mod_len) = ModexpPricer::read_lengths(input);
+        let (base_len, exp_len, mod_len) =
+            match ModexpPricer::checked_lengths(base_len, exp_len, mod_len) {
+                Some(lengths) => lengths,
+                None => return U256::max_value(),
+            };
+
+        // The lengths are below 2^31, the product of the words cannot overflow.
+        let words = (max(base_len, mod_len) + 7) / 8;
+        let mult_complexity = words * words;
+        let exp_low = ModexpPricer::read_exp_low(input, base_len, exp_len);
+        let iteration_count = max(ModexpPricer::adjusted_exp_len(exp_len, exp_low), 1);
+
+        let (gas, overflow) = mult_complexity.overflowing_mul(iteration_count);
+        if overflow {
+            return U256::max_value();
+        }
+        max(gas / 3, MIN_GAS).into()
+    }
+}
+
+impl ModexpPricer {
+    /// Reads the lengths of the base, the exponent and the modulus, as U256
+    /// for accurate gas calculation.
+    fn read_lengths(input: &[u8]) -> (U256, U256, U256) {
         let mut reader = input.chain(io::repeat(0));
         let mut buf = [0; 32];
 
-        // read lengths as U256 here for accurate gas calculation.
         let mut read_len = || {
             reader
                 .read_exact(&mut buf[..])
@@ -133,21 +190,21 @@ impl Pricer for ModexpPricer {
         let base_len = read_len();
         let exp_len = read_len();
         let mod_len = read_len();
+        (base_len, exp_len, mod_len)
+    }
 
-        if mod_len.is_zero() && base_len.is_zero() {
-            return U256::zero();
-        }
-
+    /// The lengths as u64, `None` if any of them is too large to be priced.
+    fn checked_lengths(base_len: U256, exp_len: U256, mod_len: U256) -> Option<(u64, u64, u64)> {
         let max_len = U256::from(u32::max_value() / 2);
         if base_len > max_len || mod_len > max_len || exp_len > max_len {
-            return U256::max_value();
+            return None;
         }
-        let (base_len, exp_len, mod_len) =
-            (base_len.low_u64(), exp_len.low_u64(), mod_len.low_u64());
+        Some((base_len.low_u64(), exp_len.low_u64(), mod_len.low_u64()))
+    }
 
-        let m = max(mod_len, base_len);
-        // read fist 32-byte word of the exponent.
-        let exp_low = if base_len + 96 >= input.len() as u64 {
+    /// Reads the first 32-byte word of the exponent.
+    fn read_exp_low(input: &[u8], base_len: u64, exp_len: u64) -> U256 {
+        if base_len + 96 >= input.len() as u64 {
             U256::zero()
         } else {
             let mut buf = [0; 32];
@@ -157,19 +214,9 @@ impl Pricer for ModexpPricer {
                 .read_exact(&mut buf[(32 - len)..])
                 .expect("reading from zero-extended memory cannot fail; qed");
             U256::from_big_endian(&buf[..])
-        };
-
-        let adjusted_exp_len = Self::adjusted_exp_len(exp_len, exp_low);
-
-        let (gas, overflow) = Self::mult_complexity(m).overflowing_mul(max(adjusted_exp_len, 1));
-        if overflow {
-            return U256::max_value();
         }
-        (gas / self.divisor as u64).into()
     }
-}
 
-impl ModexpPricer {
     fn adjusted_exp_len(len: u64, exp_low: U256) -> u64 {
         let bit_index = if exp_low.is_zero() {
             0
@@ -228,15 +275,22 @@ impl Pricer for Blake2FPricer {
 ///
 /// Unless `is_active` is true,
 pub struct Builtin {
-    pricer: Box<dyn Pricer>,
+    /// The pricers by the block number from which they apply.
+    pricer: BTreeMap<u64, Box<dyn Pricer>>,
     native: Box<dyn Impl>,
     activate_at: u64,
 }
 
 impl Builtin {
-    /// Simple forwarder for cost.
-    pub fn cost(&self, input: &[u8]) -> U256 {
-        self.pricer.cost(input)
+    /// The cost for the given input, by the pricer in effect at the given
+    /// block number.
+    pub fn cost(&self, input: &[u8], at: u64) -> U256 {
+        let (_, pricer) = self
+            .pricer
+            .range(0..=at)
+            .last()
+            .expect("the first pricer applies from 0; qed");
+        pricer.cost(input)
     }
 
     /// Simple forwarder for execute.
@@ -250,12 +304,21 @@ impl Builtin {
     }
 
     pub fn new(pricer: Box<dyn Pricer>, native: Box<dyn Impl>, activate_at: u64) -> Builtin {
+        let mut pricers = BTreeMap::new();
+        pricers.insert(0, pricer);
         Builtin {
-            pricer,
+            pricer: pricers,
             native,
             activate_at,
         }
     }
+
+    /// Replaces the pricer from the block number `at`, e.g. for a repricing
+    /// transition.
+    pub fn with_pricer_from(mut self, at: u64, pricer: Box<dyn Pricer>) -> Builtin {
+        self.pricer.insert(at, pricer);
+        self
+    }
 }
 
 /// Built-in instruction factory.
@@ -280,7 +343,7 @@ pub fn builtin_factory(name: &str) -> Box<dyn Impl> {
 // - ec recovery
 // - sha256
 // - ripemd160
-// - modexp (EIP198)
+// - modexp (EIP198, priced by EIP2565)
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -713,7 +776,9 @@ impl Impl for Blake2FImpl {
 
 #[cfg(test)]
 mod tests {
-    use super::{builtin_factory, modexp as me, Builtin, Linear, ModexpPricer, Pricer};
+    use super::{
+        builtin_factory, modexp as me, Builtin, Eip2565ModexpPricer, Linear, ModexpPricer, Pricer,
+    };
     use crate::{builtin::Blake2FPricer, bytes::BytesRef};
     use cfx_types::U256;
     use num::{BigUint, One, Zero};
@@ -969,17 +1034,17 @@ mod tests {
 
     #[test]
     fn modexp() {
-        let f = Builtin {
-            pricer: Box::new(ModexpPricer { divisor: 20 }),
-            native: builtin_factory("modexp"),
-            activate_at: 0,
-        };
+        let f = Builtin::new(
+            Box::new(ModexpPricer { divisor: 20 }),
+            builtin_factory("modexp"),
+            0,
+        );
 
         // test for potential gas cost multiplication overflow
         {
             let input: Vec<u8> = FromHex::from_hex("0000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000003b27bafd00000000000000000000000000000000000000000000000000000000503c8ac3").unwrap();
             let expected_cost = U256::max_value();
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
 
         // test for potential exp len overflow
@@ -1002,7 +1067,7 @@ mod tests {
             f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..]))
                 .expect("Builtin should fail");
             assert_eq!(output, expected);
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
 
         // fermat's little theorem example.
@@ -1028,7 +1093,7 @@ mod tests {
             f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..]))
                 .expect("Builtin should not fail");
             assert_eq!(output, expected);
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
 
         // zero base.
@@ -1053,7 +1118,7 @@ mod tests {
             f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..]))
                 .expect("Builtin should not fail");
             assert_eq!(output, expected);
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
 
         // zero-padding
@@ -1079,7 +1144,7 @@ mod tests {
             f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..]))
                 .expect("Builtin should not fail");
             assert_eq!(output, expected);
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
 
         // zero-length modulus.
@@ -1100,18 +1165,143 @@ mod tests {
             f.execute(&input[..], &mut BytesRef::Flexible(&mut output))
                 .expect("Builtin should not fail");
             assert_eq!(output.len(), 0); // shouldn't have written any output.
-            assert_eq!(f.cost(&input[..]), expected_cost.into());
+            assert_eq!(f.cost(&input[..], 0), expected_cost.into());
         }
     }
 
     #[test]
-    fn bn128_add() {
-        let f = Builtin {
-            pricer: Box::new(Linear { base: 0, word: 0 }),
-            native: builtin_factory("alt_bn128_add"),
-            activate_at: 0,
+    fn modexp_pricer_transition() {
+        let f = Builtin::new(
+            Box::new(ModexpPricer::new(20)),
+            builtin_factory("modexp"),
+            0,
+        )
+        .with_pricer_from(100, Box::new(Eip2565ModexpPricer));
+
+        // fermat's little theorem example of EIP-198.
+        let input: Vec<u8> = FromHex::from_hex(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             03\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )
+        .unwrap();
+        assert_eq!(f.cost(&input[..], 0), U256::from(13056));
+        assert_eq!(f.cost(&input[..], 99), U256::from(13056));
+        assert_eq!(f.cost(&input[..], 100), U256::from(1360));
+        assert_eq!(f.cost(&input[..], u64::MAX), U256::from(1360));
+    }
+
+    #[test]
+    fn modexp_eip2565() {
+        let f = Builtin::new(Box::new(Eip2565ModexpPricer), builtin_factory("modexp"), 0);
+        let check = |input: &str, expected: &str, expected_cost: u64| {
+            let input: Vec<u8> = FromHex::from_hex(input).unwrap();
+            let expected: Vec<u8> = FromHex::from_hex(expected).unwrap();
+            let mut output = vec![];
+            f.execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert_eq!(output, expected);
+            assert_eq!(f.cost(&input[..], 0), U256::from(expected_cost));
         };
 
+        // fermat's little theorem example of EIP-198.
+        check(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             03\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            1360,
+        );
+
+        // zero base example of EIP-198.
+        check(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            1360,
+        );
+
+        // 0^0 % 5 is 1, at the minimum cost.
+        check(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             05",
+            "01",
+            200,
+        );
+
+        // 0^0 % 1 is 0.
+        check(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             01",
+            "00",
+            200,
+        );
+
+        // zero-length modulus returns nothing, at the minimum cost.
+        check(
+            "\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             03\
+             ffff",
+            "",
+            200,
+        );
+
+        // oversized inputs cannot be afforded.
+        {
+            let input: Vec<u8> = FromHex::from_hex(
+                "\
+                 0000000000000000000000000000000000000000000000000000000100000000\
+                 0000000000000000000000000000000000000000000000000000000000000001\
+                 0000000000000000000000000000000000000000000000000000000000000001",
+            )
+            .unwrap();
+            assert_eq!(f.cost(&input[..], 0), U256::max_value());
+        }
+
+        // a large exponent, 8 * (64 - 32) + 255 iterations of 4 words.
+        {
+            let input: Vec<u8> = FromHex::from_hex(
+                "\
+                 0000000000000000000000000000000000000000000000000000000000000020\
+                 0000000000000000000000000000000000000000000000000000000000000040\
+                 0000000000000000000000000000000000000000000000000000000000000020\
+                 0000000000000000000000000000000000000000000000000000000000000002\
+                 ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            )
+            .unwrap();
+            assert_eq!(f.cost(&input[..], 0), U256::from(16 * (8 * 32 + 255) / 3));
+        }
+    }
+
+    #[test]
+    fn bn128_add() {
+        let f = Builtin::new(
+            Box::new(Linear { base: 0, word: 0 }),
+            builtin_factory("alt_bn128_add"),
+            0,
+        );
+
         // zero-points additions
         {
             let input: Vec<u8> = FromHex::from_hex(
@@ -1174,11 +1364,11 @@ mod tests {
 
     #[test]
     fn bn128_mul() {
-        let f = Builtin {
-            pricer: Box::new(Linear { base: 0, word: 0 }),
-            native: builtin_factory("alt_bn128_mul"),
-            activate_at: 0,
-        };
+        let f = Builtin::new(
+            Box::new(Linear { base: 0, word: 0 }),
+            builtin_factory("alt_bn128_mul"),
+            0,
+        );
 
         // zero-point multiplication
         {
@@ -1221,11 +1411,11 @@ mod tests {
     }
 
     fn builtin_pairing() -> Builtin {
-        Builtin {
-            pricer: Box::new(Linear { base: 0, word: 0 }),
-            native: builtin_factory("alt_bn128_pairing"),
-            activate_at: 0,
-        }
+        Builtin::new(
+            Box::new(Linear { base: 0, word: 0 }),
+            builtin_factory("alt_bn128_pairing"),
+            0,
+        )
     }
 
     fn empty_test(f: Builtin, expected: Vec<u8>) {
@@ -1311,11 +1501,11 @@ mod tests {
     #[test]
     fn is_active() {
         let pricer = Box::new(Linear { base: 10, word: 20 });
-        let b = Builtin {
-            pricer: pricer as Box<dyn Pricer>,
-            native: builtin_factory("identity"),
-            activate_at: 100_000,
-        };
+        let b = Builtin::new(
+            pricer as Box<dyn Pricer>,
+            builtin_factory("identity"),
+            100_000,
+        );
 
         assert!(!b.is_active(99_999));
         assert!(b.is_active(100_000));
@@ -1325,16 +1515,12 @@ mod tests {
     #[test]
     fn from_named_linear() {
         let pricer = Box::new(Linear { base: 10, word: 20 });
-        let b = Builtin {
-            pricer: pricer as Box<dyn Pricer>,
-            native: builtin_factory("identity"),
-            activate_at: 1,
-        };
+        let b = Builtin::new(pricer as Box<dyn Pricer>, builtin_factory("identity"), 1);
 
-        assert_eq!(b.cost(&[0; 0]), U256::from(10));
-        assert_eq!(b.cost(&[0; 1]), U256::from(30));
-        assert_eq!(b.cost(&[0; 32]), U256::from(30));
-        assert_eq!(b.cost(&[0; 33]), U256::from(50));
+        assert_eq!(b.cost(&[0; 0], 0), U256::from(10));
+        assert_eq!(b.cost(&[0; 1], 0), U256::from(30));
+        assert_eq!(b.cost(&[0; 32], 0), U256::from(30));
+        assert_eq!(b.cost(&[0; 33], 0), U256::from(50));
 
         let i = [0u8, 1, 2, 3];
         let mut o = [255u8; 4];
@@ -1344,11 +1530,11 @@ mod tests {
     }
 
     fn blake2f_builtin() -> Builtin {
-        Builtin {
-            pricer: Box::new(Blake2FPricer::new(123)) as Box<dyn Pricer>,
-            native: builtin_factory("blake2_f"),
-            activate_at: 0,
-        }
+        Builtin::new(
+            Box::new(Blake2FPricer::new(123)) as Box<dyn Pricer>,
+            builtin_factory("blake2_f"),
+            0,
+        )
     }
 
     #[test]
@@ -1360,7 +1546,7 @@ mod tests {
         f.execute(&input[..], &mut BytesRef::Fixed(&mut output[..]))
             .unwrap();
 
-        assert_eq!(f.cost(&input[..]), U256::from(123 * 5));
+        assert_eq!(f.cost(&input[..], 0), U256::from(123 * 5));
     }
 
     #[test]
@@ -1369,7 +1555,7 @@ mod tests {
         // invalid input (too short)
         let input: Vec<u8> = FromHex::from_hex("00").unwrap();
 
-        assert_eq!(f.cost(&input[..]), U256::from(0));
+        assert_eq!(f.cost(&input[..], 0), U256::from(0));
     }
 
     #[test]
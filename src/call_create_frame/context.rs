@@ -5,6 +5,7 @@
 // Transaction execution environment.
 use crate::{
     bytes::Bytes,
+    hash::KECCAK_EMPTY,
     internal_contract::{suicide as suicide_impl, InternalRefContext},
     machine::Machine,
     observer::VmObserve,
@@ -116,6 +117,16 @@ impl<'a, 'b> FrameContext<'a> {
     }
 }
 
+impl<'a, 'b> Context<'a, 'b> {
+    /// Whether the code of the contract is hidden because it has been killed
+    /// in the transaction, although the contract is removed only at the end
+    /// of the transaction.
+    fn killed_in_transaction(&self, address: &AddressWithSpace) -> bool {
+        self.local_part.spec.killed_contract_no_code
+            && self.callstack.suicided_in_transaction(address)
+    }
+}
+
 impl<'a, 'b> ContextTrait for Context<'a, 'b> {
     fn storage_at(&self, key: &Vec<u8>) -> vm::Result<U256> {
         let caller = AddressWithSpace {
@@ -131,12 +142,65 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             space: self.local_part.space,
         };
         if self.is_static() {
-            Err(vm::Error::MutableCallInStaticContext)
-        } else {
-            self.state
-                .set_storage(&caller, key, value)
-                .map_err(Into::into)
+            return Err(vm::Error::MutableCallInStaticContext);
+        }
+        let old_value = self.state.storage_at(&caller, &key)?;
+        if let Some(max_entries) = self.local_part.spec.max_storage_entries_per_account {
+            if !value.is_zero()
+                && old_value.is_zero()
+                && self.state.storage_entry_count(&caller)? >= max_entries
+            {
+                return Err(vm::Error::ExceedStorageEntryLimit(caller.address));
+            }
+        }
+        if self.local_part.spec.eip3529 && old_value != value {
+            // EIP-2200 refund accounting with the EIP-3529 clearing refund.
+            let original_value = self.callstack.original_storage_at(&caller, &key, old_value);
+            let refund = self.local_part.spec.sstore_refund_gas;
+            let substate = &mut self.local_part.substate;
+            if !original_value.is_zero() {
+                if old_value.is_zero() {
+                    // The slot cleared earlier in the transaction is set
+                    // again.
+                    substate.sub_refund(&caller, &key, refund);
+                } else if value.is_zero() {
+                    substate.add_refund(&caller, &key, refund);
+                }
+            }
         }
+        self.state
+            .set_storage(&caller, key, value)
+            .map_err(Into::into)
+    }
+
+    fn mark_storage_accessed(&mut self, key: &[u8]) {
+        let caller = AddressWithSpace {
+            address: self.local_part.origin.address,
+            space: self.local_part.space,
+        };
+        self.local_part
+            .substate
+            .accessed_storage_keys
+            .insert((caller, H256::from_slice(key)));
+    }
+
+    fn is_storage_warm(&self, key: &[u8]) -> bool {
+        let caller = AddressWithSpace {
+            address: self.local_part.origin.address,
+            space: self.local_part.space,
+        };
+        self.local_part
+            .substate
+            .accessed_storage_keys
+            .contains(&(caller, H256::from_slice(key)))
+    }
+
+    fn mark_address_accessed(&mut self, address: &Address) {
+        let address = AddressWithSpace {
+            address: *address,
+            space: self.local_part.space,
+        };
+        self.local_part.substate.accessed_addresses.insert(address);
     }
 
     fn exists(&self, address: &Address) -> vm::Result<bool> {
@@ -198,13 +262,15 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             space: self.local_part.space,
         };
 
+        let caller_nonce = self.state.nonce(&caller)?;
+
         let create_type = CreateType::from_address_scheme(&address_scheme);
         // create new contract address
         let (address_with_space, code_hash) = contract_address(
             address_scheme,
             self.local_part.env.number.into(),
             &caller,
-            &self.state.nonce(&caller)?,
+            &caller_nonce,
             &code,
         );
 
@@ -242,13 +308,22 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
 
         if !self.is_static() {
             if !self.local_part.spec.keep_unsigned_nonce || params.sender != UNSIGNED_SENDER {
-                self.state.inc_nonce(
+                let inc_nonce = self.state.inc_nonce(
                     &caller,
                     // The sender of a CREATE call is guaranteed to exist,
                     // therefore the start_nonce below
                     // doesn't matter.
                     &self.local_part.spec.contract_start_nonce,
-                )?;
+                    self.local_part.spec.eip2681,
+                );
+                match inc_nonce {
+                    Err(cfx_statedb::Error(cfx_statedb::ErrorKind::NonceOverflow(address), _)) => {
+                        debug!("Contract nonce overflow!");
+                        let err = vm::Error::NonceOverflow(address);
+                        return Ok(Ok(ContractCreateResult::Failed(err)));
+                    }
+                    result => result?,
+                }
             }
         }
 
@@ -276,6 +351,8 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             .contract(&code_address_with_space, self.local_part.spec)
         {
             (Some(contract.code()), Some(contract.code_hash()))
+        } else if self.killed_in_transaction(&code_address_with_space) {
+            (None, Some(KECCAK_EMPTY))
         } else {
             (
                 self.state.code(&code_address_with_space)?,
@@ -316,6 +393,8 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             .contract(&address, self.local_part.spec)
         {
             Ok(Some(contract.code()))
+        } else if self.killed_in_transaction(&address) {
+            Ok(Some(Arc::new(Bytes::new())))
         } else {
             Ok(self.state.code(&address)?)
         }
@@ -331,6 +410,8 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             .contract(&address, self.local_part.spec)
         {
             Ok(Some(contract.code_hash()))
+        } else if self.killed_in_transaction(&address) {
+            Ok(Some(KECCAK_EMPTY))
         } else {
             Ok(self.state.code_hash(&address)?)
         }
@@ -346,6 +427,8 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             .contract(&address, self.local_part.spec)
         {
             Ok(Some(contract.code_size()))
+        } else if self.killed_in_transaction(&address) {
+            Ok(Some(0))
         } else {
             Ok(self.state.code_size(&address)?)
         }
@@ -414,19 +497,31 @@ impl<'a, 'b> ContextTrait for Context<'a, 'b> {
             return Err(vm::Error::MutableCallInStaticContext);
         }
 
+        let contract_address = self
+            .local_part
+            .origin
+            .address
+            .with_space(self.local_part.space);
+        let created_in_transaction = self.callstack.created_in_transaction(&contract_address);
         suicide_impl(
-            &self
-                .local_part
-                .origin
-                .address
-                .with_space(self.local_part.space),
+            &contract_address,
             &refund_address.with_space(self.local_part.space),
             self.state.as_mut_state_ops(),
             &self.local_part.spec,
             &mut self.local_part.substate,
             tracer,
             account_start_nonce,
-        )
+            created_in_transaction,
+        )?;
+        if self
+            .local_part
+            .substate
+            .suicides
+            .contains(&contract_address)
+        {
+            self.callstack.record_suicide(contract_address);
+        }
+        Ok(())
     }
 
     fn spec(&self) -> &Spec {
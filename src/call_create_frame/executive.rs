@@ -2,6 +2,7 @@ use crate::{
     builtin::Builtin,
     evm::{CallType, Context, GasLeft, ReturnData},
     internal_contract::InternalContractTrait,
+    machine::Machine,
     observer::VmObserve,
     vm::{ActionParams, Error as VmError, Exec, ExecTrapResult, TrapResult},
 };
@@ -23,6 +24,7 @@ impl Exec for NoopExec {
 }
 pub struct BuiltinExec<'a> {
     pub builtin: &'a Builtin,
+    pub machine: &'a Machine,
     pub params: ActionParams,
 }
 
@@ -30,7 +32,7 @@ impl<'a> Exec for BuiltinExec<'a> {
     // Copied from exec function of CallCreateExecutive.
     fn exec(
         self: Box<Self>,
-        _: &mut dyn Context,
+        context: &mut dyn Context,
         _: &mut dyn VmObserve,
     ) -> ExecTrapResult<GasLeft> {
         let default = [];
@@ -40,7 +42,12 @@ impl<'a> Exec for BuiltinExec<'a> {
             &default as &[u8]
         };
 
-        let cost = self.builtin.cost(data);
+        let cost = self.machine.builtin_gas(
+            &self.params.code_address,
+            self.builtin,
+            data,
+            context.env().number,
+        );
         let output = if cost <= self.params.gas {
             let mut builtin_out_buffer = Vec::new();
             let result = {
@@ -244,6 +244,12 @@ impl<'a> CallCreateFrame<'a> {
         substate: &mut Substate,
         account_start_nonce: U256,
     ) -> DbResult<()> {
+        // Like `DELEGATECALL`, `CALLCODE` runs in the context of the caller,
+        // so its value moves no balance but is still exposed by `CALLVALUE`.
+        if params.call_type == CallType::CallCode {
+            return Ok(());
+        }
+
         let sender = AddressWithSpace {
             address: params.sender,
             space: params.space,
@@ -285,11 +291,10 @@ impl<'a> CallCreateFrame<'a> {
             // contract address.
             let prev_balance = state.balance(&receiver)?;
             state.sub_balance(&sender, &val, &mut cleanup_mode(substate, &spec))?;
-            let nonce = U256::from(1);
             state.new_contract(
                 &receiver,
                 val.saturating_add(prev_balance),
-                nonce,
+                spec.contract_start_nonce,
                 storage_layout,
             )?;
         } else {
@@ -313,6 +318,13 @@ impl<'a> CallCreateFrame<'a> {
         callstack: &mut FrameStackInfo,
         tracer: &mut dyn VmObserve,
     ) -> DbResult<vm::Result<FrameReturn>> {
+        let result = match (result, self.context.spec.max_return_data_size) {
+            (Ok(GasLeft::NeedsReturn { data, .. }), Some(max_size)) if data.len() > max_size => {
+                Err(vm::Error::OutOfGas)
+            }
+            (result, _) => result,
+        };
+
         let context = self.context.activate(state, callstack);
         // The post execution task in spec is completed here.
         let finalized_result = result.finalize(context);
@@ -336,6 +348,7 @@ impl<'a> CallCreateFrame<'a> {
         } else {
             maybe_substate = None;
             state.revert_to_checkpoint();
+            callstack.revert_suicides();
         }
 
         let executive_result = finalized_result
@@ -352,6 +365,18 @@ impl<'a> CallCreateFrame<'a> {
         Ok(executive_result)
     }
 
+    /// Terminates a frame waiting for its sub-call with `error`, reverting
+    /// all its changes.
+    pub fn abort(
+        self,
+        error: vm::Error,
+        state: &mut dyn StateTrait,
+        callstack: &mut FrameStackInfo,
+        tracer: &mut dyn VmObserve,
+    ) -> DbResult<vm::Result<FrameReturn>> {
+        self.process_return(Err(error), state, callstack, tracer)
+    }
+
     /// If the executive triggers a sub-call during execution, this function
     /// outputs a trap error with sub-call parameters and return point.
     fn process_trap(mut self, trap_err: ExecTrapError) -> FrameTrapError<'a> {
@@ -411,6 +436,20 @@ impl<'a> CallCreateFrame<'a> {
 
         // Pre execution: transfer value and init contract.
         let spec = self.context.spec;
+        if is_create && spec.strict_create_collision {
+            let address = params.address.with_space(params.space);
+            let state_ops = state.as_state_ops();
+            if !state_ops.nonce(&address)?.is_zero()
+                || state_ops.is_contract_with_code(&address)?
+                || !state_ops.balance(&address)?.is_zero()
+            {
+                debug!("Contract address conflict!");
+                let err = vm::Error::ConflictAddress(params.address);
+                let result = self.process_return(Err(err), state, callstack, tracer)?;
+                return Ok(TrapResult::Return(result));
+            }
+        }
+
         if is_create {
             Self::transfer_exec_balance_and_init_contract(
                 &params,
@@ -433,7 +472,11 @@ impl<'a> CallCreateFrame<'a> {
         // Fetch execution model and execute
         let exec: Box<dyn Exec> = match self.kind {
             FrameKind::Transfer => Box::new(NoopExec { gas: params.gas }),
-            FrameKind::CallBuiltin(builtin) => Box::new(BuiltinExec { builtin, params }),
+            FrameKind::CallBuiltin(builtin) => Box::new(BuiltinExec {
+                builtin,
+                machine: self.context.machine,
+                params,
+            }),
             FrameKind::CallInternalContract(internal) => {
                 Box::new(InternalContractExec { internal, params })
             }
@@ -506,7 +549,7 @@ impl<'a> CallCreateFrame<'a> {
 
     /// Output callee executive and caller executive from trap kind error.
     pub fn from_trap_error(trap_err: FrameTrapError<'a>) -> (Self, Self) {
-        match trap_err {
+        let (mut callee, caller) = match trap_err {
             TrapError::Call(params, parent) => (
                 /* callee */
                 CallCreateFrame::new_call_raw(
@@ -533,7 +576,13 @@ impl<'a> CallCreateFrame<'a> {
                 ),
                 /* callee */ parent,
             ),
+        };
+        if caller.context.spec.eip2929 {
+            callee
+                .unconfirmed_substate()
+                .inherit_accessed(&caller.context.substate);
         }
+        (callee, caller)
     }
 }
 
@@ -21,6 +21,8 @@ pub struct FrameStack<'a> {
     tx_substate: Substate,
     observer: Observer,
     base_gas_required: u64,
+    max_sub_calls: Option<u64>,
+    sub_calls: u64,
 }
 
 pub struct FrameStackOutput {
@@ -51,6 +53,7 @@ impl<'a> FrameStack<'a> {
         top_substate: Substate,
         observer: Observer,
         base_gas_required: u64,
+        max_sub_calls: Option<u64>,
     ) -> Self {
         FrameStack {
             state,
@@ -59,6 +62,8 @@ impl<'a> FrameStack<'a> {
             tx_substate: top_substate,
             observer,
             base_gas_required,
+            max_sub_calls,
+            sub_calls: 0,
         }
     }
 
@@ -115,6 +120,11 @@ impl<'a> FrameStack<'a> {
                     let (callee, caller) = CallCreateFrame::from_trap_error(trap_err);
                     self.frame_stack.push(caller);
 
+                    self.sub_calls += 1;
+                    if self.max_sub_calls.map_or(false, |max| self.sub_calls > max) {
+                        return self.abort_on_sub_call_limit();
+                    }
+
                     callee.exec(
                         self.state,
                         &mut self.callstack,
@@ -125,6 +135,24 @@ impl<'a> FrameStack<'a> {
         }
     }
 
+    /// Aborts all the frames waiting for their sub-calls, failing the
+    /// transaction with `ExceedSubCallLimit`.
+    fn abort_on_sub_call_limit(mut self) -> DbResult<FrameStackOutput> {
+        while let Some(frame) = self.frame_stack.pop() {
+            // Only the result of the top frame is kept.
+            let result = frame.abort(
+                vm::Error::ExceedSubCallLimit,
+                self.state,
+                &mut self.callstack,
+                &mut *self.observer.as_vm_observe(),
+            )?;
+            if self.frame_stack.is_empty() {
+                return Ok(self.process_return(result));
+            }
+        }
+        unreachable!("The frame stack is not empty on sub-calls")
+    }
+
     fn process_return(mut self, mut result: vm::Result<FrameReturn>) -> FrameStackOutput {
         accrue_substate(&mut self.tx_substate, &mut result);
         return FrameStackOutput {
@@ -19,7 +19,7 @@
 // See http://www.gnu.org/licenses/
 
 use super::u256_to_address;
-use cfx_types::{Space, U256};
+use cfx_types::U256;
 use std::cmp;
 
 use super::{
@@ -51,6 +51,9 @@ pub struct InstructionRequirements<Cost> {
     pub provide_gas: Option<Cost>,
     pub memory_total_gas: Cost,
     pub memory_required_size: usize,
+    /// The value of the slot written by `SSTORE` before the write, read to
+    /// price it.
+    pub storage_value: Option<U256>,
 }
 
 pub struct Gasometer<Gas> {
@@ -127,11 +130,13 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
         current_mem_size: usize,
     ) -> vm::Result<InstructionRequirements<Gas>> {
         let spec = context.spec();
+        let gas_model = spec.gas_model();
         let tier = info.tier.idx();
-        let default_gas = Gas::from(spec.tier_step_gas[tier]);
+        let default_gas = Gas::from(gas_model.tier_gas(spec, tier));
+        let mut storage_value = None;
 
         let cost = match instruction {
-            instructions::JUMPDEST => Request::Gas(Gas::from(1)),
+            instructions::JUMPDEST => Request::Gas(Gas::from(gas_model.jumpdest_gas(spec))),
             instructions::SSTORE => {
                 let gas = {
                     let mut key = vec![0; 32];
@@ -139,41 +144,35 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 
                     let newval = stack.peek(1);
                     let val = context.storage_at(&key.to_vec())?;
+                    storage_value = Some(val);
 
-                    if val.is_zero() && !newval.is_zero() {
-                        spec.sstore_set_gas * spec.evm_gas_ratio
-                    } else {
-                        spec.sstore_reset_gas
-                    }
+                    gas_model.sstore_gas(spec, &val, newval)?
                 };
 
                 Request::Gas(Gas::from(gas))
             }
-            instructions::SLOAD => Request::Gas(Gas::from(spec.sload_gas)),
-            instructions::BALANCE => Request::Gas(Gas::from(spec.balance_gas)),
-            instructions::EXTCODESIZE => Request::Gas(Gas::from(spec.extcodesize_gas)),
-            instructions::EXTCODEHASH => Request::Gas(Gas::from(spec.extcodehash_gas)),
+            instructions::SLOAD => {
+                let mut key = vec![0; 32];
+                stack.peek(0).to_big_endian(key.as_mut());
+                let is_warm = context.is_storage_warm(&key);
+                Request::Gas(Gas::from(gas_model.sload_gas(spec, is_warm)))
+            }
+            instructions::BALANCE => Request::Gas(Gas::from(gas_model.balance_gas(spec))),
+            instructions::EXTCODESIZE => Request::Gas(Gas::from(gas_model.extcodesize_gas(spec))),
+            instructions::EXTCODEHASH => Request::Gas(Gas::from(gas_model.extcodehash_gas(spec))),
             instructions::SUICIDE => {
-                let mut gas = Gas::from(spec.suicide_gas);
-
                 let is_value_transfer = !context.origin_balance()?.is_zero();
                 let address = u256_to_address(stack.peek(0));
-                if (!spec.no_empty && !context.exists(&address)?)
+                let to_new_account = (!spec.no_empty && !context.exists(&address)?)
                     || (spec.no_empty
                         && is_value_transfer
-                        && !context.exists_and_not_null(&address)?)
-                {
-                    let ratio = if context.space() == Space::Ethereum {
-                        spec.evm_gas_ratio
-                    } else {
-                        1
-                    };
-                    gas = overflowing!(
-                        gas.overflow_add((spec.suicide_to_new_account_cost * ratio).into())
-                    );
-                }
+                        && !context.exists_and_not_null(&address)?);
 
-                Request::Gas(gas)
+                Request::Gas(Gas::from(gas_model.suicide_gas(
+                    spec,
+                    context.space(),
+                    to_new_account,
+                )?))
             }
             instructions::MSTORE | instructions::MLOAD => {
                 Request::GasMem(default_gas, mem_needed_const(stack.peek(0), 32)?)
@@ -185,12 +184,10 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 Request::GasMem(default_gas, mem_needed(stack.peek(0), stack.peek(1))?)
             }
             instructions::SHA3 => {
+                let (sha3_gas, sha3_word_gas) = gas_model.sha3_gas(spec);
                 let words = overflowing!(to_word_size(Gas::from_u256(*stack.peek(1))?));
-                let gas =
-                    overflowing!(Gas::from(spec.sha3_gas)
-                        .overflow_add(overflowing!(
-                            Gas::from(spec.sha3_word_gas).overflow_mul(words)
-                        )));
+                let gas = overflowing!(Gas::from(sha3_gas)
+                    .overflow_add(overflowing!(Gas::from(sha3_word_gas).overflow_mul(words))));
                 Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
             }
             instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => {
@@ -201,7 +198,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 )
             }
             instructions::EXTCODECOPY => Request::GasMemCopy(
-                spec.extcodecopy_base_gas.into(),
+                gas_model.extcodecopy_base_gas(spec).into(),
                 mem_needed(stack.peek(1), stack.peek(3))?,
                 Gas::from_u256(*stack.peek(3))?,
             ),
@@ -213,16 +210,15 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let no_of_topics = instruction
                     .log_topics()
                     .expect("log_topics always return some for LOG* instructions; qed");
-                let log_gas = spec.log_gas + spec.log_topic_gas * no_of_topics;
+                let (log_gas, log_data_gas) = gas_model.log_gas(spec, no_of_topics)?;
 
                 let data_gas = overflowing!(
-                    Gas::from_u256(*stack.peek(1))?.overflow_mul(Gas::from(spec.log_data_gas))
+                    Gas::from_u256(*stack.peek(1))?.overflow_mul(Gas::from(log_data_gas))
                 );
                 let gas = overflowing!(data_gas.overflow_add(Gas::from(log_gas)));
                 Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
             }
             instructions::CALL | instructions::CALLCODE => {
-                let mut gas = Gas::from(spec.call_gas);
                 let mem = cmp::max(
                     mem_needed(stack.peek(5), stack.peek(6))?,
                     mem_needed(stack.peek(3), stack.peek(4))?,
@@ -231,31 +227,25 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let address = u256_to_address(stack.peek(1));
                 let is_value_transfer = !stack.peek(2).is_zero();
 
-                if instruction == instructions::CALL
+                let to_new_account = instruction == instructions::CALL
                     && ((!spec.no_empty && !context.exists(&address)?)
                         || (spec.no_empty
                             && is_value_transfer
-                            && !context.exists_and_not_null(&address)?))
-                {
-                    let ratio = if context.space() == Space::Ethereum {
-                        spec.evm_gas_ratio
-                    } else {
-                        1
-                    };
-                    gas =
-                        overflowing!(gas.overflow_add((spec.call_new_account_gas * ratio).into()));
-                }
+                            && !context.exists_and_not_null(&address)?));
 
-                if is_value_transfer {
-                    gas = overflowing!(gas.overflow_add(spec.call_value_transfer_gas.into()));
-                }
+                let gas = Gas::from(gas_model.call_gas(
+                    spec,
+                    context.space(),
+                    to_new_account,
+                    is_value_transfer,
+                )?);
 
                 let requested = *stack.peek(0);
 
                 Request::GasMemProvide(gas, mem, Some(requested))
             }
             instructions::DELEGATECALL | instructions::STATICCALL => {
-                let gas = Gas::from(spec.call_gas);
+                let gas = Gas::from(gas_model.call_gas(spec, context.space(), false, false)?);
                 let mem = cmp::max(
                     mem_needed(stack.peek(4), stack.peek(5))?,
                     mem_needed(stack.peek(2), stack.peek(3))?,
@@ -267,12 +257,14 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
             instructions::CREATE | instructions::CREATE2 => {
                 let start = stack.peek(1);
                 let len = stack.peek(2);
-                let base = Gas::from(spec.create_gas);
+                let (create_gas, create_word_gas) = gas_model.create_gas(
+                    spec,
+                    context.space(),
+                    instruction == instructions::CREATE2,
+                );
+                let base = Gas::from(create_gas);
                 let word = overflowing!(to_word_size(Gas::from_u256(*len)?));
-                let mut word_gas = overflowing!(Gas::from(spec.sha3_word_gas).overflow_mul(word));
-                if instruction == instructions::CREATE && context.space() == Space::Ethereum {
-                    word_gas = Gas::from(0);
-                }
+                let word_gas = overflowing!(Gas::from(create_word_gas).overflow_mul(word));
                 let gas = overflowing!(base.overflow_add(word_gas));
                 let mem = mem_needed(start, len)?;
 
@@ -281,10 +273,10 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
             instructions::EXP => {
                 let expon = stack.peek(1);
                 let bytes = ((expon.bits() + 7) / 8) as usize;
-                let gas = Gas::from(spec.exp_gas + spec.exp_byte_gas * bytes);
+                let gas = Gas::from(gas_model.exp_gas(spec, bytes)?);
                 Request::Gas(gas)
             }
-            instructions::BLOCKHASH => Request::Gas(Gas::from(spec.blockhash_gas)),
+            instructions::BLOCKHASH => Request::Gas(Gas::from(gas_model.blockhash_gas(spec))),
             _ => Request::Gas(default_gas),
         };
 
@@ -294,6 +286,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 provide_gas: None,
                 memory_required_size: 0,
                 memory_total_gas: self.current_mem_gas,
+                storage_value,
             },
             Request::GasMem(gas, mem_size) => {
                 let (mem_gas_cost, new_mem_gas, new_mem_size) =
@@ -304,6 +297,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     provide_gas: None,
                     memory_required_size: new_mem_size,
                     memory_total_gas: new_mem_gas,
+                    storage_value,
                 }
             }
             Request::GasMemProvide(gas, mem_size, requested) => {
@@ -318,13 +312,15 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     provide_gas: Some(provided),
                     memory_required_size: new_mem_size,
                     memory_total_gas: new_mem_gas,
+                    storage_value,
                 }
             }
             Request::GasMemCopy(gas, mem_size, copy) => {
                 let (mem_gas_cost, new_mem_gas, new_mem_size) =
                     self.mem_gas_cost(spec, current_mem_size, &mem_size)?;
                 let copy = overflowing!(to_word_size(copy));
-                let copy_gas = overflowing!(Gas::from(spec.copy_gas).overflow_mul(copy));
+                let copy_gas =
+                    overflowing!(Gas::from(spec.gas_model().copy_gas(spec)).overflow_mul(copy));
                 let gas = overflowing!(gas.overflow_add(copy_gas));
                 let gas = overflowing!(gas.overflow_add(mem_gas_cost));
 
@@ -333,6 +329,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     provide_gas: None,
                     memory_required_size: new_mem_size,
                     memory_total_gas: new_mem_gas,
+                    storage_value,
                 }
             }
         })
@@ -347,7 +344,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
         let gas_for_mem = |mem_size: Gas| {
             let s = mem_size >> 5;
             // s * memory_gas + s * s / quad_coeff_div
-            let a = overflowing!(s.overflow_mul(Gas::from(spec.memory_gas)));
+            let a = overflowing!(s.overflow_mul(Gas::from(spec.gas_model().memory_gas(spec))));
 
             // Calculate s*s/quad_coeff_div
             assert_eq!(spec.quad_coeff_div, 512);
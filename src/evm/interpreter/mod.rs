@@ -44,7 +44,7 @@ use super::{
 use crate::{
     bytes::Bytes,
     hash::keccak,
-    observer::VmObserve,
+    observer::{OpcodeStep, VmObserve},
     vm::{
         self, ActionParams, ActionValue, CallType, ContractCreateResult, CreateContractAddress,
         GasLeft, MessageCallResult, ParamsType, ReturnData, Spec, TrapError, TrapKind,
@@ -55,6 +55,8 @@ use cfx_types::{Address, BigEndianHash, Space, H256, U256, U512};
 use std::{cmp, convert::TryFrom, marker::PhantomData, mem, sync::Arc};
 
 const GASOMETER_PROOF: &str = "If gasometer is None, Err is immediately returned in step; this function is only called by step; qed";
+const STORAGE_VALUE_PROOF: &str =
+    "The gasometer reads the storage value to price every SSTORE; qed";
 
 type ProgramCounter = usize;
 
@@ -428,6 +430,23 @@ impl<Cost: CostType> Interpreter<Cost> {
                     Ok(t) => t,
                     Err(e) => return InterpreterResult::Done(Err(e)),
                 };
+                if tracer.wants_opcodes() {
+                    tracer.record_opcode(&OpcodeStep {
+                        pc: self.reader.position - 1,
+                        opcode,
+                        op: info.name,
+                        gas: self
+                            .gasometer
+                            .as_ref()
+                            .expect(GASOMETER_PROOF)
+                            .current_gas
+                            .as_u256(),
+                        gas_cost: requirements.gas_cost.as_u256(),
+                        depth: context.depth(),
+                        stack: self.stack.peek_top(self.stack.size()),
+                        memory: &self.mem,
+                    });
+                }
                 if self.do_trace {
                     context.trace_prepare_execute(
                         self.reader.position - 1,
@@ -472,6 +491,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                     context,
                     instruction,
                     requirements.provide_gas,
+                    requirements.storage_value,
                     tracer,
                 ) {
                     Err(x) => {
@@ -649,6 +669,7 @@ impl<Cost: CostType> Interpreter<Cost> {
         context: &mut dyn vm::Context,
         instruction: Instruction,
         provided: Option<Cost>,
+        storage_value: Option<U256>,
         tracer: &mut dyn VmObserve,
     ) -> vm::Result<InstructionResult<Cost>> {
         trace!("exec instruction: {:?}", instruction);
@@ -765,6 +786,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                 let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
                 let code_address = self.stack.pop_back();
                 let code_address = u256_to_address(&code_address);
+                context.mark_address_accessed(&code_address);
 
                 let value = if instruction == instructions::DELEGATECALL {
                     None
@@ -914,6 +936,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::SUICIDE => {
                 let address = self.stack.pop_back();
                 let refund_address = u256_to_address(&address);
+                context.mark_address_accessed(&refund_address);
                 context.suicide(&refund_address, tracer, context.spec().account_start_nonce)?;
                 return Ok(InstructionResult::StopExecution);
             }
@@ -1001,6 +1024,8 @@ impl<Cost: CostType> Interpreter<Cost> {
                 let mut key = vec![0; 32];
                 self.stack.pop_back().to_big_endian(key.as_mut());
                 let word = context.storage_at(&key)?;
+                context.mark_storage_accessed(&key);
+                tracer.record_storage_read(&self.params.address, &key, &word);
                 self.stack.push(word);
             }
             instructions::SSTORE => {
@@ -1008,7 +1033,16 @@ impl<Cost: CostType> Interpreter<Cost> {
                 self.stack.pop_back().to_big_endian(key.as_mut());
                 let val = self.stack.pop_back();
 
-                context.set_storage(key, val)?;
+                let old_val = storage_value.expect(STORAGE_VALUE_PROOF);
+                context.set_storage(key.clone(), val)?;
+                context.mark_storage_accessed(&key);
+                tracer.record_storage_write(
+                    &self.params.address,
+                    &self.params.code_address,
+                    &key,
+                    &old_val,
+                    &val,
+                );
             }
             instructions::PC => {
                 self.stack.push(U256::from(self.reader.position - 1));
@@ -1026,6 +1060,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             }
             instructions::BALANCE => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.mark_address_accessed(&address);
                 let balance = context.balance(&address)?;
                 self.stack.push(balance);
             }
@@ -1064,11 +1099,13 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::RETURNDATASIZE => self.stack.push(U256::from(self.return_data.len())),
             instructions::EXTCODESIZE => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.mark_address_accessed(&address);
                 let len = context.extcodesize(&address)?.unwrap_or(0);
                 self.stack.push(U256::from(len));
             }
             instructions::EXTCODEHASH => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.mark_address_accessed(&address);
                 let hash = context.extcodehash(&address)?.unwrap_or_else(H256::zero);
                 self.stack.push(hash.into_uint());
             }
@@ -1099,6 +1136,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             }
             instructions::EXTCODECOPY => {
                 let address = u256_to_address(&self.stack.pop_back());
+                context.mark_address_accessed(&address);
                 let code = context.extcode(&address)?;
                 Self::copy_data_to_memory(
                     &mut self.mem,
@@ -1128,7 +1166,12 @@ impl<Cost: CostType> Interpreter<Cost> {
                 self.stack.push(U256::from(block_number));
             }
             instructions::DIFFICULTY => {
-                self.stack.push(context.env().difficulty.clone());
+                if context.spec().is_merge_active() {
+                    // PREVRANDAO
+                    self.stack.push(context.env().random.into_uint());
+                } else {
+                    self.stack.push(context.env().difficulty.clone());
+                }
             }
             instructions::GASLIMIT => {
                 self.stack.push(context.env().gas_limit.clone());
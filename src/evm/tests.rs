@@ -484,6 +484,35 @@ fn test_difficulty(factory: super::Factory) {
     );
 }
 
+evm_test! {test_prevrandao: test_prevrandao_int}
+fn test_prevrandao(factory: super::Factory) {
+    let code = "44600055".from_hex().unwrap();
+
+    for merge in [false, true] {
+        let mut params = ActionParams::default();
+        params.gas = U256::from(100_000);
+        params.code = Some(Arc::new(code.clone()));
+        let mut ctx = MockContext::new();
+        let mut tracer = ();
+        ctx.spec.eip4399 = merge;
+        ctx.env.difficulty = U256::from(0x1234);
+        ctx.env.random = H256::from_low_u64_be(0x5678);
+
+        let gas_left = {
+            let vm = factory.create(params, ctx.spec(), ctx.depth());
+            test_finalize(vm.exec(&mut ctx, &mut tracer).ok().unwrap()).unwrap()
+        };
+
+        assert_eq!(gas_left, U256::from(94_995));
+        let expected = if merge {
+            "0000000000000000000000000000000000000000000000000000000000005678"
+        } else {
+            "0000000000000000000000000000000000000000000000000000000000001234"
+        };
+        assert_store(&ctx, 0, expected);
+    }
+}
+
 evm_test! {test_gas_limit: test_gas_limit_int}
 fn test_gas_limit(factory: super::Factory) {
     let gas_limit = U256::from(0x1234);
@@ -0,0 +1,85 @@
+use super::{
+    executed::ExecutionOutcome, transaction_info::TransactionInfo, TXExecutor,
+    TransactCheckSettings, TransactOptions,
+};
+use crate::{
+    call_create_frame::contract_address, observer::MultiObservers as Observer,
+    vm::CreateContractAddress,
+};
+
+use cfx_statedb::Result as DbResult;
+use cfx_types::{Address, H256};
+use primitives::Action;
+use std::collections::BTreeMap;
+
+impl<'a> TXExecutor<'a> {
+    /// Executes the transaction without charging gas and returns the
+    /// accounts and storage slots it accesses, in ascending order like
+    /// `eth_createAccessList`. The state is left unchanged.
+    ///
+    /// An account accessed without touching its storage appears with no
+    /// keys. The sender, the callee or the created contract and the
+    /// precompiles are always warm, so they appear only for their storage
+    /// keys.
+    pub fn create_access_list(
+        &mut self,
+        tx: &impl TransactionInfo,
+    ) -> DbResult<Vec<(Address, Vec<H256>)>> {
+        let options = TransactOptions {
+            observer: Observer::with_no_tracing(),
+            check_settings: TransactCheckSettings {
+                charge_gas: false,
+                real_execution: false,
+                override_init_gas: None,
+                verify_signature: false,
+                reject_zero_gas_price: false,
+                bump_nonce_on_not_enough_cash: true,
+            },
+            state_overrides: Default::default(),
+            state_diff: false,
+        };
+
+        self.state.checkpoint();
+        let outcome = self.transact(tx, options);
+        self.state.revert_to_checkpoint();
+
+        let (accessed_addresses, accessed_storage_keys) = match outcome? {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => {
+                (executed.accessed_addresses, executed.accessed_storage_keys)
+            }
+            _ => (vec![], vec![]),
+        };
+
+        let sender = tx.sender();
+        let recipient = match &*tx.action() {
+            Action::Call(address) => *address,
+            Action::Create => {
+                contract_address(
+                    CreateContractAddress::FromSenderNonce,
+                    /* block_number = */ 0.into(),
+                    &sender,
+                    &tx.nonce(),
+                    &tx.data(),
+                )
+                .0
+                .address
+            }
+        };
+        let always_warm = |address: &Address| {
+            *address == sender.address
+                || *address == recipient
+                || self.machine.builtins().contains_key(address)
+        };
+
+        let mut access_list: BTreeMap<Address, Vec<H256>> = accessed_addresses
+            .into_iter()
+            .filter(|address| !always_warm(address))
+            .map(|address| (address, vec![]))
+            .collect();
+        for (address, key) in accessed_storage_keys {
+            access_list.entry(address).or_default().push(key);
+        }
+        Ok(access_list.into_iter().collect())
+    }
+}
@@ -0,0 +1,38 @@
+use super::ExecutionOutcome;
+use cfx_types::{AddressWithSpace, U256};
+
+/// The aggregate of the outcomes of the transactions in a block.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BlockSummary {
+    /// The gas charged to the senders, as in the receipts.
+    pub total_gas_used: U256,
+    pub total_logs: usize,
+    /// The contracts created by the transactions, in the order of the
+    /// transactions.
+    pub contracts_created: Vec<AddressWithSpace>,
+    /// The number of transactions executed with an error, e.g. a revert.
+    pub reverted_count: usize,
+}
+
+/// Summarizes the outcomes of the transactions in a block. The transactions
+/// not executed are skipped.
+pub fn summarize_block(outcomes: &[ExecutionOutcome]) -> BlockSummary {
+    let mut summary = BlockSummary::default();
+    for outcome in outcomes {
+        let executed = match outcome {
+            ExecutionOutcome::Finished(executed) => executed,
+            ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => {
+                summary.reverted_count += 1;
+                executed
+            }
+            ExecutionOutcome::NotExecutedDrop(_)
+            | ExecutionOutcome::NotExecutedToReconsiderPacking(_) => continue,
+        };
+        summary.total_gas_used += executed.gas_charged;
+        summary.total_logs += executed.logs.len();
+        summary
+            .contracts_created
+            .extend_from_slice(&executed.contracts_created);
+    }
+    summary
+}
@@ -1,6 +1,7 @@
 use super::executed::{ExecutionError, ExecutionOutcome};
 use super::TXExecutor;
-use super::TransactOptions;
+use super::{TransactCheckSettings, TransactOptions};
+use crate::observer::MultiObservers as Observer;
 
 use cfx_parameters::consensus::ONE_CFX_IN_DRIP;
 use cfx_state::CleanupMode;
@@ -31,6 +32,18 @@ impl EstimateRequest {
     }
 }
 
+/// The minimal `max_fee_per_gas` paying `priority_fee` on top of
+/// `base_fee`.
+pub fn min_max_fee(base_fee: U256, priority_fee: U256) -> U256 {
+    base_fee.saturating_add(priority_fee)
+}
+
+/// Like `min_max_fee`, but multiplies `base_fee` by `multiplier` so the
+/// transaction stays includable if the base fee rises before it is packed.
+pub fn min_max_fee_with_multiplier(base_fee: U256, priority_fee: U256, multiplier: u64) -> U256 {
+    min_max_fee(base_fee.saturating_mul(multiplier.into()), priority_fee)
+}
+
 impl<'a> TXExecutor<'a> {
     pub fn transact_virtual(
         &mut self,
@@ -141,4 +154,52 @@ impl<'a> TXExecutor<'a> {
 
         return Ok(ExecutionOutcome::Finished(executed));
     }
+
+    /// Estimates the gas limit of the transaction and executes it without
+    /// charging gas, returning the estimated gas limit and the gas actually
+    /// used. Both are zero if the transaction is not executed. The state is
+    /// left unchanged.
+    pub fn estimate_accuracy(&mut self, tx: &SignedTransaction) -> DbResult<(U256, U256)> {
+        let request = EstimateRequest {
+            has_sender: true,
+            has_gas_limit: false,
+            has_gas_price: false,
+            has_nonce: true,
+            has_storage_limit: false,
+        };
+        self.state.checkpoint();
+        let estimate_outcome = self.transact_virtual(tx.clone(), request);
+        self.state.revert_to_checkpoint();
+
+        let options = TransactOptions {
+            observer: Observer::with_no_tracing(),
+            check_settings: TransactCheckSettings {
+                charge_gas: false,
+                real_execution: false,
+                override_init_gas: None,
+                verify_signature: false,
+                reject_zero_gas_price: false,
+                bump_nonce_on_not_enough_cash: true,
+            },
+            state_overrides: Default::default(),
+            state_diff: false,
+        };
+        self.state.checkpoint();
+        let actual_outcome = self.transact(tx, options);
+        self.state.revert_to_checkpoint();
+
+        let estimated = match estimate_outcome? {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => {
+                executed.estimated_gas_limit.unwrap_or_default()
+            }
+            _ => U256::zero(),
+        };
+        let actual = match actual_outcome? {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => executed.gas_used,
+            _ => U256::zero(),
+        };
+        Ok((estimated, actual))
+    }
 }
@@ -3,12 +3,17 @@
 // See http://www.gnu.org/licenses/
 
 use crate::{bytes::Bytes, vm};
-use cfx_types::{AddressWithSpace, U256, U512};
+use cfx_state::StateDiff;
+use cfx_types::{Address, AddressWithSpace, Space, H256, U256, U512};
 use primitives::LogEntry;
+use rlp::DecoderError;
 use solidity_abi::{ABIDecodable, ABIDecodeError};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Executed {
+    /// Hash of the executed transaction.
+    pub transaction_hash: H256,
+
     /// Gas used during execution of transaction.
     pub gas_used: U256,
 
@@ -28,12 +33,35 @@ pub struct Executed {
     ///
     /// B creation ends first, and it will be the first element of the vector.
     pub contracts_created: Vec<AddressWithSpace>,
+    /// Addresses of contracts selfdestructed during execution of transaction,
+    /// in ascending order.
+    pub selfdestructed: Vec<AddressWithSpace>,
     /// Transaction output.
     pub output: Bytes,
     /// The trace of this transaction.
     pub trace: Vec<ExecTrace>,
+    /// Internal transfers, excluding those in reverted frames. They are
+    /// collected even if the transaction is executed without tracing.
+    pub internal_transfers: Vec<InternalTransferAction>,
     /// Only for the virtual call, an accurate gas estimation for gas usage,
     pub estimated_gas_limit: Option<U256>,
+    /// Storage keys read or written by the transaction, in ascending order.
+    /// Keys accessed only in reverted frames are not included.
+    pub accessed_storage_keys: Vec<(Address, H256)>,
+    /// Accounts accessed by `BALANCE`, `EXTCODE*`, `SELFDESTRUCT` and the
+    /// `CALL` family, in ascending order. Like the storage keys, accounts
+    /// accessed only in reverted frames are not included.
+    pub accessed_addresses: Vec<Address>,
+    /// The refunds for clearing storage slots as `(address, slot, refund)`,
+    /// in execution order. A refund is negative if a cleared slot is set
+    /// again. Like the storage keys, reverted frames are not included.
+    pub refund_details: Vec<(Address, H256, i64)>,
+    /// The changes of the accounts, only collected with
+    /// `TransactOptions::exec_with_state_diff`.
+    pub state_diff: Option<StateDiff>,
+    /// The opcode-level logs, only collected with
+    /// `TransactOptions::exec_with_struct_logs`.
+    pub struct_logs: Vec<StructLog>,
 }
 
 #[derive(Debug)]
@@ -50,12 +78,69 @@ pub enum ToRepackError {
     SenderDoesNotExist,
 }
 
+impl ToRepackError {
+    /// Stable numeric code of the error, e.g. for RPC. The code of a variant
+    /// never changes.
+    pub fn error_code(&self) -> i64 {
+        match self {
+            ToRepackError::InvalidNonce { .. } => 1001,
+            ToRepackError::SenderDoesNotExist => 1002,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TxDropError {
     /// The account nonce in world-state is larger than tx nonce
     OldNonce(U256, U256),
     ///
     NotEnoughBaseGas { expected: u64, actual: u64 },
+    /// The sender recovered from the signature differs from the sender of
+    /// the transaction
+    InvalidSignature,
+    /// The gas price is zero while `reject_zero_gas_price` is set
+    ZeroGasPrice,
+    /// The max fee per gas of an EIP-1559 transaction is less than the base
+    /// fee
+    MaxFeeLessThanBaseFee { max_fee: U256, base_fee: U256 },
+    /// The max priority fee per gas of an EIP-1559 transaction is greater
+    /// than its max fee per gas
+    PriorityFeeGreaterThanMaxFee {
+        max_priority_fee: U256,
+        max_fee: U256,
+    },
+    /// The init code of a `CREATE` transaction exceeds the EIP-3860 limit
+    InitCodeTooLarge { size: usize, limit: usize },
+    /// The space of the transaction is not activated yet, e.g. the EVM space
+    /// before CIP-90
+    SpaceNotActive(Space),
+    /// The raw bytes of the transaction are not a valid RLP encoding of a
+    /// signed transaction
+    InvalidRlp(DecoderError),
+    /// The transaction is signed for another chain
+    ChainIdMismatch { expected: u32, got: u32 },
+    /// The RLP encoding of the transaction exceeds `max_transaction_size`
+    TransactionTooLarge { size: usize, limit: usize },
+}
+
+impl TxDropError {
+    /// Stable numeric code of the error, e.g. for RPC. The code of a variant
+    /// never changes.
+    pub fn error_code(&self) -> i64 {
+        match self {
+            TxDropError::OldNonce(..) => 2001,
+            TxDropError::NotEnoughBaseGas { .. } => 2002,
+            TxDropError::InvalidSignature => 2003,
+            TxDropError::ZeroGasPrice => 2004,
+            TxDropError::MaxFeeLessThanBaseFee { .. } => 2005,
+            TxDropError::InitCodeTooLarge { .. } => 2006,
+            TxDropError::SpaceNotActive(_) => 2007,
+            TxDropError::PriorityFeeGreaterThanMaxFee { .. } => 2008,
+            TxDropError::InvalidRlp(_) => 2009,
+            TxDropError::ChainIdMismatch { .. } => 2010,
+            TxDropError::TransactionTooLarge { .. } => 2011,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -91,26 +176,64 @@ impl ExecutionOutcome {
 }
 
 impl Executed {
+    /// The message of a revert with `Error(string)` data, `None` for other
+    /// outputs, e.g. a `Panic(uint256)`.
+    pub fn revert_reason(&self) -> Option<String> {
+        match self.output.len() >= 4 && self.output[..4] == ERROR_STRING_SELECTOR {
+            true => String::abi_decode(&self.output[4..]).ok(),
+            false => None,
+        }
+    }
+
+    /// The runtime code of the contracts created by the transaction, in the
+    /// order of `contracts_created`. Empty if the transaction is executed
+    /// without tracing.
+    pub fn created_code(&self) -> Vec<(Address, Bytes)> {
+        self.trace
+            .iter()
+            .filter(|t| t.valid)
+            .filter_map(|t| match &t.action {
+                Action::CreateResult(CreateResult {
+                    outcome: Outcome::Success,
+                    addr,
+                    return_data,
+                    ..
+                }) => Some((*addr, return_data.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// `fee` is paid at `gas_price` per gas.
     pub fn not_enough_balance_fee_charged(
         tx: &impl TransactionInfo,
         fee: &U256,
+        gas_price: &U256,
         trace: Vec<ExecTrace>,
         _spec: &Spec,
     ) -> Self {
-        let gas_charged = if *tx.gas_price() == U256::zero() {
+        let gas_charged = if gas_price.is_zero() {
             U256::zero()
         } else {
-            fee / *tx.gas_price()
+            fee / gas_price
         };
         Self {
+            transaction_hash: tx.transaction_hash(),
             gas_used: *tx.gas(),
             gas_charged,
             fee: fee.clone(),
             logs: vec![],
             contracts_created: vec![],
+            selfdestructed: vec![],
             output: Default::default(),
+            internal_transfers: internal_transfers(&trace),
             trace,
             estimated_gas_limit: None,
+            accessed_storage_keys: vec![],
+            accessed_addresses: vec![],
+            refund_details: vec![],
+            state_diff: None,
+            struct_logs: vec![],
         }
     }
 
@@ -120,26 +243,37 @@ impl Executed {
         _spec: &Spec,
     ) -> Self {
         Self {
+            transaction_hash: tx.transaction_hash(),
             gas_used: *tx.gas(),
             gas_charged: *tx.gas(),
             fee: tx.gas().saturating_mul(*tx.gas_price()),
             logs: vec![],
             contracts_created: vec![],
+            selfdestructed: vec![],
 
             output: Default::default(),
+            internal_transfers: internal_transfers(&trace),
             trace,
             estimated_gas_limit: None,
+            accessed_storage_keys: vec![],
+            accessed_addresses: vec![],
+            refund_details: vec![],
+            state_diff: None,
+            struct_logs: vec![],
         }
     }
 }
 
+/// Selector of `Error(string)`.
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
 pub fn revert_reason_decode(output: &Bytes) -> String {
     const MAX_LENGTH: usize = 50;
     let decode_result = if output.len() < 4 {
         Err(ABIDecodeError("Uncompleted Signature"))
     } else {
         let (sig, data) = output.split_at(4);
-        if sig != [8, 195, 121, 160] {
+        if sig != ERROR_STRING_SELECTOR {
             Err(ABIDecodeError("Unrecognized Signature"))
         } else {
             String::abi_decode(data)
@@ -157,7 +291,15 @@ pub fn revert_reason_decode(output: &Bytes) -> String {
     }
 }
 
-use crate::{observer::trace::ExecTrace, vm::Spec};
+use crate::{
+    observer::{
+        trace::{
+            internal_transfers, Action, CreateResult, ExecTrace, InternalTransferAction, Outcome,
+        },
+        StructLog,
+    },
+    vm::Spec,
+};
 #[cfg(test)]
 use rustc_hex::FromHex;
 
@@ -174,3 +316,93 @@ fn test_decode_result() {
         revert_reason_decode(&input_hex.from_hex().unwrap())
     );
 }
+
+#[test]
+fn test_error_codes() {
+    let codes = [
+        ToRepackError::InvalidNonce {
+            expected: U256::zero(),
+            got: U256::one(),
+        }
+        .error_code(),
+        ToRepackError::SenderDoesNotExist.error_code(),
+        TxDropError::OldNonce(U256::one(), U256::zero()).error_code(),
+        TxDropError::NotEnoughBaseGas {
+            expected: 21000,
+            actual: 0,
+        }
+        .error_code(),
+        TxDropError::InvalidSignature.error_code(),
+        TxDropError::ZeroGasPrice.error_code(),
+        TxDropError::MaxFeeLessThanBaseFee {
+            max_fee: U256::zero(),
+            base_fee: U256::one(),
+        }
+        .error_code(),
+        TxDropError::InitCodeTooLarge {
+            size: 98305,
+            limit: 98304,
+        }
+        .error_code(),
+        TxDropError::SpaceNotActive(Space::Ethereum).error_code(),
+        TxDropError::PriorityFeeGreaterThanMaxFee {
+            max_priority_fee: U256::one(),
+            max_fee: U256::zero(),
+        }
+        .error_code(),
+        TxDropError::InvalidRlp(DecoderError::RlpIsTooShort).error_code(),
+        TxDropError::ChainIdMismatch {
+            expected: 1,
+            got: 2,
+        }
+        .error_code(),
+        TxDropError::TransactionTooLarge {
+            size: 307201,
+            limit: 307200,
+        }
+        .error_code(),
+    ];
+    assert_eq!(
+        codes,
+        [1001, 1002, 2001, 2002, 2003, 2004, 2005, 2006, 2007, 2008, 2009, 2010, 2011]
+    );
+}
+
+#[test]
+fn test_revert_reason() {
+    use crate::test_helpers::make_signed_transaction;
+    use cfxkey::{Generator, Random};
+    use primitives::Action;
+
+    let tx = make_signed_transaction(
+        &Random.generate().unwrap(),
+        U256::zero(),
+        Action::Call(Address::zero()),
+        U256::zero(),
+        U256::from(21000),
+        vec![],
+    );
+    let mut executed = Executed::execution_error_fully_charged(&tx, vec![], &Spec::genesis_spec());
+
+    // Error("Not enough balance")
+    executed.output = "08c379a0\
+                       0000000000000000000000000000000000000000000000000000000000000020\
+                       0000000000000000000000000000000000000000000000000000000000000012\
+                       4e6f7420656e6f7567682062616c616e63650000000000000000000000000000"
+        .from_hex()
+        .unwrap();
+    assert_eq!(
+        executed.revert_reason(),
+        Some("Not enough balance".to_string())
+    );
+
+    // Panic(0x11), an arithmetic overflow.
+    executed.output = "4e487b71\
+                       0000000000000000000000000000000000000000000000000000000000000011"
+        .from_hex()
+        .unwrap();
+    assert_eq!(executed.revert_reason(), None);
+
+    executed.output = vec![];
+    assert_eq!(executed.revert_reason(), None);
+}
@@ -1,6 +1,6 @@
 use super::executed::{Executed, ExecutionError, ExecutionOutcome, ToRepackError, TxDropError};
-use super::transaction_info::TransactionInfo;
-use super::TransactOptions;
+use super::transaction_info::{effective_gas_price, TransactionInfo};
+use super::{TransactCheckSettings, TransactOptions};
 use crate::call_create_frame::{contract_address, CallCreateFrame, FrameStack, FrameStackOutput};
 
 use crate::vm_factory::VmFactory;
@@ -12,10 +12,10 @@ use crate::{
     vm::{self, ActionParams, ActionValue, CallType, CreateContractAddress, CreateType, Env, Spec},
 };
 
-use cfx_state::StateTrait;
+use cfx_state::{AccountDiff, StateTrait};
 use cfx_statedb::Result as DbResult;
-use cfx_types::{AddressSpaceUtil, AddressWithSpace, Space, U256, U512};
-use primitives::transaction::Action;
+use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256, U512};
+use primitives::{transaction::Action, SignedTransaction, TransactionWithSignature};
 use std::{
     collections::HashSet,
     convert::{TryFrom, TryInto},
@@ -26,13 +26,18 @@ use std::{
 pub struct TXExecutor<'a> {
     pub(super) state: &'a mut dyn StateTrait,
     env: &'a Env,
-    machine: &'a Machine,
+    pub(super) machine: &'a Machine,
     factory: VmFactory,
     pub(super) spec: &'a Spec,
 }
 
-pub fn gas_required_for(is_create: bool, data: &[u8], spec: &Spec) -> u64 {
-    data.iter().fold(
+pub fn gas_required_for(
+    is_create: bool,
+    data: &[u8],
+    access_list: &[(Address, Vec<H256>)],
+    spec: &Spec,
+) -> u64 {
+    let data_gas = data.iter().fold(
         (if is_create {
             spec.tx_create_gas
         } else {
@@ -44,7 +49,24 @@ pub fn gas_required_for(is_create: bool, data: &[u8], spec: &Spec) -> u64 {
                 _ => spec.tx_data_non_zero_gas,
             }) as u64
         },
-    )
+    );
+    let init_code_gas = match is_create && spec.eip3860 {
+        true => (data.len() as u64 + 31) / 32 * spec.init_code_word_gas as u64,
+        false => 0,
+    };
+    data_gas + init_code_gas + access_list_gas(access_list, spec)
+}
+
+/// Intrinsic gas of an access list, charged per address and per storage
+/// key from EIP-2930 on.
+pub fn access_list_gas(list: &[(Address, Vec<H256>)], spec: &Spec) -> u64 {
+    if !spec.eip2930 {
+        return 0;
+    }
+    list.iter().fold(0, |g, (_, keys)| {
+        g + spec.access_list_address_gas as u64
+            + keys.len() as u64 * spec.access_list_storage_key_gas as u64
+    })
 }
 
 enum PreCheckResult<'a> {
@@ -72,11 +94,200 @@ impl<'a> TXExecutor<'a> {
         }
     }
 
+    /// The environment shared by every transaction run through this
+    /// executor, e.g. all the transactions of a block.
+    pub fn env(&self) -> &'a Env {
+        self.env
+    }
+
     pub fn transact(
+        &mut self,
+        tx: &impl TransactionInfo,
+        mut options: TransactOptions,
+    ) -> DbResult<ExecutionOutcome> {
+        let state_overrides = std::mem::take(&mut options.state_overrides);
+        if state_overrides.is_empty() {
+            return self.transact_with_state_diff(tx, options);
+        }
+
+        self.state.checkpoint();
+        let outcome = self
+            .apply_state_overrides(&state_overrides)
+            .and_then(|_| self.transact_with_state_diff(tx, options));
+        self.state.revert_to_checkpoint();
+        outcome
+    }
+
+    /// Simulates `tx`: executes it, collects the accounts it changes and
+    /// reverts them. A transaction not executed changes nothing.
+    pub fn transact_with_diff(
+        &mut self,
+        tx: &impl TransactionInfo,
+        mut options: TransactOptions,
+    ) -> DbResult<(ExecutionOutcome, Vec<AccountDiff>)> {
+        options.state_diff = true;
+        self.state.checkpoint();
+        let outcome = self.transact(tx, options);
+        self.state.revert_to_checkpoint();
+
+        let outcome = outcome?;
+        let account_diffs = match &outcome {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => executed
+                .state_diff
+                .iter()
+                .flat_map(|diff| diff.accounts.values().cloned())
+                .collect(),
+            _ => vec![],
+        };
+        Ok((outcome, account_diffs))
+    }
+
+    /// Collects the changes of the transaction, including the accounts it
+    /// kills, before anything is committed.
+    fn transact_with_state_diff(
+        &mut self,
+        tx: &impl TransactionInfo,
+        options: TransactOptions,
+    ) -> DbResult<ExecutionOutcome> {
+        if !options.state_diff {
+            return self.transact_inner(tx, options);
+        }
+
+        self.state.checkpoint();
+        let outcome = self.transact_inner(tx, options).and_then(|mut outcome| {
+            let state_diff = self.state.diff_since_checkpoint()?;
+            match &mut outcome {
+                ExecutionOutcome::Finished(executed)
+                | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => {
+                    executed.state_diff = Some(state_diff);
+                }
+                _ => {}
+            }
+            Ok(outcome)
+        });
+        self.state.discard_checkpoint();
+        outcome
+    }
+
+    /// Decodes a signed transaction from its raw bytes, the RLP list of a
+    /// legacy transaction or the EIP-2718 envelope of a typed one, and
+    /// executes it. Malformed bytes, like a signature from which no sender can be
+    /// recovered, drop the transaction.
+    pub fn transact_rlp(
+        &mut self,
+        raw: &[u8],
+        options: TransactOptions,
+    ) -> DbResult<ExecutionOutcome> {
+        let transaction = match TransactionWithSignature::from_raw(raw) {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                return Ok(ExecutionOutcome::NotExecutedDrop(TxDropError::InvalidRlp(
+                    error,
+                )));
+            }
+        };
+        let public = match transaction.recover_public() {
+            Ok(public) => public,
+            Err(_) => {
+                return Ok(ExecutionOutcome::NotExecutedDrop(
+                    TxDropError::InvalidSignature,
+                ));
+            }
+        };
+        self.transact(&SignedTransaction::new(public, transaction), options)
+    }
+
+    /// Executes `txs` one after another against the same state, e.g. the
+    /// transactions of a block, with the options of the `i`-th transaction
+    /// given by `options_fn(i)`. Only a database error stops the execution.
+    pub fn transact_many(
+        &mut self,
+        txs: &[SignedTransaction],
+        options_fn: impl Fn(usize) -> TransactOptions,
+    ) -> DbResult<Vec<ExecutionOutcome>> {
+        let mut outcomes = Vec::with_capacity(txs.len());
+        self.transact_stream(txs, options_fn, |_, outcome| outcomes.push(outcome))?;
+        Ok(outcomes)
+    }
+
+    /// Executes `txs` as `transact_many` does, but hands the outcome of the
+    /// `i`-th transaction to `callback(i, outcome)` as soon as it is
+    /// executed instead of collecting the outcomes.
+    pub fn transact_stream<F: FnMut(usize, ExecutionOutcome)>(
+        &mut self,
+        txs: &[SignedTransaction],
+        options_fn: impl Fn(usize) -> TransactOptions,
+        mut callback: F,
+    ) -> DbResult<()> {
+        for (i, tx) in txs.iter().enumerate() {
+            callback(i, self.transact(tx, options_fn(i))?);
+        }
+        Ok(())
+    }
+
+    /// Checks the chain id and the size of `tx`, and that its gas limit
+    /// covers its intrinsic gas, without accessing the state. The execution
+    /// runs the same checks.
+    pub fn check_intrinsic_gas(&self, tx: &impl TransactionInfo) -> Result<(), TxDropError> {
+        let params = self.machine.params();
+        let chain_id = params
+            .chain_id
+            .read()
+            .get_chain_id(self.env.epoch_height)
+            .in_space(tx.space());
+        match tx.chain_id() {
+            Some(got) if got != chain_id => {
+                return Err(TxDropError::ChainIdMismatch {
+                    expected: chain_id,
+                    got,
+                });
+            }
+            _ => {}
+        }
+
+        match tx.rlp_size() {
+            Some(size) if size > params.max_transaction_size => {
+                return Err(TxDropError::TransactionTooLarge {
+                    size,
+                    limit: params.max_transaction_size,
+                });
+            }
+            _ => {}
+        }
+
+        let init_code_limit = 2 * self.spec.create_data_limit;
+        if self.spec.eip3860
+            && &*tx.action() == &Action::Create
+            && tx.data().len() > init_code_limit
+        {
+            return Err(TxDropError::InitCodeTooLarge {
+                size: tx.data().len(),
+                limit: init_code_limit,
+            });
+        }
+
+        let base_gas_required = gas_required_for(
+            &*tx.action() == &Action::Create,
+            &tx.data(),
+            &tx.access_list(),
+            self.spec,
+        );
+        if *tx.gas() < base_gas_required.into() {
+            return Err(TxDropError::NotEnoughBaseGas {
+                expected: base_gas_required,
+                actual: tx.gas().as_u64(),
+            });
+        }
+        Ok(())
+    }
+
+    fn transact_inner(
         &mut self,
         tx: &impl TransactionInfo,
         options: TransactOptions,
     ) -> DbResult<ExecutionOutcome> {
+        let check_settings = options.check_settings;
         let pre_check_result = self.transact_preprocessing(tx, options)?;
 
         let (top_frame, frame_stack) = match pre_check_result {
@@ -91,7 +302,55 @@ impl<'a> TXExecutor<'a> {
 
         let frame_stack_output = frame_stack.exec(top_frame)?;
 
-        Ok(self.transact_postprocessing(tx, frame_stack_output)?)
+        Ok(self.transact_postprocessing(tx, &check_settings, frame_stack_output)?)
+    }
+
+    /// Executes the transaction under `spec` instead of the spec of the
+    /// executor, e.g. to compare the gas usage before and after a fork.
+    pub fn transact_under_spec(
+        &mut self,
+        tx: &impl TransactionInfo,
+        spec: &'a Spec,
+    ) -> DbResult<ExecutionOutcome> {
+        let original_spec = std::mem::replace(&mut self.spec, spec);
+        let outcome = self.transact(tx, TransactOptions::exec_with_no_tracing());
+        self.spec = original_spec;
+        outcome
+    }
+
+    /// EIP-1559: burns the base fee part of `fee`, paid at `gas_price` per
+    /// gas, and pays the rest of it to the block author.
+    fn burn_base_fee_and_pay_tip(
+        &mut self,
+        fee: U256,
+        gas_price: U256,
+        tracer: &mut dyn StateTracer,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        // The gas price is never below the base fee, so `burnt <= fee`.
+        let burnt: U256 = if gas_price.is_zero() {
+            U256::zero()
+        } else {
+            (fee.full_mul(self.env.base_fee) / U512::from(gas_price))
+                .try_into()
+                .unwrap()
+        };
+        let tip = fee - burnt;
+        tracer.trace_internal_transfer(AddressPocket::GasPayment, AddressPocket::MintBurn, burnt);
+        self.state.subtract_total_issued(burnt);
+
+        let author = self.env.author.with_evm_space();
+        tracer.trace_internal_transfer(
+            AddressPocket::GasPayment,
+            AddressPocket::Balance(author),
+            tip,
+        );
+        self.state.add_balance(
+            &author,
+            &tip,
+            cleanup_mode(substate, self.spec),
+            self.spec.account_start_nonce,
+        )
     }
 
     fn transact_preprocessing(
@@ -102,10 +361,54 @@ impl<'a> TXExecutor<'a> {
         let TransactOptions {
             mut observer,
             check_settings,
+            ..
         } = options;
 
         let spec = self.spec;
         let sender = tx.sender();
+
+        // The EVM space only exists since CIP-90.
+        if tx.space() == Space::Ethereum && !spec.cip90 {
+            return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                TxDropError::SpaceNotActive(tx.space()),
+            )));
+        }
+
+        if check_settings.verify_signature && tx.recover_sender() != Some(sender.address) {
+            return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                TxDropError::InvalidSignature,
+            )));
+        }
+
+        if check_settings.reject_zero_gas_price
+            && check_settings.real_execution
+            && tx.gas_price().is_zero()
+        {
+            return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                TxDropError::ZeroGasPrice,
+            )));
+        }
+
+        if let Some(max_priority_fee) = tx.max_priority_fee_per_gas() {
+            if max_priority_fee > *tx.gas_price() {
+                return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                    TxDropError::PriorityFeeGreaterThanMaxFee {
+                        max_priority_fee,
+                        max_fee: *tx.gas_price(),
+                    },
+                )));
+            }
+            if *tx.gas_price() < self.env.base_fee {
+                return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
+                    TxDropError::MaxFeeLessThanBaseFee {
+                        max_fee: *tx.gas_price(),
+                        base_fee: self.env.base_fee,
+                    },
+                )));
+            }
+        }
+        let gas_price = effective_gas_price(tx, &self.env.base_fee);
+
         let nonce = self.state.nonce(&sender)?;
 
         // Validate transaction nonce
@@ -122,27 +425,35 @@ impl<'a> TXExecutor<'a> {
             ));
         }
 
-        let base_gas_required =
-            gas_required_for(&*tx.action() == &Action::Create, &tx.data(), spec);
-        if *tx.gas() < base_gas_required.into() {
+        if let Err(error) = self.check_intrinsic_gas(tx) {
             return Ok(PreCheckResult::Fail(ExecutionOutcome::NotExecutedDrop(
-                TxDropError::NotEnoughBaseGas {
-                    expected: base_gas_required,
-                    actual: tx.gas().as_u64(),
-                },
+                error,
             )));
         }
+        let base_gas_required = gas_required_for(
+            &*tx.action() == &Action::Create,
+            &tx.data(),
+            &tx.access_list(),
+            spec,
+        );
 
         let balance = self.state.balance(&sender)?;
         let gas_cost = if check_settings.charge_gas {
-            tx.gas().full_mul(*tx.gas_price())
+            tx.gas().full_mul(gas_price)
         } else {
             0.into()
         };
 
         let sender_balance = U512::from(balance);
 
-        let total_cost = U512::from(*tx.value()) + gas_cost;
+        // The sender must afford the max fee of an EIP-1559 transaction, even
+        // though it pays the effective gas price.
+        let max_gas_cost = if check_settings.charge_gas {
+            tx.gas().full_mul(*tx.gas_price())
+        } else {
+            0.into()
+        };
+        let total_cost = U512::from(*tx.value()) + max_gas_cost;
 
         let mut tx_substate = Substate::new();
         if sender_balance < total_cost {
@@ -161,8 +472,10 @@ impl<'a> TXExecutor<'a> {
                     ),
                 ));
             }
-            self.state
-                .inc_nonce(&sender, &self.spec.account_start_nonce)?;
+            if check_settings.bump_nonce_on_not_enough_cash {
+                self.state
+                    .inc_nonce(&sender, &self.spec.account_start_nonce, self.spec.eip2681)?;
+            }
             self.state.sub_balance(
                 &sender,
                 &actual_gas_cost,
@@ -173,7 +486,16 @@ impl<'a> TXExecutor<'a> {
                 AddressPocket::GasPayment,
                 actual_gas_cost,
             );
+            if check_settings.charge_gas && tx.max_priority_fee_per_gas().is_some() {
+                self.burn_base_fee_and_pay_tip(
+                    actual_gas_cost,
+                    gas_price,
+                    observer.as_state_tracer(),
+                    &mut tx_substate,
+                )?;
+            }
 
+            let (trace, internal_transfers) = observer.drain_traces();
             return Ok(PreCheckResult::Fail(
                 ExecutionOutcome::ExecutionErrorBumpNonce(
                     ExecutionError::NotEnoughCash {
@@ -181,12 +503,16 @@ impl<'a> TXExecutor<'a> {
                         got: sender_balance,
                         actual_gas_cost: actual_gas_cost.clone(),
                     },
-                    Executed::not_enough_balance_fee_charged(
-                        tx,
-                        &actual_gas_cost,
-                        observer.tracer.map_or(Default::default(), |t| t.drain()),
-                        &self.spec,
-                    ),
+                    Executed {
+                        internal_transfers,
+                        ..Executed::not_enough_balance_fee_charged(
+                            tx,
+                            &actual_gas_cost,
+                            &gas_price,
+                            trace,
+                            &self.spec,
+                        )
+                    },
                 ),
             ));
         } else {
@@ -195,7 +521,7 @@ impl<'a> TXExecutor<'a> {
             // execution is guaranteed. Note that inc_nonce() will create a
             // new account if the account does not exist.
             self.state
-                .inc_nonce(&sender, &self.spec.account_start_nonce)?;
+                .inc_nonce(&sender, &self.spec.account_start_nonce, self.spec.eip2681)?;
         }
 
         // Subtract the transaction fee from sender or contract.
@@ -214,9 +540,11 @@ impl<'a> TXExecutor<'a> {
             )?;
         }
 
-        let init_gas = *tx.gas() - base_gas_required;
+        let init_gas = check_settings
+            .override_init_gas
+            .unwrap_or(*tx.gas() - base_gas_required);
 
-        let top_frame = match *tx.action() {
+        let mut top_frame = match *tx.action() {
             Action::Create => {
                 let address_scheme = match tx.space() {
                     Space::Ethereum => CreateContractAddress::FromSenderNonce,
@@ -237,7 +565,7 @@ impl<'a> TXExecutor<'a> {
                     sender: sender.address,
                     original_sender: sender.address,
                     gas: init_gas,
-                    gas_price: *tx.gas_price(),
+                    gas_price,
                     value: ActionValue::Transfer(*tx.value()),
                     code: Some(Arc::new(tx.data().into_owned())),
                     data: None,
@@ -264,7 +592,7 @@ impl<'a> TXExecutor<'a> {
                     sender: sender.address,
                     original_sender: sender.address,
                     gas: init_gas,
-                    gas_price: *tx.gas_price(),
+                    gas_price,
                     value: ActionValue::Transfer(*tx.value()),
                     code: self.state.code(&address)?,
                     code_hash: self.state.code_hash(&address)?,
@@ -285,7 +613,19 @@ impl<'a> TXExecutor<'a> {
             }
         };
 
-        let frame_stack = FrameStack::new(self.state, tx_substate, observer, base_gas_required);
+        if self.spec.eip2930 {
+            top_frame
+                .unconfirmed_substate()
+                .mark_access_list_accessed(&tx.access_list(), sender.space);
+        }
+
+        let frame_stack = FrameStack::new(
+            self.state,
+            tx_substate,
+            observer,
+            base_gas_required,
+            self.spec.max_sub_calls,
+        );
 
         Ok(PreCheckResult::Pass {
             top_frame,
@@ -297,6 +637,7 @@ impl<'a> TXExecutor<'a> {
     fn transact_postprocessing(
         &mut self,
         tx: &impl TransactionInfo,
+        check_settings: &TransactCheckSettings,
         frame_stack_output: FrameStackOutput,
     ) -> DbResult<ExecutionOutcome> {
         let FrameStackOutput {
@@ -306,6 +647,8 @@ impl<'a> TXExecutor<'a> {
             base_gas_required,
         } = frame_stack_output;
 
+        self.state.clear_transient_storage();
+
         let output = result
             .as_ref()
             .map(|res| res.return_data.to_vec())
@@ -321,41 +664,87 @@ impl<'a> TXExecutor<'a> {
             _ => 0.into(),
         };
 
+        let gas_limit = match check_settings.override_init_gas {
+            Some(init_gas) => init_gas.saturating_add(base_gas_required.into()),
+            None => *tx.gas(),
+        };
         // gas_used is only used to estimate gas needed
-        let gas_used = *tx.gas() - gas_left;
+        let gas_used = gas_limit - gas_left;
+        // The sender prepaid the gas limit of the transaction, even if the
+        // gas given to the execution is overridden. The gas charged and the
+        // gas refunded add up to the prepaid gas.
+        let prepaid_gas = *tx.gas();
         // gas_left should be smaller than 1/4 of gas_limit, otherwise
         // 3/4 of gas_limit is charged.
+        let gas_price = effective_gas_price(tx, &self.env.base_fee);
         let charge_all = (gas_left + gas_left + gas_left) >= gas_used;
-        let (gas_charged, fees_value, refund_value) = if charge_all {
-            let gas_refunded = *tx.gas() >> 2;
-            let gas_charged = *tx.gas() - gas_refunded;
+        // EIP-3529: the refund for clearing storage is capped at a fifth of
+        // the gas used.
+        let sstore_refund =
+            U256::from(substate.sstore_clears_refund.max(0) as u64).min(gas_used / 5);
+        let (gas_charged, fees_value, refund_value) = if !check_settings.charge_gas {
+            // Nothing is paid before the execution, so nothing is refunded.
+            (gas_used, U256::zero(), U256::zero())
+        } else if charge_all {
+            let gas_refunded = prepaid_gas >> 2;
+            let gas_charged = prepaid_gas - gas_refunded;
             (
                 gas_charged,
-                gas_charged.saturating_mul(*tx.gas_price()),
-                gas_refunded.saturating_mul(*tx.gas_price()),
+                gas_charged.saturating_mul(gas_price),
+                gas_refunded.saturating_mul(gas_price),
             )
         } else {
+            let gas_charged = (gas_used - sstore_refund).min(prepaid_gas);
+            let gas_refunded = prepaid_gas - gas_charged;
             (
-                gas_used,
-                gas_used.saturating_mul(*tx.gas_price()),
-                gas_left.saturating_mul(*tx.gas_price()),
+                gas_charged,
+                gas_charged.saturating_mul(gas_price),
+                gas_refunded.saturating_mul(gas_price),
             )
         };
 
         {
+            let percent = U256::from(self.spec.refund_to_author_percent.min(100));
+            let author_refund: U256 = (refund_value.full_mul(percent) / U512::from(100))
+                .try_into()
+                .unwrap();
+            let sender_refund = refund_value - author_refund;
+            if !author_refund.is_zero() {
+                let author = self.env.author.with_evm_space();
+                observer.as_state_tracer().trace_internal_transfer(
+                    AddressPocket::GasPayment,
+                    AddressPocket::Balance(author),
+                    author_refund,
+                );
+                self.state.add_balance(
+                    &author,
+                    &author_refund,
+                    cleanup_mode(&mut substate, self.spec),
+                    self.spec.account_start_nonce,
+                )?;
+            }
             observer.as_state_tracer().trace_internal_transfer(
                 AddressPocket::GasPayment,
                 AddressPocket::Balance(tx.sender().into_owned()),
-                refund_value.clone(),
+                sender_refund,
             );
             self.state.add_balance(
                 &tx.sender(),
-                &refund_value,
+                &sender_refund,
                 cleanup_mode(&mut substate, self.spec),
                 self.spec.account_start_nonce,
             )?;
         };
 
+        if check_settings.charge_gas && tx.max_priority_fee_per_gas().is_some() {
+            self.burn_base_fee_and_pay_tip(
+                fees_value,
+                gas_price,
+                observer.as_state_tracer(),
+                &mut substate,
+            )?;
+        }
+
         // perform suicides
 
         let subsubstate = self.kill_process(&substate.suicides, observer.as_state_tracer())?;
@@ -382,28 +771,63 @@ impl<'a> TXExecutor<'a> {
         //            spec.kill_dust == CleanDustMode::WithCodeAndStorage,
         //        )?;
 
+        let struct_logs = observer
+            .struct_logger
+            .take()
+            .map_or(Default::default(), |t| t.drain());
+        let (trace, internal_transfers) = observer.drain_traces();
+
         match result {
             Err(vm::Error::StateDbError(e)) => bail!(e.0),
             Err(exception) => Ok(ExecutionOutcome::ExecutionErrorBumpNonce(
                 ExecutionError::VmError(exception),
-                Executed::execution_error_fully_charged(
-                    tx,
-                    observer.tracer.map_or(Default::default(), |t| t.drain()),
-                    &self.spec,
-                ),
+                Executed {
+                    gas_used,
+                    gas_charged,
+                    fee: fees_value,
+                    struct_logs,
+                    internal_transfers,
+                    ..Executed::execution_error_fully_charged(tx, trace, &self.spec)
+                },
             )),
             Ok(r) => {
-                let trace = observer.tracer.map_or(Default::default(), |t| t.drain());
+                let mut accessed_storage_keys: Vec<_> = substate
+                    .accessed_storage_keys
+                    .iter()
+                    .map(|(address, key)| (address.address, *key))
+                    .collect();
+                accessed_storage_keys.sort();
+                let mut accessed_addresses: Vec<_> = substate
+                    .accessed_addresses
+                    .iter()
+                    .map(|address| address.address)
+                    .collect();
+                accessed_addresses.sort();
+
+                let mut selfdestructed: Vec<_> = substate.suicides.iter().cloned().collect();
+                selfdestructed.sort();
 
                 let executed = Executed {
+                    transaction_hash: tx.transaction_hash(),
                     gas_used,
                     gas_charged,
                     fee: fees_value,
                     logs: substate.logs.to_vec(),
                     contracts_created: substate.contracts_created.to_vec(),
+                    selfdestructed,
                     output,
+                    internal_transfers,
                     trace,
                     estimated_gas_limit,
+                    accessed_storage_keys,
+                    accessed_addresses,
+                    refund_details: substate
+                        .refund_details
+                        .iter()
+                        .map(|(address, key, refund)| (address.address, *key, *refund))
+                        .collect(),
+                    state_diff: None,
+                    struct_logs,
                 };
 
                 if r.apply_state {
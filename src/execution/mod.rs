@@ -1,11 +1,21 @@
+mod access_list;
+mod block_summary;
 mod estimate;
 pub mod executed;
 mod executor;
 mod options;
+mod rpc_receipt;
+mod state_override;
 mod transaction_info;
 
-pub use estimate::EstimateRequest;
+#[cfg(test)]
+mod tests;
+
+pub use block_summary::{summarize_block, BlockSummary};
+pub use estimate::{min_max_fee, min_max_fee_with_multiplier, EstimateRequest};
 pub use executed::*;
-pub use executor::{gas_required_for, TXExecutor};
+pub use executor::{access_list_gas, gas_required_for, TXExecutor};
 pub use options::{TransactCheckSettings, TransactOptions};
-pub use transaction_info::TransactionInfo;
+pub use rpc_receipt::{to_rpc_receipt, RpcLog, RpcReceipt};
+pub use state_override::StateOverride;
+pub use transaction_info::{effective_gas_price, is_simple_transfer, tx_hash, TransactionInfo};
@@ -1,10 +1,21 @@
-use super::estimate::EstimateRequest;
+use super::{estimate::EstimateRequest, StateOverride};
 use crate::observer::MultiObservers as Observer;
+use cfx_types::{AddressWithSpace, U256};
+use std::collections::HashMap;
+
+/// The gas given to the top-level frame by `TransactOptions::no_gas`.
+const NO_GAS_INIT_GAS: u64 = 1 << 60;
 
 /// Transaction execution options.
 pub struct TransactOptions {
     pub observer: Observer,
     pub check_settings: TransactCheckSettings,
+    /// Account overrides applied before the execution. If not empty, the
+    /// overrides and all the changes of the transaction are reverted after
+    /// the execution.
+    pub state_overrides: HashMap<AddressWithSpace, StateOverride>,
+    /// Collects the changes of the accounts into `Executed::state_diff`.
+    pub state_diff: bool,
 }
 
 impl TransactOptions {
@@ -12,6 +23,8 @@ impl TransactOptions {
         Self {
             observer: Observer::with_tracing(),
             check_settings: TransactCheckSettings::all_checks(),
+            state_overrides: HashMap::new(),
+            state_diff: false,
         }
     }
 
@@ -19,6 +32,34 @@ impl TransactOptions {
         Self {
             observer: Observer::with_no_tracing(),
             check_settings: TransactCheckSettings::all_checks(),
+            state_overrides: HashMap::new(),
+            state_diff: false,
+        }
+    }
+
+    pub fn exec_with_state_diff() -> Self {
+        Self {
+            state_diff: true,
+            ..Self::exec_with_no_tracing()
+        }
+    }
+
+    /// Executes the transaction with practically unlimited gas, ignoring its
+    /// gas limit, and charges no fee.
+    pub fn no_gas() -> Self {
+        let mut options = Self::exec_with_no_tracing();
+        options.check_settings.charge_gas = false;
+        options.check_settings.override_init_gas = Some(NO_GAS_INIT_GAS.into());
+        options
+    }
+
+    /// Collects the opcode-level logs into `Executed::struct_logs`.
+    pub fn exec_with_struct_logs() -> Self {
+        Self {
+            observer: Observer::with_struct_logs(),
+            check_settings: TransactCheckSettings::all_checks(),
+            state_overrides: HashMap::new(),
+            state_diff: false,
         }
     }
 
@@ -26,14 +67,37 @@ impl TransactOptions {
         Self {
             observer: Observer::virtual_call(),
             check_settings: TransactCheckSettings::from_estimate_request(request),
+            state_overrides: HashMap::new(),
+            state_diff: false,
         }
     }
+
+    pub fn with_state_overrides(
+        mut self,
+        state_overrides: HashMap<AddressWithSpace, StateOverride>,
+    ) -> Self {
+        self.state_overrides = state_overrides;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct TransactCheckSettings {
+    /// Charges the fee from the sender. Otherwise the sender pays nothing and
+    /// gets no refund.
     pub charge_gas: bool,
     pub real_execution: bool,
+    /// Gas given to the top-level frame in place of the transaction gas
+    /// minus the intrinsic gas.
+    pub override_init_gas: Option<U256>,
+    /// Recovers the sender from the signature and drops the transaction if
+    /// it differs from the sender of the transaction.
+    pub verify_signature: bool,
+    /// Drops the transactions with a zero gas price in real execution.
+    pub reject_zero_gas_price: bool,
+    /// Bumps the nonce of a sender which cannot afford the transaction and
+    /// is charged what it has. Otherwise only the charge is applied.
+    pub bump_nonce_on_not_enough_cash: bool,
 }
 
 impl TransactCheckSettings {
@@ -41,6 +105,10 @@ impl TransactCheckSettings {
         Self {
             charge_gas: true,
             real_execution: true,
+            override_init_gas: None,
+            verify_signature: false,
+            reject_zero_gas_price: false,
+            bump_nonce_on_not_enough_cash: true,
         }
     }
 
@@ -48,6 +116,10 @@ impl TransactCheckSettings {
         Self {
             charge_gas: request.charge_gas(),
             real_execution: false,
+            override_init_gas: None,
+            verify_signature: false,
+            reject_zero_gas_price: false,
+            bump_nonce_on_not_enough_cash: true,
         }
     }
 }
@@ -0,0 +1,118 @@
+use super::{effective_gas_price, ExecutionOutcome};
+use crate::{
+    call_create_frame::contract_address,
+    vm::{CreateContractAddress, Env},
+};
+
+use cfx_types::{Address, Bloom, H256, U256, U64};
+use primitives::{Action, SignedTransaction};
+use rustc_hex::ToHex;
+use serde::{Serialize, Serializer};
+
+/// A log in the shape of the `eth_getTransactionReceipt` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLog {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    #[serde(serialize_with = "serialize_hex")]
+    pub data: Vec<u8>,
+    pub block_number: U64,
+    pub transaction_hash: H256,
+    pub transaction_index: U64,
+    /// Index of the log in the transaction.
+    pub log_index: U64,
+}
+
+/// A transaction receipt in the shape of the `eth_getTransactionReceipt`
+/// response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReceipt {
+    pub transaction_hash: H256,
+    pub transaction_index: U64,
+    pub block_number: U64,
+    pub from: Address,
+    pub to: Option<Address>,
+    /// The gas charged to the sender.
+    pub gas_used: U256,
+    pub cumulative_gas_used: U256,
+    pub effective_gas_price: U256,
+    /// The created contract of a successful creation.
+    pub contract_address: Option<Address>,
+    pub logs: Vec<RpcLog>,
+    pub logs_bloom: Bloom,
+    /// 1 on success, 0 on failure.
+    pub status: U64,
+}
+
+/// Builds the receipt of a transaction executed in `block`, or `None` if the
+/// transaction is not executed. `cumulative_gas` is the gas used by the
+/// transactions before it in the block.
+pub fn to_rpc_receipt(
+    outcome: &ExecutionOutcome,
+    tx: &SignedTransaction,
+    block: &Env,
+    tx_index: usize,
+    cumulative_gas: U256,
+) -> Option<RpcReceipt> {
+    let (executed, success) = match outcome {
+        ExecutionOutcome::Finished(executed) => (executed, true),
+        ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => (executed, false),
+        ExecutionOutcome::NotExecutedDrop(_)
+        | ExecutionOutcome::NotExecutedToReconsiderPacking(_) => return None,
+    };
+
+    let (to, contract_address) = match tx.action() {
+        Action::Call(address) => (Some(*address), None),
+        Action::Create if success => {
+            let (address, _) = contract_address(
+                CreateContractAddress::FromSenderNonce,
+                block.number.into(),
+                &tx.sender(),
+                tx.nonce(),
+                tx.data(),
+            );
+            (None, Some(address.address))
+        }
+        Action::Create => (None, None),
+    };
+
+    let logs = executed
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(log_index, log)| RpcLog {
+            address: log.address,
+            topics: log.topics.clone(),
+            data: log.data.clone(),
+            block_number: block.number.into(),
+            transaction_hash: tx.hash(),
+            transaction_index: tx_index.into(),
+            log_index: log_index.into(),
+        })
+        .collect();
+    let logs_bloom = executed.logs.iter().fold(Bloom::zero(), |mut bloom, log| {
+        bloom.accrue_bloom(&log.bloom());
+        bloom
+    });
+
+    Some(RpcReceipt {
+        transaction_hash: tx.hash(),
+        transaction_index: tx_index.into(),
+        block_number: block.number.into(),
+        from: tx.sender().address,
+        to,
+        gas_used: executed.gas_charged,
+        cumulative_gas_used: cumulative_gas + executed.gas_charged,
+        effective_gas_price: effective_gas_price(tx, &block.base_fee),
+        contract_address,
+        logs,
+        logs_bloom,
+        status: (success as u64).into(),
+    })
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", bytes.to_hex::<String>()))
+}
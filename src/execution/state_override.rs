@@ -0,0 +1,59 @@
+use super::TXExecutor;
+
+use cfx_bytes::Bytes;
+use cfx_state::CleanupMode;
+use cfx_statedb::Result as DbResult;
+use cfx_types::{AddressWithSpace, H256, U256};
+use std::collections::HashMap;
+
+/// Replaces parts of an account before executing a transaction. Fields left
+/// as `None` keep their values in the state.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverride {
+    pub balance: Option<U256>,
+    pub nonce: Option<U256>,
+    pub code: Option<Bytes>,
+    /// Storage slots to overwrite, the other slots are kept.
+    pub storage: HashMap<H256, U256>,
+}
+
+impl<'a> TXExecutor<'a> {
+    /// Applies the overrides to the state, creating the missing accounts.
+    pub(super) fn apply_state_overrides(
+        &mut self,
+        overrides: &HashMap<AddressWithSpace, StateOverride>,
+    ) -> DbResult<()> {
+        let account_start_nonce = self.spec.account_start_nonce;
+        for (address, state_override) in overrides {
+            self.state.add_balance(
+                address,
+                &U256::zero(),
+                CleanupMode::ForceCreate,
+                account_start_nonce,
+            )?;
+
+            if let Some(balance) = state_override.balance {
+                let current_balance = self.state.balance(address)?;
+                self.state
+                    .sub_balance(address, &current_balance, &mut CleanupMode::NoEmpty)?;
+                self.state.add_balance(
+                    address,
+                    &balance,
+                    CleanupMode::NoEmpty,
+                    account_start_nonce,
+                )?;
+            }
+            if let Some(nonce) = state_override.nonce {
+                self.state.set_nonce(address, &nonce)?;
+            }
+            if let Some(code) = &state_override.code {
+                self.state.init_code(address, code.clone())?;
+            }
+            for (key, value) in &state_override.storage {
+                self.state
+                    .set_storage(address, key.as_bytes().to_vec(), *value)?;
+            }
+        }
+        Ok(())
+    }
+}
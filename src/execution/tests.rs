@@ -0,0 +1,3215 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{
+    access_list_gas, gas_required_for, is_simple_transfer, min_max_fee,
+    min_max_fee_with_multiplier, summarize_block, to_rpc_receipt, tx_hash, ExecutionError,
+    ExecutionOutcome, StateOverride, TXExecutor, TransactOptions, TransactionInfo, TxDropError,
+};
+use crate::{
+    builtin::{Linear, Pricer},
+    call_create_frame::contract_address,
+    hash::{keccak, KECCAK_EMPTY},
+    machine::{new_machine_with_builtin, PrecompilePricing},
+    observer::{
+        trace::{Action as TraceAction, InternalTransferAction, Outcome},
+        AccountState, AddressPocket, PrestateTracer,
+    },
+    spec::CommonParams,
+    state::{CheckpointEvent, CheckpointRecorder},
+    test_helpers::{
+        deploy_code_for_test, make_signed_transaction, new_funded_account, new_machine_for_test,
+        new_state_for_test,
+    },
+    vm::{self, CreateContractAddress, Env, GasModel, Spec, StandardGasModel},
+    vm_factory::VmFactory,
+};
+use cfx_parameters::{
+    block::CROSS_SPACE_GAS_RATIO, internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS,
+};
+use cfx_state::{state_trait::StateOpsTrait, AccountDiff, AccountSnapshot, CleanupMode};
+use cfx_types::{
+    Address, AddressSpaceUtil, AddressWithSpace, Bloom, BloomInput, Space, H256, U256, U512,
+};
+use cfxkey::{Generator, Random};
+use primitives::{
+    AccessListItem, Action, Eip1559Transaction, Eip155Transaction, Eip2930Transaction,
+    SignedTransaction, Transaction, TransactionWithSignature, EIP1559_TYPE, EIP2930_TYPE,
+};
+use rustc_hex::FromHex;
+use solidity_abi::ABIEncodable;
+use std::{collections::HashMap, sync::Arc};
+
+#[test]
+fn test_create_with_contract_nonce_at_cap() {
+    // PUSH1 0 PUSH1 0 PUSH1 0 CREATE PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "600060006000f060005500".from_hex().unwrap();
+
+    let mut params = CommonParams::default();
+    params.transition_numbers.eip2681 = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+
+    let below_cap = U256::from(u64::MAX - 1);
+    let at_cap = U256::from(u64::MAX);
+    for (number, nonce, created) in [
+        (10, below_cap, true),
+        (10, at_cap, false),
+        (9, at_cap, true),
+    ] {
+        let env = Env {
+            number,
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        let contract_with_space = contract.with_evm_space();
+        deploy_code_for_test(&mut state, &contract, code.clone());
+        state.set_nonce(&contract_with_space, &nonce).unwrap();
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        let new_address = state
+            .storage_at(&contract_with_space, &vec![0u8; 32])
+            .unwrap();
+        if created {
+            assert_eq!(executed.contracts_created.len(), 1);
+            assert!(!new_address.is_zero());
+            assert_eq!(state.nonce(&contract_with_space).unwrap(), nonce + 1);
+        } else {
+            assert!(executed.contracts_created.is_empty());
+            assert!(new_address.is_zero());
+            assert_eq!(state.nonce(&contract_with_space).unwrap(), nonce);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct UnitGasModel;
+
+impl GasModel for UnitGasModel {
+    fn tier_gas(&self, _: &Spec, _: usize) -> usize {
+        1
+    }
+
+    fn jumpdest_gas(&self, _: &Spec) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_custom_gas_model() {
+    // PUSH1 1 PUSH1 2 ADD JUMPDEST POP STOP
+    let code: Vec<u8> = "60016002015b5000".from_hex().unwrap();
+    let opcode_count = 6;
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let mut spec = machine.spec(env.number);
+    spec.gas_model = Some(Arc::new(UnitGasModel));
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(executed.gas_used, U256::from(spec.tx_gas + opcode_count));
+}
+
+#[test]
+fn test_gas_model_overflow() {
+    let mut spec = Spec::genesis_spec();
+    spec.exp_byte_gas = usize::MAX;
+    assert_eq!(StandardGasModel.exp_gas(&spec, 2), Err(vm::Error::OutOfGas));
+    assert_eq!(
+        StandardGasModel.call_gas(&spec, Space::Ethereum, true, true),
+        Ok(spec.call_gas
+            + spec.call_new_account_gas * spec.evm_gas_ratio
+            + spec.call_value_transfer_gas)
+    );
+    spec.call_new_account_gas = usize::MAX;
+    assert_eq!(
+        StandardGasModel.call_gas(&spec, Space::Ethereum, true, false),
+        Err(vm::Error::OutOfGas)
+    );
+}
+
+#[test]
+fn test_create_on_funded_address() {
+    for strict_create_collision in [false, true] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.strict_create_collision = strict_create_collision;
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let (new_address, _) = contract_address(
+            CreateContractAddress::FromSenderNonce,
+            env.number.into(),
+            &sender.address().with_evm_space(),
+            &U256::zero(),
+            &[],
+        );
+        state
+            .add_balance(
+                &new_address,
+                &U256::from(1000),
+                CleanupMode::NoEmpty,
+                U256::zero(),
+            )
+            .unwrap();
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Create,
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+
+        if strict_create_collision {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::ConflictAddress(address)),
+                    _,
+                ) => assert_eq!(address, new_address.address),
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+            assert!(state.nonce(&new_address).unwrap().is_zero());
+        } else {
+            let executed = outcome
+                .successfully_executed()
+                .expect("transaction should be executed");
+            assert_eq!(executed.contracts_created, vec![new_address]);
+            assert_eq!(state.nonce(&new_address).unwrap(), U256::one());
+        }
+        assert_eq!(state.balance(&new_address).unwrap(), U256::from(1000));
+    }
+}
+
+#[test]
+fn test_accessed_storage_keys() {
+    // PUSH1 0 SLOAD POP PUSH1 1 SLOAD POP PUSH1 1 PUSH1 2 SSTORE STOP
+    let code: Vec<u8> = "6000545060015450600160025500".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(
+        executed.accessed_storage_keys,
+        vec![
+            (contract, H256::from_low_u64_be(0)),
+            (contract, H256::from_low_u64_be(1)),
+            (contract, H256::from_low_u64_be(2)),
+        ]
+    );
+}
+
+#[test]
+fn test_create_access_list() {
+    let caller = Address::from_low_u64_be(0x1234);
+    let callee = Address::from_low_u64_be(0x5678);
+    let storageless = Address::from_low_u64_be(0x9abc);
+    // PUSH1 0 SLOAD POP
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 PUSH2 0xffff CALL POP
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x9abc PUSH2 0xffff CALL POP
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 4 PUSH2 0xffff CALL POP STOP
+    let caller_code: Vec<u8> = concat!(
+        "60005450",
+        "6000600060006000600061567861fffff150",
+        "60006000600060006000619abc61fffff150",
+        "60006000600060006000600461fffff150",
+        "00"
+    )
+    .from_hex()
+    .unwrap();
+    // PUSH1 1 PUSH1 9 SSTORE STOP
+    let callee_code: Vec<u8> = "600160095500".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    deploy_code_for_test(&mut state, &caller, caller_code);
+    deploy_code_for_test(&mut state, &callee, callee_code);
+    deploy_code_for_test(&mut state, &storageless, vec![0x00]);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(caller),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let access_list = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .create_access_list(&tx)
+        .expect("no db error");
+
+    assert_eq!(
+        access_list,
+        vec![
+            (caller, vec![H256::from_low_u64_be(0)]),
+            (callee, vec![H256::from_low_u64_be(9)]),
+            // The called contract without storage access has no keys, while
+            // the sender and the precompile are omitted.
+            (storageless, vec![]),
+        ]
+    );
+    // The virtual execution leaves no trace in the state.
+    assert!(state
+        .storage_at(
+            &callee.with_evm_space(),
+            H256::from_low_u64_be(9).as_bytes()
+        )
+        .unwrap()
+        .is_zero());
+    assert!(state
+        .nonce(&sender.address().with_evm_space())
+        .unwrap()
+        .is_zero());
+}
+
+#[test]
+fn test_max_storage_entries_per_account() {
+    // PUSH1 1 PUSH1 0 SSTORE PUSH1 1 PUSH1 1 SSTORE PUSH1 1 PUSH1 2 SSTORE STOP
+    let code: Vec<u8> = "60016000556001600155600160025500".from_hex().unwrap();
+
+    for max_entries in [3, 2] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.max_storage_entries_per_account = Some(max_entries);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        let contract_with_space = contract.with_evm_space();
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+
+        if max_entries == 3 {
+            assert!(outcome.successfully_executed().is_some());
+            assert_eq!(state.storage_entry_count(&contract_with_space).unwrap(), 3);
+        } else {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::ExceedStorageEntryLimit(address)),
+                    _,
+                ) => assert_eq!(address, contract),
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+            assert_eq!(state.storage_entry_count(&contract_with_space).unwrap(), 0);
+        }
+    }
+}
+
+#[test]
+fn test_internal_transfers_of_gas_payment() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    // The transfers are reported with or without tracing.
+    let options: [fn() -> TransactOptions; 2] = [
+        TransactOptions::exec_with_tracing,
+        TransactOptions::exec_with_no_tracing,
+    ];
+    for options in options {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let sender_with_space = sender.address().with_evm_space();
+        let receiver = Address::from_low_u64_be(0x1234);
+        let gas = U256::from(100_000);
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(receiver),
+            U256::from(1000),
+            gas,
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options())
+            .expect("no db error")
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        // Over a quarter of the gas is left, so 3/4 of the gas limit is
+        // charged.
+        assert_eq!(
+            executed.internal_transfers,
+            vec![
+                InternalTransferAction {
+                    from: AddressPocket::Balance(sender_with_space),
+                    to: AddressPocket::GasPayment,
+                    value: gas,
+                },
+                InternalTransferAction {
+                    from: AddressPocket::GasPayment,
+                    to: AddressPocket::Balance(sender_with_space),
+                    value: gas / 4,
+                },
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_call_with_excessive_gas_is_clamped() {
+    let caller = Address::from_low_u64_be(0x1234);
+    let callee = Address::from_low_u64_be(0x5678);
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 PUSH4 0xffffffff
+    // CALL STOP
+    let caller_code: Vec<u8> = "6000600060006000600061567863fffffffff100"
+        .from_hex()
+        .unwrap();
+    // GAS PUSH1 0 SSTORE STOP
+    let callee_code: Vec<u8> = "5a60005500".from_hex().unwrap();
+    let tx_gas = 1_000_000;
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    deploy_code_for_test(&mut state, &caller, caller_code);
+    deploy_code_for_test(&mut state, &callee, callee_code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(caller),
+        U256::zero(),
+        U256::from(tx_gas),
+        vec![],
+    );
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    // Seven PUSHes cost 3 each before the CALL, and the callee spends 2 on
+    // GAS. The requested 0xffffffff is clamped to all but 1/64 of the gas
+    // left after the CALL cost.
+    let gas_left = tx_gas - spec.tx_gas - 7 * 3 - spec.call_gas;
+    let gas_provided = gas_left - gas_left / 64;
+    assert_eq!(
+        state
+            .storage_at(&callee.with_evm_space(), &vec![0u8; 32])
+            .unwrap(),
+        U256::from(gas_provided - 2)
+    );
+}
+
+#[test]
+fn test_reverted_create_leaves_no_account() {
+    // PUSH1 0 PUSH1 0 REVERT
+    let init_code: Vec<u8> = "60006000fd".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let (new_address, _) = contract_address(
+        CreateContractAddress::FromSenderNonce,
+        env.number.into(),
+        &sender.address().with_evm_space(),
+        &U256::zero(),
+        &init_code,
+    );
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Create,
+        U256::zero(),
+        U256::from(100_000),
+        init_code,
+    );
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+
+    match outcome {
+        ExecutionOutcome::ExecutionErrorBumpNonce(
+            ExecutionError::VmError(vm::Error::Reverted),
+            executed,
+        ) => assert!(executed.contracts_created.is_empty()),
+        _ => panic!("unexpected outcome {:?}", outcome),
+    }
+    assert!(!state.exists(&new_address).unwrap());
+}
+
+#[test]
+fn test_is_simple_transfer() {
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = Address::from_low_u64_be(0x1234);
+    let contract = Address::from_low_u64_be(0x5678);
+    // STOP
+    deploy_code_for_test(&mut state, &contract, vec![0x00]);
+
+    let transfer = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver),
+        U256::from(1000),
+        U256::from(21000),
+        vec![],
+    );
+    assert!(is_simple_transfer(&transfer, &state).unwrap());
+
+    let contract_call = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::from(1000),
+        U256::from(100_000),
+        vec![],
+    );
+    assert!(!is_simple_transfer(&contract_call, &state).unwrap());
+
+    let create = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Create,
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    assert!(!is_simple_transfer(&create, &state).unwrap());
+}
+
+#[test]
+fn test_cross_space_call_gas_is_clamped() {
+    let callee = Address::from_low_u64_be(0x1234);
+    // callEVM(bytes20,bytes)
+    let mut data: Vec<u8> = "bea05ee3".from_hex().unwrap();
+    data.extend((callee.0, Vec::<u8>::new()).abi_encode());
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let mut spec = machine.spec(env.number);
+    spec.cip90 = true;
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    // STOP
+    deploy_code_for_test(&mut state, &callee, vec![0x00]);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(*CROSS_SPACE_CONTRACT_ADDRESS),
+        U256::zero(),
+        U256::from(1_000_000),
+        data,
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    let calls: Vec<_> = executed
+        .trace
+        .iter()
+        .filter_map(|t| match &t.action {
+            TraceAction::Call(call) => Some(call),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[1].to, callee);
+    // The upfront payment of `callEVM` to an existing account without value
+    // and data: the call, the transaction, the address mapping, the `Call`
+    // log with 4 words of data and the `Outcome` log with 1 word.
+    let upfront_gas = spec.call_gas
+        + 2 * spec.tx_gas
+        + 2 * spec.sha3_gas
+        + (spec.log_gas + 3 * spec.log_topic_gas + 4 * 32 * spec.log_data_gas)
+        + (spec.log_gas + spec.log_topic_gas + 32 * spec.log_data_gas);
+    // The internal contract forwards exactly 1/CROSS_SPACE_GAS_RATIO of the
+    // gas left after its upfront payment, no stipend is added without value.
+    assert_eq!(
+        calls[1].gas,
+        (calls[0].gas - upfront_gas) / CROSS_SPACE_GAS_RATIO
+    );
+}
+
+#[test]
+fn test_transact_under_spec() {
+    // PUSH1 1 PUSH1 0 SSTORE PUSH1 1 PUSH1 1 SSTORE PUSH1 1 PUSH1 2 SSTORE STOP
+    let code: Vec<u8> = "60016000556001600155600160025500".from_hex().unwrap();
+    let contract = Address::from_low_u64_be(0x1234);
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut expensive_spec = machine.spec(env.number);
+    expensive_spec.sstore_set_gas *= 2;
+
+    let mut gas_used = vec![];
+    for override_spec in [&spec, &expensive_spec] {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact_under_spec(&tx, override_spec)
+            .expect("no db error")
+            .successfully_executed()
+            .expect("transaction should be executed");
+        gas_used.push(executed.gas_used);
+    }
+
+    let extra_sstore_gas = spec.sstore_set_gas * spec.evm_gas_ratio;
+    assert_eq!(gas_used[1] - gas_used[0], U256::from(3 * extra_sstore_gas));
+}
+
+#[test]
+fn test_logs_of_reverted_frame_are_dropped() {
+    let caller = Address::from_low_u64_be(0x1234);
+    let callee = Address::from_low_u64_be(0x5678);
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL POP
+    // PUSH1 0 PUSH1 0 LOG0 STOP
+    let caller_code: Vec<u8> = "600060006000600060006156785af15060006000a000"
+        .from_hex()
+        .unwrap();
+    // PUSH1 0 PUSH1 0 LOG0 PUSH1 0 PUSH1 0 REVERT
+    let callee_code: Vec<u8> = "60006000a060006000fd".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    deploy_code_for_test(&mut state, &caller, caller_code);
+    deploy_code_for_test(&mut state, &callee, callee_code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(caller),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(executed.logs.len(), 1);
+    assert_eq!(executed.logs[0].address, caller);
+}
+
+#[test]
+fn test_override_init_gas() {
+    // PUSH1 1 PUSH1 1 PUSH1 1 PUSH1 1 STOP
+    let code: Vec<u8> = "600160016001600100".from_hex().unwrap();
+    let code_gas = 4 * 3;
+
+    for init_gas in [code_gas, code_gas - 1] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let spec = machine.spec(env.number);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let mut options = TransactOptions::exec_with_no_tracing();
+        options.check_settings.override_init_gas = Some(U256::from(init_gas));
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options)
+            .expect("no db error");
+
+        if init_gas == code_gas {
+            assert!(outcome.successfully_executed().is_some());
+        } else {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::OutOfGas),
+                    _,
+                ) => {}
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_override_init_gas_conserves_balance() {
+    // PUSH2 10000 JUMPDEST PUSH1 1 SWAP1 SUB DUP1 PUSH1 3 JUMPI STOP
+    let code: Vec<u8> = "6127105b600190038060035700".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    // The loop costs about 260000 gas, the overrides run out of gas, leave
+    // most of the gas, or use more gas than the prepaid 100000.
+    for init_gas in [50_000u64, 10_000_000, 270_000] {
+        let mut state = new_state_for_test();
+        let balance = U256::from(1_000_000_000_000u64);
+        let sender = new_funded_account(&mut state, balance);
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        let mut options = TransactOptions::exec_with_no_tracing();
+        assert!(options.check_settings.charge_gas);
+        options.check_settings.override_init_gas = Some(U256::from(init_gas));
+        let executed = match TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options)
+            .expect("no db error")
+        {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => executed,
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        };
+
+        assert!(executed.gas_charged <= U256::from(100_000));
+        assert_eq!(
+            state.balance(&sender.address().with_evm_space()).unwrap() + executed.fee,
+            balance
+        );
+    }
+}
+
+#[test]
+fn test_reverted_create_returns_revert_data() {
+    // PUSH1 0x2a PUSH1 0 MSTORE PUSH1 32 PUSH1 0 REVERT
+    let init_code = "602a60005260206000fd";
+    // PUSH10 <init code> PUSH1 0 MSTORE PUSH1 10 PUSH1 22 PUSH1 0 CREATE POP
+    // RETURNDATASIZE PUSH1 0 SSTORE
+    // PUSH1 32 PUSH1 0 PUSH1 0 RETURNDATACOPY PUSH1 0 MLOAD PUSH1 1 SSTORE STOP
+    let code: Vec<u8> = format!(
+        "69{}600052600a60166000f0503d6000556020600060003e60005160015500",
+        init_code
+    )
+    .from_hex()
+    .unwrap();
+    let contract = Address::from_low_u64_be(0x1234);
+    let contract_with_space = contract.with_evm_space();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    let create_results: Vec<_> = executed
+        .trace
+        .iter()
+        .filter_map(|t| match &t.action {
+            TraceAction::CreateResult(result) => Some(result),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(create_results.len(), 1);
+    assert_eq!(create_results[0].outcome, Outcome::Reverted);
+    let mut revert_data = vec![0u8; 32];
+    revert_data[31] = 0x2a;
+    assert_eq!(create_results[0].return_data, revert_data);
+
+    // The revert data is visible to the creator through RETURNDATA*.
+    let key = |slot: u64| H256::from_low_u64_be(slot).as_bytes().to_vec();
+    assert_eq!(
+        state.storage_at(&contract_with_space, &key(0)).unwrap(),
+        U256::from(32)
+    );
+    assert_eq!(
+        state.storage_at(&contract_with_space, &key(1)).unwrap(),
+        U256::from(0x2a)
+    );
+}
+
+#[test]
+fn test_max_sub_calls() {
+    // PUSH1 5 JUMPDEST
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL POP
+    // PUSH1 1 SWAP1 SUB DUP1 PUSH1 2 JUMPI STOP
+    let code: Vec<u8> = "60055b600060006000600060006156785af150600190038060025700"
+        .from_hex()
+        .unwrap();
+
+    for max_sub_calls in [5, 4] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.max_sub_calls = Some(max_sub_calls);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+
+        if max_sub_calls == 5 {
+            assert!(outcome.successfully_executed().is_some());
+        } else {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::ExceedSubCallLimit),
+                    _,
+                ) => {}
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_max_return_data_size() {
+    // PUSH2 0x0100 PUSH1 0 RETURN
+    let code: Vec<u8> = "6101006000f3".from_hex().unwrap();
+
+    for max_size in [256, 255] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.max_return_data_size = Some(max_size);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+
+        if max_size == 256 {
+            let executed = outcome.successfully_executed().expect("at the limit");
+            assert_eq!(executed.output, vec![0u8; 256]);
+        } else {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::OutOfGas),
+                    executed,
+                ) => assert_eq!(executed.gas_used, U256::from(1_000_000)),
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sstore_clears_refund() {
+    // PUSH1 0 PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "600060005500".from_hex().unwrap();
+
+    for sstore_refund_gas in [15000, 1000] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.eip3529 = true;
+        spec.sstore_refund_gas = sstore_refund_gas;
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+        state
+            .set_storage(&contract.with_evm_space(), vec![0; 32], U256::one())
+            .unwrap();
+
+        // Use up all the gas, so the refund is not hidden by the charge of a
+        // quarter of the gas limit.
+        let gas_limit = spec.tx_gas + 2 * 3 + spec.sstore_reset_gas;
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(gas_limit),
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        assert_eq!(executed.gas_used, U256::from(gas_limit));
+        let refund = sstore_refund_gas.min(gas_limit / 5);
+        assert_eq!(executed.gas_charged, U256::from(gas_limit - refund));
+        assert_eq!(executed.fee, U256::from(gas_limit - refund));
+        assert_eq!(
+            executed.refund_details,
+            vec![(contract, H256::zero(), sstore_refund_gas as i64)]
+        );
+    }
+}
+
+#[test]
+fn test_sstore_clear_reset_clear_refund() {
+    // PUSH1 0 PUSH1 0 SSTORE PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "600060005560016000556000600055".from_hex().unwrap();
+
+    let mut params = CommonParams::default();
+    params.transition_numbers.eip3529 = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+    for number in [10, 9] {
+        let env = Env {
+            number,
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number);
+        assert_eq!(spec.eip3529, number >= 10);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+        state
+            .set_storage(&contract.with_evm_space(), vec![0; 32], U256::one())
+            .unwrap();
+
+        let gas_limit = spec.tx_gas
+            + 6 * 3
+            + 2 * spec.sstore_reset_gas
+            + spec.sstore_set_gas * spec.evm_gas_ratio;
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(gas_limit),
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        // Setting the cleared slot again takes back the refund of the first
+        // clearing, only the last clearing is refunded.
+        assert_eq!(executed.gas_used, U256::from(gas_limit));
+        let refund = if spec.eip3529 { 4800 } else { 0 };
+        assert_eq!(executed.gas_charged, U256::from(gas_limit - refund));
+    }
+}
+
+#[test]
+fn test_estimate_accuracy() {
+    // CALLDATASIZE PUSH1 5 JUMPI STOP
+    // JUMPDEST PUSH1 1 PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "36600557005b600160005500".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    for data in [vec![], vec![1]] {
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(1_000_000),
+            data,
+        );
+        let (estimated, actual) = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .estimate_accuracy(&tx)
+            .expect("no db error");
+        assert!(actual > U256::from(21000));
+        assert!(estimated >= actual);
+    }
+    assert_eq!(
+        state
+            .storage_at(&contract.with_evm_space(), &[0u8; 32])
+            .unwrap(),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_state_overrides() {
+    // PUSH1 0 SLOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let code: Vec<u8> = "60005460005260206000f3".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    let contract_with_space = contract.with_evm_space();
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let mut state_override = StateOverride::default();
+    state_override.storage.insert(H256::zero(), U256::from(42));
+    let options = TransactOptions::exec_with_no_tracing()
+        .with_state_overrides(HashMap::from([(contract_with_space, state_override)]));
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, options)
+        .expect("no db error")
+        .successfully_executed()
+        .unwrap();
+    assert_eq!(U256::from_big_endian(&executed.output), U256::from(42));
+
+    // The overrides and the execution are reverted.
+    assert_eq!(
+        state.storage_at(&contract_with_space, &[0u8; 32]).unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        state.nonce(&sender.address().with_evm_space()).unwrap(),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_verify_signature() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let other = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(Address::from_low_u64_be(0x1234)),
+        U256::from(1000),
+        U256::from(21000),
+        vec![],
+    );
+    let mut tampered_tx = tx.clone();
+    tampered_tx.sender = other.address();
+
+    let mut options = TransactOptions::exec_with_no_tracing();
+    options.check_settings.verify_signature = true;
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tampered_tx, options)
+        .expect("no db error");
+    match outcome {
+        ExecutionOutcome::NotExecutedDrop(TxDropError::InvalidSignature) => {}
+        _ => panic!("unexpected outcome {:?}", outcome),
+    }
+
+    let mut options = TransactOptions::exec_with_no_tracing();
+    options.check_settings.verify_signature = true;
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, options)
+        .expect("no db error");
+    assert!(outcome.successfully_executed().is_some());
+}
+
+#[test]
+fn test_precompile_pricing() {
+    let identity = Address::from_low_u64_be(4);
+    let mut gas_used = vec![];
+
+    for repriced in [false, true] {
+        let mut machine = new_machine_for_test();
+        if repriced {
+            let mut pricing = PrecompilePricing::new();
+            pricing.insert(identity, Box::new(Linear::new(1000, 0)) as Box<dyn Pricer>);
+            machine.set_precompile_pricing(pricing);
+        }
+        let env = Env::default();
+        let spec = machine.spec(env.number);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(identity),
+            U256::zero(),
+            U256::from(100_000),
+            vec![1; 32],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .unwrap();
+        assert_eq!(executed.output, vec![1; 32]);
+        gas_used.push(executed.gas_used);
+    }
+
+    // The identity costs 15 + 3 per word by default.
+    assert_eq!(gas_used[1] - gas_used[0], U256::from(1000 - 18));
+}
+
+#[test]
+fn test_call_codeless_account_with_data() {
+    // PUSH1 0 PUSH1 0 PUSH1 32 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL
+    // PUSH1 0 SSTORE RETURNDATASIZE PUSH1 1 ADD PUSH1 1 SSTORE STOP
+    let code: Vec<u8> = "600060006020600060006156785af16000553d60010160015500"
+        .from_hex()
+        .unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    let contract_with_space = contract.with_evm_space();
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+    assert!(outcome.successfully_executed().is_some());
+
+    // The call succeeds with empty return data.
+    let mut key = vec![0u8; 32];
+    assert_eq!(
+        state.storage_at(&contract_with_space, &key).unwrap(),
+        U256::one()
+    );
+    key[31] = 1;
+    assert_eq!(
+        state.storage_at(&contract_with_space, &key).unwrap(),
+        U256::one()
+    );
+}
+
+#[test]
+fn test_callcode_with_value() {
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 100 PUSH2 0x5678 GAS CALLCODE STOP
+    let caller_code: Vec<u8> = "600060006000600060646156785af200".from_hex().unwrap();
+    // CALLVALUE PUSH1 0 SSTORE STOP
+    let callee_code: Vec<u8> = "3460005500".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let caller = Address::from_low_u64_be(0x1234);
+    let caller_with_space = caller.with_evm_space();
+    let callee = Address::from_low_u64_be(0x5678);
+    deploy_code_for_test(&mut state, &caller, caller_code);
+    deploy_code_for_test(&mut state, &callee, callee_code);
+    state
+        .add_balance(
+            &caller_with_space,
+            &U256::from(1000),
+            CleanupMode::NoEmpty,
+            U256::zero(),
+        )
+        .unwrap();
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(caller),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+    assert!(outcome.successfully_executed().is_some());
+
+    // The callee code sees the value while no balance moves.
+    assert_eq!(
+        state.storage_at(&caller_with_space, &[0u8; 32]).unwrap(),
+        U256::from(100)
+    );
+    assert_eq!(state.balance(&caller_with_space).unwrap(), U256::from(1000));
+    assert_eq!(
+        state.balance(&callee.with_evm_space()).unwrap(),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_base_gas_override() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    assert_eq!(gas_required_for(false, &[], &[], &spec), 21000);
+
+    let spec = spec.with_base_gas(10500, 26500);
+    assert_eq!(gas_required_for(false, &[], &[], &spec), 10500);
+    assert_eq!(gas_required_for(true, &[], &[], &spec), 26500);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(Address::from_low_u64_be(0x1234)),
+        U256::from(1000),
+        U256::from(10500),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .unwrap();
+    assert_eq!(executed.gas_used, U256::from(10500));
+}
+
+#[test]
+fn test_reject_zero_gas_price() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    for reject_zero_gas_price in [true, false] {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let tx = Transaction::from(Eip155Transaction {
+            nonce: U256::zero(),
+            gas_price: U256::zero(),
+            gas: U256::from(21000),
+            value: U256::from(1000),
+            action: Action::Call(Address::from_low_u64_be(0x1234)),
+            chain_id: Some(1),
+            data: vec![],
+        })
+        .sign(&sender.secret());
+
+        let mut options = TransactOptions::exec_with_no_tracing();
+        options.check_settings.reject_zero_gas_price = reject_zero_gas_price;
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options)
+            .expect("no db error");
+        match outcome {
+            ExecutionOutcome::NotExecutedDrop(TxDropError::ZeroGasPrice)
+                if reject_zero_gas_price => {}
+            ExecutionOutcome::Finished(executed) if !reject_zero_gas_price => {
+                assert_eq!(executed.fee, U256::zero())
+            }
+            _ => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_access_list_gas() {
+    let mut spec = Spec::genesis_spec();
+    let key = H256::zero();
+
+    // The access list is free before EIP-2930.
+    assert_eq!(
+        access_list_gas(&[(Address::from_low_u64_be(1), vec![key])], &spec),
+        0
+    );
+    spec.eip2930 = true;
+
+    assert_eq!(access_list_gas(&[], &spec), 0);
+    assert_eq!(
+        access_list_gas(&[(Address::from_low_u64_be(1), vec![])], &spec),
+        2400
+    );
+    assert_eq!(
+        access_list_gas(
+            &[
+                (Address::from_low_u64_be(1), vec![key, key]),
+                (Address::from_low_u64_be(2), vec![key]),
+            ],
+            &spec
+        ),
+        2 * 2400 + 3 * 1900
+    );
+}
+
+#[test]
+fn test_intrinsic_gas_with_access_list() {
+    let mut spec = Spec::genesis_spec();
+    spec.eip2930 = true;
+    let data = [0u8, 1];
+    let access_list = [
+        (
+            Address::from_low_u64_be(1),
+            vec![H256::zero(), H256::zero()],
+        ),
+        (Address::from_low_u64_be(2), vec![]),
+    ];
+
+    for is_create in [false, true] {
+        let without_list = gas_required_for(is_create, &data, &[], &spec);
+        let with_list = gas_required_for(is_create, &data, &access_list, &spec);
+        assert_eq!(with_list - without_list, 2 * 2400 + 2 * 1900);
+    }
+}
+
+#[test]
+fn test_warm_sload_with_access_list() {
+    // PUSH1 0 SLOAD STOP
+    let code: Vec<u8> = "60005400".from_hex().unwrap();
+    // CALL(GAS, 0x1234, 0, 0, 0, 0, 0) STOP
+    let proxy_code: Vec<u8> =
+        "600060006000600060007300000000000000000000000000000000000012345af100"
+            .from_hex()
+            .unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let mut spec = machine.spec(env.number);
+    spec.eip2929 = true;
+    spec.eip2930 = true;
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+    let proxy = Address::from_low_u64_be(0x5678);
+    deploy_code_for_test(&mut state, &proxy, proxy_code);
+
+    let access_list = vec![AccessListItem {
+        address: contract,
+        storage_keys: vec![H256::zero()],
+    }];
+    let mut nonce = U256::zero();
+    let mut gas_used = |spec: &Spec, to: Address, access_list: Vec<AccessListItem>| {
+        let tx = Transaction::from(Eip2930Transaction {
+            chain_id: 1,
+            nonce,
+            gas_price: U256::one(),
+            gas: U256::from(100_000),
+            action: Action::Call(to),
+            value: U256::zero(),
+            data: vec![],
+            access_list,
+        })
+        .sign(&sender.secret());
+        nonce += U256::one();
+        TXExecutor::new(&mut state, &env, &machine, spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .expect("transaction should be executed")
+            .gas_used
+            .as_usize()
+    };
+
+    // PUSH1 costs 3, the slot in the access list is warm.
+    let access_list_gas = spec.access_list_address_gas + spec.access_list_storage_key_gas;
+    assert_eq!(
+        gas_used(&spec, contract, vec![]),
+        spec.tx_gas + 3 + spec.cold_sload_gas
+    );
+    assert_eq!(
+        gas_used(&spec, contract, access_list.clone()),
+        spec.tx_gas + access_list_gas + 3 + spec.warm_storage_read_gas
+    );
+
+    // The slot is still warm in a sub-call.
+    let cold_call = gas_used(&spec, proxy, vec![]);
+    let warm_call = gas_used(&spec, proxy, access_list.clone());
+    assert_eq!(
+        warm_call,
+        cold_call + access_list_gas - (spec.cold_sload_gas - spec.warm_storage_read_gas)
+    );
+
+    // Before EIP-2930, the access list is neither charged nor accessed.
+    let mut spec_without_eip2930 = spec.clone();
+    spec_without_eip2930.eip2930 = false;
+    assert_eq!(
+        gas_used(&spec_without_eip2930, contract, access_list),
+        spec.tx_gas + 3 + spec.cold_sload_gas
+    );
+}
+
+#[test]
+fn test_selfdestruct_to_self() {
+    // ADDRESS SELFDESTRUCT
+    let code: Vec<u8> = "30ff".from_hex().unwrap();
+
+    for cancun_selfdestruct in [false, true] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.cancun_selfdestruct = cancun_selfdestruct;
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        let contract_with_space = contract.with_evm_space();
+        deploy_code_for_test(&mut state, &contract, code.clone());
+        state
+            .add_balance(
+                &contract_with_space,
+                &U256::from(1000),
+                CleanupMode::NoEmpty,
+                U256::zero(),
+            )
+            .unwrap();
+
+        // A contract created before the transaction.
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+        assert!(outcome.successfully_executed().is_some());
+        if cancun_selfdestruct {
+            assert_eq!(
+                state.balance(&contract_with_space).unwrap(),
+                U256::from(1000)
+            );
+            assert!(state.is_contract_with_code(&contract_with_space).unwrap());
+        } else {
+            assert_eq!(state.balance(&contract_with_space).unwrap(), U256::zero());
+            assert!(!state.is_contract_with_code(&contract_with_space).unwrap());
+        }
+
+        // A contract destroying itself in its constructor is always killed.
+        let tx = make_signed_transaction(
+            &sender,
+            U256::one(),
+            Action::Create,
+            U256::from(1000),
+            U256::from(100_000),
+            code.clone(),
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .unwrap();
+        let created = executed.contracts_created[0];
+        assert_eq!(state.balance(&created).unwrap(), U256::zero());
+    }
+}
+
+#[test]
+fn test_rpc_receipt() {
+    // PUSH1 0 PUSH1 0 LOG0 STOP
+    let init_code: Vec<u8> = "60006000a000".from_hex().unwrap();
+    // PUSH1 0 PUSH1 0 REVERT
+    let revert_code: Vec<u8> = "60006000fd".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, revert_code);
+
+    let create_tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Create,
+        U256::zero(),
+        U256::from(100_000),
+        init_code.clone(),
+    );
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&create_tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+    let receipt = to_rpc_receipt(&outcome, &create_tx, &env, 0, U256::zero()).unwrap();
+    let (created, _) = contract_address(
+        CreateContractAddress::FromSenderNonce,
+        env.number.into(),
+        &sender.address().with_evm_space(),
+        &U256::zero(),
+        &init_code,
+    );
+    assert_eq!(receipt.status, 1.into());
+    assert_eq!(receipt.to, None);
+    assert_eq!(receipt.contract_address, Some(created.address));
+    assert_eq!(receipt.logs.len(), 1);
+    assert_eq!(receipt.logs[0].address, created.address);
+    assert_eq!(receipt.logs[0].log_index, 0.into());
+    assert!(receipt
+        .logs_bloom
+        .contains_input(BloomInput::Raw(created.address.as_bytes())));
+    let create_gas = receipt.cumulative_gas_used;
+    assert_eq!(create_gas, receipt.gas_used);
+
+    let call_tx = make_signed_transaction(
+        &sender,
+        U256::one(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&call_tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+    let receipt = to_rpc_receipt(&outcome, &call_tx, &env, 1, create_gas).unwrap();
+    assert_eq!(receipt.status, 0.into());
+    assert_eq!(receipt.to, Some(contract));
+    assert_eq!(receipt.contract_address, None);
+    assert!(receipt.logs.is_empty());
+    assert_eq!(receipt.logs_bloom, Bloom::zero());
+    assert_eq!(receipt.cumulative_gas_used, create_gas + receipt.gas_used);
+    assert_eq!(receipt.transaction_index, 1.into());
+}
+
+#[test]
+fn test_min_max_fee() {
+    let gwei = U256::from(1_000_000_000u64);
+
+    assert_eq!(min_max_fee(U256::zero(), U256::zero()), U256::zero());
+    assert_eq!(min_max_fee(gwei * 30, gwei * 2), gwei * 32);
+    assert_eq!(min_max_fee(gwei * 30, U256::zero()), gwei * 30);
+    assert_eq!(min_max_fee(U256::MAX, gwei), U256::MAX);
+
+    assert_eq!(
+        min_max_fee_with_multiplier(gwei * 30, gwei * 2, 1),
+        gwei * 32
+    );
+    assert_eq!(
+        min_max_fee_with_multiplier(gwei * 30, gwei * 2, 2),
+        gwei * 62
+    );
+    assert_eq!(min_max_fee_with_multiplier(U256::zero(), gwei, 2), gwei);
+}
+
+#[test]
+fn test_checkpoint_observer() {
+    let caller = Address::from_low_u64_be(0x1234);
+    let callee = Address::from_low_u64_be(0x5678);
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL STOP
+    let caller_code: Vec<u8> = "600060006000600060006156785af100".from_hex().unwrap();
+    // PUSH1 0 PUSH1 0 REVERT
+    let callee_code: Vec<u8> = "60006000fd".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    deploy_code_for_test(&mut state, &caller, caller_code);
+    deploy_code_for_test(&mut state, &callee, callee_code);
+
+    let recorder = CheckpointRecorder::default();
+    state.set_checkpoint_observer(Some(Box::new(recorder.clone())));
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(caller),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(
+        recorder.events(),
+        vec![
+            CheckpointEvent::Checkpoint(0),
+            CheckpointEvent::Checkpoint(1),
+            CheckpointEvent::Revert(1),
+            CheckpointEvent::Discard(0),
+        ]
+    );
+}
+
+#[test]
+fn test_transact_rlp() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = Address::from_low_u64_be(0x1234);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver),
+        U256::from(100),
+        U256::from(21_000),
+        vec![],
+    );
+    let raw = rlp::encode(&tx.transaction);
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_rlp(&raw, TransactOptions::exec_with_no_tracing())
+        .expect("valid transaction")
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert_eq!(executed.gas_used, U256::from(21_000));
+    assert_eq!(
+        state.balance(&receiver.with_evm_space()).unwrap(),
+        U256::from(100)
+    );
+
+    // Truncated bytes are malformed.
+    for malformed in [&raw[..raw.len() - 1], &[0xc0][..]] {
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact_rlp(malformed, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+        assert!(
+            matches!(
+                outcome,
+                ExecutionOutcome::NotExecutedDrop(TxDropError::InvalidRlp(_))
+            ),
+            "unexpected outcome {:?}",
+            outcome
+        );
+    }
+}
+
+#[test]
+fn test_transact_rlp_eip2930() {
+    let mut params = CommonParams::default();
+    params.transition_numbers.eip2930 = 0;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    assert!(spec.eip2930);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = Address::from_low_u64_be(0x1234);
+
+    let tx = Transaction::from(Eip2930Transaction {
+        chain_id: 1,
+        nonce: U256::zero(),
+        gas_price: U256::one(),
+        gas: U256::from(30_000),
+        action: Action::Call(receiver),
+        value: U256::from(100),
+        data: vec![],
+        access_list: vec![AccessListItem {
+            address: receiver,
+            storage_keys: vec![H256::zero()],
+        }],
+    })
+    .sign(&sender.secret());
+
+    // The raw transaction is its EIP-2718 envelope, which is hashed.
+    let raw = tx.transaction.encoded();
+    assert_eq!(raw[0], EIP2930_TYPE);
+    assert_eq!(tx.hash(), keccak(&raw));
+    // In a list, the envelope is a byte string.
+    let decoded: TransactionWithSignature = rlp::decode(&rlp::encode(&tx.transaction)).unwrap();
+    assert_eq!(decoded.transaction, tx.transaction.transaction);
+    assert_eq!(decoded.hash(), tx.hash());
+
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_rlp(&raw, TransactOptions::exec_with_no_tracing())
+        .expect("valid transaction")
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert_eq!(executed.gas_used, U256::from(21_000 + 2400 + 1900));
+    assert_eq!(
+        state.balance(&receiver.with_evm_space()).unwrap(),
+        U256::from(100)
+    );
+}
+
+#[test]
+fn test_selfdestructed() {
+    // CALLER SELFDESTRUCT
+    let code: Vec<u8> = "33ff".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(executed.selfdestructed, vec![contract.with_evm_space()]);
+}
+
+#[test]
+fn test_refund_to_author() {
+    let machine = new_machine_for_test();
+    let mut env = Env::default();
+    env.author = Address::from_low_u64_be(0xabcd);
+    let initial_balance = U256::from(1_000_000_000_000u64);
+
+    for percent in [0u64, 50, 100] {
+        let mut spec = machine.spec(env.number);
+        spec.refund_to_author_percent = percent;
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, initial_balance);
+        // A transfer using 21000 of 40000 gas is charged 3/4 of the gas
+        // limit, the remaining 10000 is refunded.
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(Address::from_low_u64_be(0x1234)),
+            U256::zero(),
+            U256::from(40000),
+            vec![],
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .unwrap();
+        assert_eq!(executed.fee, U256::from(30000));
+
+        let author_refund = U256::from(100 * percent);
+        let sender_refund = U256::from(10000) - author_refund;
+        assert_eq!(
+            state.balance(&env.author.with_evm_space()).unwrap(),
+            author_refund
+        );
+        assert_eq!(
+            state.balance(&sender.address().with_evm_space()).unwrap(),
+            initial_balance - U256::from(40000) + sender_refund
+        );
+        assert_eq!(
+            executed
+                .internal_transfers
+                .iter()
+                .any(|transfer| transfer.to == AddressPocket::Balance(env.author.with_evm_space())),
+            percent != 0
+        );
+    }
+}
+
+#[test]
+fn test_check_intrinsic_gas() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut state = new_state_for_test();
+    let key = Random.generate().unwrap();
+    let data = vec![0u8, 1u8];
+    let intrinsic_gas = gas_required_for(false, &data, &[], &spec);
+
+    for gas in [intrinsic_gas, intrinsic_gas + 1, intrinsic_gas - 1] {
+        // The sender is not funded, the check does not read the state.
+        let tx = make_signed_transaction(
+            &key,
+            U256::zero(),
+            Action::Call(Address::from_low_u64_be(0x1234)),
+            U256::zero(),
+            U256::from(gas),
+            data.clone(),
+        );
+        let result = TXExecutor::new(&mut state, &env, &machine, &spec).check_intrinsic_gas(&tx);
+        if gas >= intrinsic_gas {
+            assert!(result.is_ok());
+        } else {
+            match result {
+                Err(TxDropError::NotEnoughBaseGas { expected, actual }) => {
+                    assert_eq!(expected, intrinsic_gas);
+                    assert_eq!(actual, gas);
+                }
+                _ => panic!("unexpected result {:?}", result),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_check_intrinsic_gas_chain_id() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut state = new_state_for_test();
+    let key = Random.generate().unwrap();
+
+    for chain_id in [Some(1), None, Some(2)] {
+        let tx = Transaction::from(Eip155Transaction {
+            nonce: U256::zero(),
+            gas_price: U256::one(),
+            gas: U256::from(21000),
+            value: U256::zero(),
+            action: Action::Call(Address::from_low_u64_be(0x1234)),
+            chain_id,
+            data: vec![],
+        })
+        .sign(&key.secret());
+        let result = TXExecutor::new(&mut state, &env, &machine, &spec).check_intrinsic_gas(&tx);
+        match result {
+            Ok(()) if chain_id != Some(2) => {}
+            Err(TxDropError::ChainIdMismatch {
+                expected: 1,
+                got: 2,
+            }) if chain_id == Some(2) => {}
+            _ => panic!("unexpected result {:?}", result),
+        }
+    }
+}
+
+#[test]
+fn test_check_intrinsic_gas_transaction_size() {
+    let mut params = CommonParams::default();
+    params.max_transaction_size = 1024;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+
+    for data_size in [512, 1024] {
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(Address::from_low_u64_be(0x1234)),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![1u8; data_size],
+        );
+        let result = TXExecutor::new(&mut state, &env, &machine, &spec).check_intrinsic_gas(&tx);
+        if data_size == 512 {
+            assert!(result.is_ok());
+            continue;
+        }
+        match result {
+            Err(TxDropError::TransactionTooLarge { size, limit: 1024 }) => {
+                assert_eq!(size, tx.rlp_size())
+            }
+            _ => panic!("unexpected result {:?}", result),
+        }
+
+        // The execution runs the same checks.
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+        match outcome {
+            ExecutionOutcome::NotExecutedDrop(TxDropError::TransactionTooLarge { .. }) => {}
+            _ => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_ecrecover_input_length() {
+    let ecrecover = Address::from_low_u64_be(1);
+    let input: Vec<u8> = "47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b650acf9d3f5f0a2c799776a1254355d5f4061762a237396a99a0e0e3fc2bcd6729514a0dacb2e623ac4abd157cb18163ff942280db4d5caad66ddf941ba12e03"
+        .from_hex()
+        .unwrap();
+    let recovered: Vec<u8> = "000000000000000000000000c08b5542d177ac6686946920409741463a15dddb"
+        .from_hex()
+        .unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+
+    // The correct input, the input with trailing bytes ignored, and short
+    // inputs padded with zeros into a signature with zero r and s.
+    let cases = vec![
+        (input.clone(), recovered.clone()),
+        ([&input[..], &[0xff; 32]].concat(), recovered),
+        (input[..64].to_vec(), vec![]),
+        (vec![], vec![]),
+    ];
+    for (nonce, (data, output)) in cases.into_iter().enumerate() {
+        let tx = make_signed_transaction(
+            &sender,
+            U256::from(nonce),
+            Action::Call(ecrecover),
+            U256::zero(),
+            U256::from(100_000),
+            data,
+        );
+        let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error")
+            .successfully_executed()
+            .expect("malformed input should not fail the call");
+        assert_eq!(executed.output, output);
+    }
+}
+
+#[test]
+fn test_tx_hash() {
+    // The signed transaction in the example of EIP-155.
+    let raw: Vec<u8> = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        .from_hex()
+        .unwrap();
+    let transaction: TransactionWithSignature = rlp::decode(&raw).unwrap();
+    let public = transaction.recover_public().unwrap();
+    let tx = SignedTransaction::new(public, transaction);
+
+    let expected: H256 = "33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+        .parse()
+        .unwrap();
+    assert_eq!(tx_hash(&tx), expected);
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let mut state = new_state_for_test();
+    state
+        .add_balance(
+            &tx.sender(),
+            &U256::from(10).pow(U256::from(19)),
+            CleanupMode::NoEmpty,
+            U256::zero(),
+        )
+        .unwrap();
+    state.set_nonce(&tx.sender(), tx.nonce()).unwrap();
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .expect("no db error")
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert_eq!(executed.transaction_hash, expected);
+}
+
+#[test]
+fn test_env_shared_across_transactions() {
+    let machine = new_machine_for_test();
+    let env = Env {
+        timestamp: 1_000,
+        ..Default::default()
+    };
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    // TIMESTAMP PUSH1 0x00 SSTORE
+    deploy_code_for_test(&mut state, &contract, "4260005500".from_hex().unwrap());
+
+    let mut executor = TXExecutor::new(&mut state, &env, &machine, &spec);
+    for nonce in 0..2u64 {
+        let tx = make_signed_transaction(
+            &sender,
+            U256::from(nonce),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        executor
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+        assert!(std::ptr::eq(executor.env(), &env));
+    }
+    drop(executor);
+
+    assert_eq!(
+        state
+            .storage_at(&contract.with_evm_space(), &[0u8; 32])
+            .unwrap(),
+        U256::from(1_000)
+    );
+}
+
+#[test]
+fn test_transient_storage_cleared_after_transaction() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = Address::from_low_u64_be(0x1234).with_evm_space();
+    state.set_transient_storage(&receiver, vec![0; 32], U256::one());
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver.address),
+        U256::zero(),
+        U256::from(21_000),
+        vec![],
+    );
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert_eq!(
+        state.transient_storage_at(&receiver, &[0; 32]),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_eip1559_fee_split() {
+    let machine = new_machine_for_test();
+    let env = Env {
+        author: Address::from_low_u64_be(0xabcd),
+        base_fee: U256::from(5),
+        ..Default::default()
+    };
+    let spec = machine.spec(env.number);
+    let initial_balance = U256::from(1_000_000_000_000u64);
+
+    let mut state = new_state_for_test();
+    state.add_total_issued(initial_balance);
+    let sender = new_funded_account(&mut state, initial_balance);
+    let tx = |max_fee: u64| {
+        Transaction::from(Eip1559Transaction {
+            chain_id: 1,
+            nonce: U256::zero(),
+            max_priority_fee_per_gas: U256::from(2),
+            max_fee_per_gas: U256::from(max_fee),
+            gas: U256::from(21000),
+            action: Action::Call(Address::from_low_u64_be(0x1234)),
+            value: U256::zero(),
+            data: vec![],
+            access_list: vec![],
+        })
+        .sign(&sender.secret())
+    };
+
+    let raw = tx(10).transaction.encoded();
+    assert_eq!(raw[0], EIP1559_TYPE);
+    let decoded = TransactionWithSignature::from_raw(&raw).unwrap();
+    assert_eq!(decoded.max_priority_fee_per_gas(), Some(&U256::from(2)));
+    assert_eq!(decoded.gas_price(), &U256::from(10));
+
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx(4), TransactOptions::exec_with_no_tracing())
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        ExecutionOutcome::NotExecutedDrop(TxDropError::MaxFeeLessThanBaseFee { .. })
+    ));
+
+    // The effective gas price is min(10, 5 + 2).
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx(10), TransactOptions::exec_with_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+    let burnt = U256::from(21000 * 5);
+    let tip = U256::from(21000 * 2);
+    assert_eq!(executed.fee, burnt + tip);
+    assert_eq!(
+        state.balance(&sender.address().with_evm_space()).unwrap(),
+        initial_balance - burnt - tip
+    );
+    assert_eq!(state.balance(&env.author.with_evm_space()).unwrap(), tip);
+    assert_eq!(state.total_issued_tokens(), initial_balance - burnt);
+
+    let transfer_amount = |to: AddressPocket| {
+        executed
+            .internal_transfers
+            .iter()
+            .find(|transfer| transfer.from == AddressPocket::GasPayment && transfer.to == to)
+            .map(|transfer| transfer.value)
+    };
+    assert_eq!(transfer_amount(AddressPocket::MintBurn), Some(burnt));
+    assert_eq!(
+        transfer_amount(AddressPocket::Balance(env.author.with_evm_space())),
+        Some(tip)
+    );
+}
+
+#[test]
+fn test_eip1559_max_fee_checks() {
+    let machine = new_machine_for_test();
+    let env = Env {
+        author: Address::from_low_u64_be(0xabcd),
+        base_fee: U256::from(5),
+        ..Default::default()
+    };
+    let spec = machine.spec(env.number);
+    // Enough for the effective gas price, not for the max fee.
+    let initial_balance = U256::from(21000 * 50);
+
+    let mut state = new_state_for_test();
+    state.add_total_issued(initial_balance);
+    let sender = new_funded_account(&mut state, initial_balance);
+    let tx = |max_fee: u64, max_priority_fee: u64| {
+        Transaction::from(Eip1559Transaction {
+            chain_id: 1,
+            nonce: U256::zero(),
+            max_priority_fee_per_gas: U256::from(max_priority_fee),
+            max_fee_per_gas: U256::from(max_fee),
+            gas: U256::from(21000),
+            action: Action::Call(Address::from_low_u64_be(0x1234)),
+            value: U256::zero(),
+            data: vec![],
+            access_list: vec![],
+        })
+        .sign(&sender.secret())
+    };
+
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx(10, 11), TransactOptions::exec_with_no_tracing())
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        ExecutionOutcome::NotExecutedDrop(TxDropError::PriorityFeeGreaterThanMaxFee { .. })
+    ));
+
+    // The balance is checked against the max fee, and the gas charged at the
+    // effective price min(100, 5 + 2) is split between burn and tip.
+    let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx(100, 2), TransactOptions::exec_with_no_tracing())
+        .unwrap();
+    let burnt = U256::from(21000 * 5);
+    let tip = U256::from(21000 * 2);
+    match outcome {
+        ExecutionOutcome::ExecutionErrorBumpNonce(
+            ExecutionError::NotEnoughCash {
+                required,
+                actual_gas_cost,
+                ..
+            },
+            executed,
+        ) => {
+            assert_eq!(required, U512::from(21000 * 100));
+            assert_eq!(actual_gas_cost, burnt + tip);
+            assert_eq!(executed.gas_charged, U256::from(21000));
+        }
+        _ => panic!("unexpected outcome {:?}", outcome),
+    }
+    assert_eq!(
+        state.balance(&sender.address().with_evm_space()).unwrap(),
+        initial_balance - burnt - tip
+    );
+    assert_eq!(state.balance(&env.author.with_evm_space()).unwrap(), tip);
+    assert_eq!(state.total_issued_tokens(), initial_balance - burnt);
+}
+
+#[test]
+fn test_transact_many() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = Address::from_low_u64_be(0x1234);
+    let reverting = Address::from_low_u64_be(0x5678);
+    // PUSH1 0 PUSH1 0 REVERT
+    deploy_code_for_test(&mut state, &reverting, "60006000fd".from_hex().unwrap());
+
+    let txs: Vec<_> = [receiver, reverting, receiver]
+        .iter()
+        .enumerate()
+        .map(|(nonce, to)| {
+            make_signed_transaction(
+                &sender,
+                U256::from(nonce),
+                Action::Call(*to),
+                U256::from(100),
+                U256::from(100_000),
+                vec![],
+            )
+        })
+        .collect();
+    let outcomes = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_many(&txs, |_| TransactOptions::exec_with_no_tracing())
+        .expect("no db error");
+
+    assert_eq!(outcomes.len(), 3);
+    assert!(matches!(outcomes[0], ExecutionOutcome::Finished(_)));
+    assert!(matches!(
+        outcomes[1],
+        ExecutionOutcome::ExecutionErrorBumpNonce(ExecutionError::VmError(vm::Error::Reverted), _)
+    ));
+    assert!(matches!(outcomes[2], ExecutionOutcome::Finished(_)));
+    assert_eq!(
+        state.nonce(&sender.address().with_evm_space()).unwrap(),
+        U256::from(3)
+    );
+    assert_eq!(
+        state.balance(&receiver.with_evm_space()).unwrap(),
+        U256::from(200)
+    );
+    assert!(state
+        .balance(&reverting.with_evm_space())
+        .unwrap()
+        .is_zero());
+}
+
+#[test]
+fn test_contract_start_nonce() {
+    // PUSH1 0 PUSH1 0 RETURN
+    let init_code: Vec<u8> = "60006000f3".from_hex().unwrap();
+
+    // Pre-EIP-161 creation starts the contract nonce at 0.
+    for contract_start_nonce in [1u64, 0] {
+        let machine = new_machine_for_test();
+        let env = Env::default();
+        let mut spec = machine.spec(env.number);
+        spec.contract_start_nonce = U256::from(contract_start_nonce);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let (new_address, _) = contract_address(
+            CreateContractAddress::FromSenderNonce,
+            env.number.into(),
+            &sender.address().with_evm_space(),
+            &U256::zero(),
+            &init_code,
+        );
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Create,
+            U256::zero(),
+            U256::from(100_000),
+            init_code.clone(),
+        );
+        TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+        assert!(state.exists(&new_address).unwrap());
+        assert_eq!(
+            state.nonce(&new_address).unwrap(),
+            U256::from(contract_start_nonce)
+        );
+    }
+}
+
+#[test]
+fn test_state_diff_of_transfer() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let initial_balance = U256::from(1_000_000_000_000u64);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, initial_balance);
+    let sender_address = sender.address().with_evm_space();
+    let receiver = Address::from_low_u64_be(0x1234).with_evm_space();
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver.address),
+        U256::from(100),
+        U256::from(21000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_state_diff())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    let diff = executed.state_diff.expect("state diff is collected");
+    assert_eq!(diff.accounts.len(), 2);
+    assert_eq!(
+        diff.accounts[&sender_address],
+        AccountDiff {
+            address: sender_address,
+            before: Some(AccountSnapshot {
+                balance: initial_balance,
+                nonce: U256::zero(),
+                code_hash: KECCAK_EMPTY,
+            }),
+            after: Some(AccountSnapshot {
+                balance: initial_balance - U256::from(21000 + 100),
+                nonce: U256::one(),
+                code_hash: KECCAK_EMPTY,
+            }),
+            storage: Default::default(),
+        }
+    );
+    let receiver_diff = &diff.accounts[&receiver];
+    assert_eq!(receiver_diff.before, None);
+    assert_eq!(
+        receiver_diff.after.as_ref().map(|account| account.balance),
+        Some(U256::from(100))
+    );
+
+    // The diff is only collected on request.
+    let tx = make_signed_transaction(
+        &sender,
+        U256::one(),
+        Action::Call(receiver.address),
+        U256::from(100),
+        U256::from(21000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .unwrap()
+        .successfully_executed()
+        .unwrap();
+    assert_eq!(executed.state_diff, None);
+}
+
+#[test]
+fn test_transact_with_diff() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+    let initial_balance = U256::from(1_000_000_000_000u64);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, initial_balance);
+    let sender_address = sender.address().with_evm_space();
+    let receiver = Address::from_low_u64_be(0x1234).with_evm_space();
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver.address),
+        U256::from(100),
+        U256::from(21000),
+        vec![],
+    );
+    let (outcome, diff) = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_with_diff(&tx, TransactOptions::exec_with_no_tracing())
+        .unwrap();
+    assert!(outcome.successfully_executed().is_some());
+
+    let balance_change = |address| {
+        let account_diff: &AccountDiff = diff
+            .iter()
+            .find(|account_diff| &account_diff.address == address)
+            .expect("the account is changed");
+        (
+            account_diff.before.as_ref().map(|account| account.balance),
+            account_diff.after.as_ref().map(|account| account.balance),
+        )
+    };
+    assert_eq!(
+        balance_change(&sender_address),
+        (
+            Some(initial_balance),
+            Some(initial_balance - U256::from(21000 + 100))
+        )
+    );
+    assert_eq!(balance_change(&receiver), (None, Some(U256::from(100))));
+
+    // The simulation leaves the state unchanged.
+    assert_eq!(state.balance(&sender_address).unwrap(), initial_balance);
+    assert_eq!(state.nonce(&sender_address).unwrap(), U256::zero());
+    assert!(!state.exists(&receiver).unwrap());
+}
+
+#[test]
+fn test_max_depth() {
+    // PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 ADDRESS GAS CALL POP STOP
+    let code: Vec<u8> = "60005460010160005560006000600060006000305af15000"
+        .from_hex()
+        .unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let mut spec = machine.spec(env.number);
+    spec.max_depth = 4;
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(1_000_000),
+        vec![],
+    );
+    // The call from the frame at depth 4 fails without entering a frame,
+    // while the outer frames continue and succeed.
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert_eq!(
+        state
+            .storage_at(&contract.with_evm_space(), &[0u8; 32])
+            .unwrap(),
+        U256::from(5)
+    );
+}
+
+#[test]
+fn test_no_gas() {
+    // PUSH2 10000 JUMPDEST PUSH1 1 SWAP1 SUB DUP1 PUSH1 3 JUMPI
+    // PUSH1 1 PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "6127105b6001900380600357600160005500".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    for no_gas in [false, true] {
+        let mut state = new_state_for_test();
+        let balance = U256::from(1_000_000_000_000u64);
+        let sender = new_funded_account(&mut state, balance);
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        // The loop costs about 260000 gas.
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        let options = if no_gas {
+            TransactOptions::no_gas()
+        } else {
+            TransactOptions::exec_with_no_tracing()
+        };
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options)
+            .unwrap();
+        let stored = state
+            .storage_at(&contract.with_evm_space(), &[0u8; 32])
+            .unwrap();
+        if no_gas {
+            let executed = outcome
+                .successfully_executed()
+                .expect("transaction should be executed");
+            assert!(executed.gas_used > U256::from(100_000));
+            assert_eq!(executed.fee, U256::zero());
+            assert_eq!(stored, U256::one());
+            assert_eq!(
+                state.balance(&sender.address().with_evm_space()).unwrap(),
+                balance
+            );
+        } else {
+            assert!(matches!(
+                outcome,
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::OutOfGas),
+                    _
+                )
+            ));
+            assert_eq!(stored, U256::zero());
+        }
+    }
+}
+
+#[test]
+fn test_create_data_limit() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let mut spec = machine.spec(env.number);
+    // EIP-170
+    spec.create_data_limit = 24576;
+
+    for code_size in [24576u16, 24577] {
+        // PUSH2 code_size PUSH1 0 RETURN
+        let mut init_code = vec![0x61];
+        init_code.extend_from_slice(&code_size.to_be_bytes());
+        init_code.extend_from_slice(&[0x60, 0x00, 0xf3]);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let (new_address, _) = contract_address(
+            CreateContractAddress::FromSenderNonce,
+            env.number.into(),
+            &sender.address().with_evm_space(),
+            &U256::zero(),
+            &init_code,
+        );
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Create,
+            U256::zero(),
+            U256::from(10_000_000),
+            init_code,
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+
+        if code_size as usize <= spec.create_data_limit {
+            outcome
+                .successfully_executed()
+                .expect("transaction should be executed");
+            assert_eq!(
+                state.code_size(&new_address).unwrap(),
+                Some(code_size as usize)
+            );
+        } else {
+            match outcome {
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(vm::Error::OutOfGas),
+                    executed,
+                ) => assert!(executed.contracts_created.is_empty()),
+                _ => panic!("unexpected outcome {:?}", outcome),
+            }
+            assert!(!state.exists(&new_address).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_summarize_block() {
+    // PUSH1 0 PUSH1 0 LOG0
+    let init_code: Vec<u8> = "60006000a0".from_hex().unwrap();
+    // PUSH1 0 PUSH1 0 REVERT
+    let reverting_code: Vec<u8> = "60006000fd".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let reverting = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &reverting, reverting_code);
+    let (created, _) = contract_address(
+        CreateContractAddress::FromSenderNonce,
+        env.number.into(),
+        &sender.address().with_evm_space(),
+        &U256::zero(),
+        &init_code,
+    );
+
+    let txs = vec![
+        make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Create,
+            U256::zero(),
+            U256::from(100_000),
+            init_code,
+        ),
+        make_signed_transaction(
+            &sender,
+            U256::one(),
+            Action::Call(reverting),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        ),
+        // Dropped for the old nonce.
+        make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(reverting),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        ),
+    ];
+    let outcomes = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_many(&txs, |_| TransactOptions::exec_with_no_tracing())
+        .unwrap();
+    let gas_charged: U256 = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            ExecutionOutcome::Finished(executed)
+            | ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => Some(executed.gas_charged),
+            _ => None,
+        })
+        .fold(U256::zero(), |sum, gas| sum + gas);
+
+    let summary = summarize_block(&outcomes);
+    assert_eq!(summary.total_gas_used, gas_charged);
+    assert!(!summary.total_gas_used.is_zero());
+    assert_eq!(summary.total_logs, 1);
+    assert_eq!(summary.contracts_created, vec![created]);
+    assert_eq!(summary.reverted_count, 1);
+}
+
+#[test]
+fn test_eip3860_init_code() {
+    let mut params = CommonParams::default();
+    params.transition_numbers.eip3860 = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+    let env = Env {
+        number: 10,
+        ..Default::default()
+    };
+    let spec = machine.spec(env.number);
+    assert!(spec.eip3860 && !machine.spec(9).eip3860);
+
+    // 33 bytes of init code take 2 words.
+    let init_code = vec![0u8; 33];
+    let gas_without_eip3860 = gas_required_for(true, &init_code, &[], &machine.spec(9));
+    assert_eq!(
+        gas_required_for(true, &init_code, &[], &spec),
+        gas_without_eip3860 + 4
+    );
+    // Only the init code of a creation is charged.
+    assert_eq!(
+        gas_required_for(false, &init_code, &[], &spec),
+        21000 + 33 * 4
+    );
+
+    let limit = 2 * spec.create_data_limit;
+    for size in [limit, limit + 1] {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Create,
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![0u8; size],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+        match outcome {
+            ExecutionOutcome::Finished(_) if size == limit => {}
+            ExecutionOutcome::NotExecutedDrop(TxDropError::InitCodeTooLarge {
+                size: actual,
+                limit: expected,
+            }) if size > limit => {
+                assert_eq!((actual, expected), (size, limit));
+            }
+            _ => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_evm_space_before_cip90() {
+    let mut params = CommonParams::default();
+    params.transition_numbers.cip90b = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+
+    for number in [9, 10] {
+        let env = Env {
+            number,
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(Address::from_low_u64_be(0x1234)),
+            U256::from(1000),
+            U256::from(21000),
+            vec![],
+        );
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .expect("no db error");
+        match outcome {
+            ExecutionOutcome::NotExecutedDrop(TxDropError::SpaceNotActive(Space::Ethereum))
+                if number < 10 =>
+            {
+                assert!(state
+                    .nonce(&sender.address().with_evm_space())
+                    .unwrap()
+                    .is_zero());
+            }
+            ExecutionOutcome::Finished(_) if number >= 10 => {}
+            _ => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_struct_logs() {
+    // PUSH1 2 PUSH1 3 ADD STOP
+    let code: Vec<u8> = "600260030100".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let contract = Address::from_low_u64_be(0x1234);
+    deploy_code_for_test(&mut state, &contract, code);
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_struct_logs())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    let logs = &executed.struct_logs;
+    assert_eq!(
+        logs.iter().map(|log| log.op).collect::<Vec<_>>(),
+        vec!["PUSH1", "PUSH1", "ADD", "STOP"]
+    );
+    assert_eq!(
+        logs.iter().map(|log| log.pc).collect::<Vec<_>>(),
+        vec![0, 2, 4, 5]
+    );
+    assert!(logs.iter().all(|log| log.depth == 1));
+    assert_eq!(logs[2].gas_cost, U256::from(3));
+    assert_eq!(logs[3].gas, logs[2].gas - logs[2].gas_cost);
+    assert_eq!(logs[2].stack, vec![U256::from(2), U256::from(3)]);
+    assert_eq!(logs[3].stack, vec![U256::from(5)]);
+    assert!(logs.iter().all(|log| log.memory.is_empty()));
+
+    // Not collected by default.
+    let tx = make_signed_transaction(
+        &sender,
+        U256::one(),
+        Action::Call(contract),
+        U256::zero(),
+        U256::from(100_000),
+        vec![],
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+    assert!(executed.struct_logs.is_empty());
+}
+
+#[test]
+fn test_created_code() {
+    // The child returns the runtime code 0x42:
+    // PUSH1 0x42 PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+    // The parent creates the child and returns the runtime code 0x43:
+    // PUSH10 child PUSH1 0 MSTORE PUSH1 10 PUSH1 22 PUSH1 0 CREATE POP
+    // PUSH1 0x43 PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+    let init_code: Vec<u8> = "69604260005360016000f3600052600a60166000f050\
+                              604360005360016000f3"
+        .from_hex()
+        .unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let (parent, _) = contract_address(
+        CreateContractAddress::FromSenderNonce,
+        env.number.into(),
+        &sender.address().with_evm_space(),
+        &U256::zero(),
+        &init_code,
+    );
+
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Create,
+        U256::zero(),
+        U256::from(1_000_000),
+        init_code,
+    );
+    let executed = TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+
+    assert_eq!(executed.contracts_created.len(), 2);
+    assert_eq!(executed.contracts_created[1], parent);
+    let child = executed.contracts_created[0];
+    assert_eq!(
+        executed.created_code(),
+        vec![(child.address, vec![0x42]), (parent.address, vec![0x43])]
+    );
+}
+
+#[test]
+fn test_sha3_and_memory_gas() {
+    // PUSH2 1024 PUSH1 0 SHA3 POP STOP
+    let code: Vec<u8> = "6104006000205000".from_hex().unwrap();
+
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let default_spec = machine.spec(env.number);
+
+    let gas_used = |spec: &Spec| {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        TXExecutor::new(&mut state, &env, &machine, spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed")
+            .gas_used
+    };
+
+    let base = gas_used(&default_spec);
+    // The 1024 bytes take 32 words.
+    let doubled_sha3 = default_spec
+        .clone()
+        .with_sha3_gas(default_spec.sha3_gas, default_spec.sha3_word_gas * 2);
+    assert_eq!(
+        gas_used(&doubled_sha3),
+        base + U256::from(32 * default_spec.sha3_word_gas)
+    );
+    let doubled_memory = default_spec
+        .clone()
+        .with_memory_gas(default_spec.memory_gas * 2);
+    assert_eq!(
+        gas_used(&doubled_memory),
+        base + U256::from(32 * default_spec.memory_gas)
+    );
+}
+
+#[test]
+fn test_prestate_tracer() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let receiver = new_funded_account(&mut state, U256::from(5000));
+    let sender_address = sender.address().with_evm_space();
+    let receiver_address = receiver.address().with_evm_space();
+
+    let tracer = PrestateTracer::default();
+    state.set_access_observer(Some(Box::new(tracer.clone())));
+    let tx = make_signed_transaction(
+        &sender,
+        U256::zero(),
+        Action::Call(receiver.address()),
+        U256::from(1000),
+        U256::from(21000),
+        vec![],
+    );
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::exec_with_no_tracing())
+        .unwrap()
+        .successfully_executed()
+        .expect("transaction should be executed");
+    state.set_access_observer(None);
+
+    let prestate = tracer.into_prestate();
+    let account = |balance: u64| AccountState {
+        balance: U256::from(balance),
+        nonce: U256::zero(),
+        code_hash: KECCAK_EMPTY,
+        code: Some(vec![]),
+        storage: Default::default(),
+    };
+    assert_eq!(prestate[&sender_address], account(1_000_000_000_000));
+    assert_eq!(prestate[&receiver_address], account(5000));
+    // The state after the transaction.
+    assert_eq!(state.nonce(&sender_address).unwrap(), U256::one());
+    assert_eq!(state.balance(&receiver_address).unwrap(), U256::from(6000));
+}
+
+#[test]
+fn test_call_suicided_contract() {
+    // CALLDATASIZE PUSH1 6 JUMPI CALLER SELFDESTRUCT
+    // JUMPDEST PUSH1 32 PUSH1 0 RETURN
+    let victim_code: Vec<u8> = "3660065733ff5b60206000f3".from_hex().unwrap();
+    // Calls the victim without data, which kills it, then with one byte of
+    // data, and stores the success and the return data size of the latter.
+    // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL POP
+    // PUSH1 0 PUSH1 0 PUSH1 1 PUSH1 0 PUSH1 0 PUSH2 0x5678 GAS CALL
+    // PUSH1 1 SSTORE RETURNDATASIZE PUSH1 0 SSTORE
+    // Then stores the code size, the code hash and the copied code of the
+    // victim.
+    // PUSH2 0x5678 EXTCODESIZE PUSH1 2 SSTORE
+    // PUSH2 0x5678 EXTCODEHASH PUSH1 3 SSTORE
+    // PUSH1 12 PUSH1 0 PUSH1 0 PUSH2 0x5678 EXTCODECOPY
+    // PUSH1 0 MLOAD PUSH1 4 SSTORE STOP
+    let caller_code: Vec<u8> = concat!(
+        "600060006000600060006156785af150600060006001600060006156785af16001553d600055",
+        "6156783b600255",
+        "6156783f600355",
+        "600c600060006156783c",
+        "60005160045500"
+    )
+    .from_hex()
+    .unwrap();
+
+    let mut params = CommonParams::default();
+    params.transition_numbers.killed_contract_no_code = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+    for number in [10, 9] {
+        let env = Env {
+            number,
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number);
+        let killed_contract_no_code = number >= 10;
+        assert_eq!(spec.killed_contract_no_code, killed_contract_no_code);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let caller = Address::from_low_u64_be(0x1234);
+        let victim = Address::from_low_u64_be(0x5678);
+        deploy_code_for_test(&mut state, &caller, caller_code.clone());
+        deploy_code_for_test(&mut state, &victim, victim_code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(caller),
+            U256::zero(),
+            U256::from(1_000_000),
+            vec![],
+        );
+        TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        let caller = caller.with_evm_space();
+        let storage_at = |slot: u64| {
+            let key = H256::from_low_u64_be(slot).as_bytes().to_vec();
+            state.storage_at(&caller, &key).unwrap()
+        };
+        assert_eq!(storage_at(1), U256::one());
+        if killed_contract_no_code {
+            // The second call succeeds as a plain transfer, without returning
+            // data, and the victim looks like an account without code.
+            assert_eq!(storage_at(0), U256::zero());
+            assert_eq!(storage_at(2), U256::zero());
+            assert_eq!(
+                storage_at(3),
+                U256::from_big_endian(KECCAK_EMPTY.as_bytes())
+            );
+            assert_eq!(storage_at(4), U256::zero());
+        } else {
+            assert_eq!(storage_at(0), U256::from(32));
+            assert_eq!(storage_at(2), U256::from(victim_code.len()));
+            assert_eq!(
+                storage_at(3),
+                U256::from_big_endian(keccak(&victim_code).as_bytes())
+            );
+            let mut copied = [0u8; 32];
+            copied[..12].copy_from_slice(&victim_code);
+            assert_eq!(storage_at(4), U256::from_big_endian(&copied));
+        }
+        assert_eq!(state.code(&victim.with_evm_space()).unwrap(), None);
+    }
+}
+
+#[test]
+fn test_bump_nonce_on_not_enough_cash() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    for bump_nonce in [true, false] {
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(30_000));
+        let sender_address = sender.address().with_evm_space();
+        // The sender can afford the gas but not the value.
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(Address::from_low_u64_be(0x1234)),
+            U256::from(10_000),
+            U256::from(21000),
+            vec![],
+        );
+
+        let mut options = TransactOptions::exec_with_no_tracing();
+        options.check_settings.bump_nonce_on_not_enough_cash = bump_nonce;
+        let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, options)
+            .expect("no db error");
+        assert!(matches!(
+            outcome,
+            ExecutionOutcome::ExecutionErrorBumpNonce(ExecutionError::NotEnoughCash { .. }, _)
+        ));
+
+        // The gas is charged in both modes.
+        assert_eq!(state.balance(&sender_address).unwrap(), U256::from(9000));
+        let expected_nonce = if bump_nonce { 1 } else { 0 };
+        assert_eq!(
+            state.nonce(&sender_address).unwrap(),
+            U256::from(expected_nonce)
+        );
+    }
+}
+
+#[test]
+fn test_transact_stream() {
+    let machine = new_machine_for_test();
+    let env = Env::default();
+    let spec = machine.spec(env.number);
+
+    let mut state = new_state_for_test();
+    let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+    let sender_address = sender.address().with_evm_space();
+    let receiver = Address::from_low_u64_be(0x1234);
+
+    let txs: Vec<_> = (0..5)
+        .map(|nonce| {
+            make_signed_transaction(
+                &sender,
+                U256::from(nonce),
+                Action::Call(receiver),
+                U256::from(100),
+                U256::from(21000),
+                vec![],
+            )
+        })
+        .collect();
+    let mut received = vec![];
+    TXExecutor::new(&mut state, &env, &machine, &spec)
+        .transact_stream(
+            &txs,
+            |_| TransactOptions::exec_with_no_tracing(),
+            |i, outcome| {
+                assert!(matches!(outcome, ExecutionOutcome::Finished(_)));
+                received.push(i);
+            },
+        )
+        .expect("no db error");
+
+    assert_eq!(received, vec![0, 1, 2, 3, 4]);
+    assert_eq!(state.nonce(&sender_address).unwrap(), U256::from(5));
+    assert_eq!(
+        state.balance(&receiver.with_evm_space()).unwrap(),
+        U256::from(500)
+    );
+}
+
+#[test]
+fn test_prevrandao_transition() {
+    // DIFFICULTY PUSH1 0 SSTORE STOP
+    let code: Vec<u8> = "4460005500".from_hex().unwrap();
+
+    let mut params = CommonParams::default();
+    params.transition_numbers.eip4399 = 10;
+    let machine = new_machine_with_builtin(params, VmFactory::new(1024 * 32));
+
+    for number in [9, 10] {
+        let env = Env {
+            number,
+            difficulty: U256::from(0x1234),
+            random: H256::from_low_u64_be(0x5678),
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number);
+        assert_eq!(spec.is_merge_active(), number >= 10);
+
+        let mut state = new_state_for_test();
+        let sender = new_funded_account(&mut state, U256::from(1_000_000_000_000u64));
+        let contract = Address::from_low_u64_be(0x1234);
+        deploy_code_for_test(&mut state, &contract, code.clone());
+
+        let tx = make_signed_transaction(
+            &sender,
+            U256::zero(),
+            Action::Call(contract),
+            U256::zero(),
+            U256::from(100_000),
+            vec![],
+        );
+        TXExecutor::new(&mut state, &env, &machine, &spec)
+            .transact(&tx, TransactOptions::exec_with_no_tracing())
+            .unwrap()
+            .successfully_executed()
+            .expect("transaction should be executed");
+
+        let expected = if number >= 10 { 0x5678 } else { 0x1234 };
+        assert_eq!(
+            state
+                .storage_at(&contract.with_evm_space(), &[0u8; 32])
+                .unwrap(),
+            U256::from(expected)
+        );
+    }
+}
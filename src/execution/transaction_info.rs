@@ -1,4 +1,8 @@
-use cfx_types::{AddressWithSpace, Space, U256};
+use crate::state::State;
+use cfx_state::state_trait::StateOpsTrait;
+use cfx_statedb::Result as DbResult;
+use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256};
+use cfxkey::public_to_address;
 use primitives::{Action, SignedTransaction};
 use std::borrow::Cow;
 use Cow::{Borrowed, Owned};
@@ -7,10 +11,38 @@ pub trait TransactionInfo {
     fn sender(&self) -> Cow<AddressWithSpace>;
     fn nonce(&self) -> Cow<U256>;
     fn gas(&self) -> Cow<U256>;
+    /// The gas price, or the max fee per gas of an EIP-1559 transaction.
     fn gas_price(&self) -> Cow<U256>;
     fn data(&self) -> Cow<[u8]>;
     fn action(&self) -> Cow<Action>;
     fn value(&self) -> Cow<U256>;
+    /// The sender recovered from the signature, `None` if the signature is
+    /// invalid.
+    fn recover_sender(&self) -> Option<Address>;
+    fn transaction_hash(&self) -> H256;
+
+    /// The EIP-2930 access list declared by the transaction.
+    fn access_list(&self) -> Cow<[(Address, Vec<H256>)]> {
+        Borrowed(&[])
+    }
+
+    /// The max priority fee per gas of an EIP-1559 transaction, `None` for
+    /// the transactions paying a flat gas price.
+    fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        None
+    }
+
+    /// The chain id the transaction is signed for, `None` if it is not
+    /// replay protected.
+    fn chain_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// The size of the RLP encoding of the transaction, `None` if the
+    /// transaction has no encoding.
+    fn rlp_size(&self) -> Option<usize> {
+        None
+    }
 
     fn space(&self) -> Space {
         Space::Ethereum
@@ -45,4 +77,65 @@ impl TransactionInfo for SignedTransaction {
     fn value(&self) -> Cow<U256> {
         Borrowed((**self).value())
     }
+
+    fn recover_sender(&self) -> Option<Address> {
+        self.transaction
+            .recover_public()
+            .ok()
+            .map(|public| public_to_address(&public))
+    }
+
+    fn transaction_hash(&self) -> H256 {
+        tx_hash(self)
+    }
+
+    fn access_list(&self) -> Cow<[(Address, Vec<H256>)]> {
+        match self.transaction.access_list() {
+            Some(access_list) => Owned(
+                access_list
+                    .iter()
+                    .map(|item| (item.address, item.storage_keys.clone()))
+                    .collect(),
+            ),
+            None => Borrowed(&[]),
+        }
+    }
+
+    fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        self.transaction.max_priority_fee_per_gas().copied()
+    }
+
+    fn chain_id(&self) -> Option<u32> {
+        self.transaction.chain_id()
+    }
+
+    fn rlp_size(&self) -> Option<usize> {
+        Some(self.rlp_size())
+    }
+}
+
+/// The gas price actually paid per gas. An EIP-1559 transaction pays
+/// `base_fee` plus its priority fee, up to its max fee.
+pub fn effective_gas_price(tx: &impl TransactionInfo, base_fee: &U256) -> U256 {
+    match tx.max_priority_fee_per_gas() {
+        Some(priority_fee) => U256::min(*tx.gas_price(), base_fee.saturating_add(priority_fee)),
+        None => *tx.gas_price(),
+    }
+}
+
+/// The hash of the canonical RLP encoding of the signed transaction, computed
+/// once when the transaction is signed or decoded.
+pub fn tx_hash(tx: &SignedTransaction) -> H256 {
+    tx.hash()
+}
+
+/// Returns true if the transaction calls an account without code and carries
+/// no data.
+pub fn is_simple_transfer(tx: &dyn TransactionInfo, state: &State) -> DbResult<bool> {
+    match &*tx.action() {
+        Action::Call(address) if tx.data().is_empty() => {
+            Ok(!state.is_contract_with_code(&address.with_space(tx.space()))?)
+        }
+        _ => Ok(false),
+    }
 }
@@ -17,6 +17,9 @@ use cfx_types::{AddressWithSpace, U256};
 ///   2. refund sponsor balance
 ///   3. refund contract balance
 ///   4. kill the contract
+///
+/// Under `cancun_selfdestruct`, a contract not created in the transaction is
+/// not killed and only its balance is refunded.
 pub fn suicide(
     contract_address: &AddressWithSpace,
     refund_address: &AddressWithSpace,
@@ -25,11 +28,19 @@ pub fn suicide(
     substate: &mut Substate,
     tracer: &mut dyn VmObserve,
     account_start_nonce: U256,
+    created_in_transaction: bool,
 ) -> vm::Result<()> {
-    substate.suicides.insert(contract_address.clone());
+    let kill = !spec.cancun_selfdestruct || created_in_transaction;
+    if kill {
+        substate.suicides.insert(contract_address.clone());
+    }
     let balance = state.balance(contract_address)?;
 
     if refund_address == contract_address {
+        if !kill {
+            // The balance stays with the surviving contract.
+            return Ok(());
+        }
         tracer.trace_internal_transfer(
             AddressPocket::Balance(*contract_address),
             AddressPocket::MintBurn,
@@ -29,8 +29,8 @@ use super::super::{
 pub fn create_gas(context: &InternalRefContext, code: &[u8]) -> DbResult<U256> {
     let code_length = code.len();
 
-    let transaction_gas =
-        gas_required_for(/* is_create */ true, code, context.spec) + context.spec.tx_gas as u64;
+    let transaction_gas = gas_required_for(/* is_create */ true, code, &[], context.spec)
+        + context.spec.tx_gas as u64;
 
     let create_gas = U256::from(context.spec.create_gas);
 
@@ -61,8 +61,8 @@ pub fn call_gas(
 ) -> DbResult<U256> {
     let data_length = data.len();
 
-    let transaction_gas =
-        gas_required_for(/* is_create */ false, data, context.spec) + context.spec.tx_gas as u64;
+    let transaction_gas = gas_required_for(/* is_create */ false, data, &[], context.spec)
+        + context.spec.tx_gas as u64;
 
     let new_account = !context
         .state
@@ -308,9 +308,11 @@ pub fn call_to_evmcore(
 
     if call_type == CallType::Call {
         let nonce = context.state.nonce(&mapped_sender)?;
-        context
-            .state
-            .inc_nonce(&mapped_sender, &context.spec.account_start_nonce)?;
+        context.state.inc_nonce(
+            &mapped_sender,
+            &context.spec.account_start_nonce,
+            context.spec.eip2681,
+        )?;
         CallEvent::log(
             &(mapped_sender.address.0, address.address.0),
             &(value, nonce, data),
@@ -399,9 +401,11 @@ pub fn create_to_evmcore(
     };
 
     let nonce = context.state.nonce(&mapped_sender)?;
-    context
-        .state
-        .inc_nonce(&mapped_sender, &context.spec.account_start_nonce)?;
+    context.state.inc_nonce(
+        &mapped_sender,
+        &context.spec.account_start_nonce,
+        context.spec.eip2681,
+    )?;
     CreateEvent::log(
         &(mapped_sender.address.0, address.0),
         &(value, nonce, init),
@@ -444,9 +448,11 @@ pub fn withdraw_from_evmcore(
     );
 
     let nonce = context.state.nonce(&mapped_address)?;
-    context
-        .state
-        .inc_nonce(&mapped_address, &context.spec.account_start_nonce)?;
+    context.state.inc_nonce(
+        &mapped_address,
+        &context.spec.account_start_nonce,
+        context.spec.eip2681,
+    )?;
     WithdrawEvent::log(
         &(mapped_address.address.0, sender),
         &(value, nonce),
@@ -1,3 +1,47 @@
+//! EVM-space transaction execution of Conflux.
+//!
+//! The most used types of the dependencies are re-exported at the crate
+//! root, e.g. to execute a transfer:
+//!
+//! ```
+//! use cfx_evm::{
+//!     new_machine_with_builtin, Action, AddressSpaceUtil, CleanupMode, CommonParams,
+//!     Eip155Transaction, Env, Generator, InMemoryDb, Random, State, StateDb, StateOpsTrait,
+//!     TXExecutor, TransactOptions, Transaction, VmFactory, U256,
+//! };
+//!
+//! let machine = new_machine_with_builtin(CommonParams::default(), VmFactory::new(1024 * 32));
+//! let env = Env::default();
+//! let spec = machine.spec(env.number);
+//! let mut state = State::new(StateDb::new(InMemoryDb::new())).unwrap();
+//!
+//! let sender = Random.generate().unwrap();
+//! state
+//!     .add_balance(
+//!         &sender.address().with_evm_space(),
+//!         &U256::from(1_000_000_000),
+//!         CleanupMode::NoEmpty,
+//!         U256::zero(),
+//!     )
+//!     .unwrap();
+//!
+//! let tx = Transaction::from(Eip155Transaction {
+//!     nonce: U256::zero(),
+//!     gas_price: U256::one(),
+//!     gas: U256::from(21000),
+//!     value: U256::from(1000),
+//!     action: Action::Call(Default::default()),
+//!     chain_id: Some(1),
+//!     data: vec![],
+//! })
+//! .sign(&sender.secret());
+//!
+//! let outcome = TXExecutor::new(&mut state, &env, &machine, &spec)
+//!     .transact(&tx, TransactOptions::exec_with_no_tracing())
+//!     .unwrap();
+//! assert!(outcome.successfully_executed().is_some());
+//! ```
+
 extern crate cfx_bytes as bytes;
 extern crate keccak_hash as hash;
 extern crate substrate_bn as bn;
@@ -20,15 +64,27 @@ mod state;
 pub mod vm;
 mod vm_factory;
 
+#[cfg(test)]
+mod test_helpers;
+
+pub use builtin::Pricer;
 pub use call_create_frame::contract_address;
 pub use execution::TXExecutor;
 pub use execution::{ExecutionOutcome, TransactOptions, TransactionInfo};
-pub use machine::{new_machine_with_builtin, Machine};
+pub use machine::{new_machine_with_builtin, Machine, PrecompilePricing};
 pub use spec::CommonParams;
-pub use state::State;
+pub use state::{
+    AccessObserver, AccessedAccount, AccountKind, CheckpointEvent, CheckpointObserver,
+    CheckpointRecorder, State,
+};
 pub use vm::{Env, Spec};
 pub use vm_factory::VmFactory;
 
-pub use cfx_state::StateTrait;
+pub use cfx_state::{
+    state_trait::StateOpsTrait, AccountDiff, AccountSnapshot, CleanupMode, StateDiff, StateTrait,
+};
 pub use cfx_statedb::{StateDb, StateDbExt, StateDbTrait};
-pub use cfx_storage::StorageTrait;
+pub use cfx_storage::{InMemoryDb, InMemoryDbSnapshot, StorageTrait};
+pub use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256};
+pub use cfxkey::{Generator, KeyPair, Random};
+pub use primitives::{Account, Action, Eip155Transaction, SignedTransaction, Transaction};
@@ -4,24 +4,32 @@
 
 use super::builtin::Builtin;
 use crate::{
-    builtin::{builtin_factory, AltBn128PairingPricer, Blake2FPricer, Linear, ModexpPricer},
+    builtin::{
+        builtin_factory, AltBn128PairingPricer, Blake2FPricer, Eip2565ModexpPricer, Linear,
+        ModexpPricer, Pricer,
+    },
     internal_contract::InternalContractMap,
     spec::CommonParams,
     vm::Spec,
     vm_factory::VmFactory,
 };
-use cfx_types::{Address, AddressWithSpace, Space, H256};
+use cfx_types::{Address, AddressWithSpace, Space, H256, U256};
 use primitives::BlockNumber;
 use std::{collections::BTreeMap, sync::Arc};
 
 pub type SpecCreationRules = dyn Fn(&mut Spec, BlockNumber) + Sync + Send;
 
+/// Pricers replacing the builtin pricing of the precompiles at the given
+/// addresses.
+pub type PrecompilePricing = BTreeMap<Address, Box<dyn Pricer>>;
+
 pub struct Machine {
     params: CommonParams,
     vm: VmFactory,
     builtins: Arc<BTreeMap<Address, Builtin>>,
     internal_contracts: Arc<InternalContractMap>,
     spec_rules: Option<Box<SpecCreationRules>>,
+    precompile_pricing: PrecompilePricing,
 }
 
 impl Machine {
@@ -45,6 +53,26 @@ impl Machine {
         self.spec_rules = Some(rules);
     }
 
+    /// Reprice the precompiles, e.g. for a fork.
+    pub fn set_precompile_pricing(&mut self, pricing: PrecompilePricing) {
+        self.precompile_pricing = pricing;
+    }
+
+    /// Gas cost of running `builtin` at `address` with `input` in the block
+    /// `block_number`, the repriced cost if the precompile is repriced.
+    pub fn builtin_gas(
+        &self,
+        address: &Address,
+        builtin: &Builtin,
+        input: &[u8],
+        block_number: u64,
+    ) -> U256 {
+        match self.precompile_pricing.get(address) {
+            Some(pricer) => pricer.cost(input),
+            None => builtin.cost(input, block_number),
+        }
+    }
+
     /// Get the general parameters of the chain.
     pub fn params(&self) -> &CommonParams {
         &self.params
@@ -86,6 +114,7 @@ pub fn new_machine(params: CommonParams, vm: VmFactory) -> Machine {
         builtins: Arc::new(BTreeMap::new()),
         internal_contracts: Arc::new(InternalContractMap::default()),
         spec_rules: None,
+        precompile_pricing: BTreeMap::new(),
     }
 }
 
@@ -124,6 +153,10 @@ fn new_builtin_map(params: &CommonParams, space: Space) -> BTreeMap<Address, Bui
             Box::new(ModexpPricer::new(20)),
             builtin_factory("modexp"),
             params.transition_numbers.cip62,
+        )
+        .with_pricer_from(
+            params.transition_numbers.eip2565,
+            Box::new(Eip2565ModexpPricer),
         ),
     );
     btree.insert(
@@ -171,5 +204,6 @@ pub fn new_machine_with_builtin(params: CommonParams, vm: VmFactory) -> Machine
         builtins: Arc::new(builtins),
         internal_contracts: Arc::new(internal_contracts),
         spec_rules: None,
+        precompile_pricing: BTreeMap::new(),
     }
 }
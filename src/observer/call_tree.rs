@@ -0,0 +1,149 @@
+use super::trace::{Action, ExecTrace, Outcome};
+
+/// Renders the calls and creates in `traces` as a tree, one line per frame
+/// indented by its depth. Each line shows the call type, the addresses, the
+/// value and gas of the frame, followed by the gas left and the outcome once
+/// the frame has returned. Internal transfers are omitted.
+pub fn format_call_tree(traces: &[ExecTrace]) -> String {
+    let mut lines = Vec::new();
+    // Lines of the frames not returned yet.
+    let mut open_frames = Vec::new();
+
+    for trace in traces {
+        let indent = "  ".repeat(open_frames.len());
+        match &trace.action {
+            Action::Call(call) => {
+                open_frames.push(lines.len());
+                lines.push(format!(
+                    "{}{:?} {:?} -> {:?} value: {} gas: {}",
+                    indent, call.call_type, call.from, call.to, call.value, call.gas
+                ));
+            }
+            Action::Create(create) => {
+                open_frames.push(lines.len());
+                lines.push(format!(
+                    "{}{:?} {:?} value: {} gas: {}",
+                    indent, create.create_type, create.from, create.value, create.gas
+                ));
+            }
+            Action::CallResult(result) => {
+                if let Some(line) = open_frames.pop() {
+                    lines[line] += &format!(
+                        " gas_left: {} [{}]",
+                        result.gas_left,
+                        outcome_marker(&result.outcome)
+                    );
+                }
+            }
+            Action::CreateResult(result) => {
+                if let Some(line) = open_frames.pop() {
+                    lines[line] += &format!(
+                        " created: {:?} gas_left: {} [{}]",
+                        result.addr,
+                        result.gas_left,
+                        outcome_marker(&result.outcome)
+                    );
+                }
+            }
+            Action::InternalTransferAction(_) => {}
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn outcome_marker(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "success",
+        Outcome::Reverted => "reverted",
+        Outcome::Fail => "failed",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_call_tree;
+    use crate::{
+        observer::trace::{Action, Call, CallResult, Create, CreateResult, ExecTrace, Outcome},
+        vm::{CallType, CreateType},
+    };
+    use cfx_types::{Address, Space, U256};
+
+    fn trace(action: Action) -> ExecTrace {
+        ExecTrace {
+            action,
+            valid: true,
+        }
+    }
+
+    fn call(from: Address, to: Address, call_type: CallType, gas: u64) -> Action {
+        Action::Call(Call {
+            space: Space::Ethereum,
+            from,
+            to,
+            value: U256::zero(),
+            gas: gas.into(),
+            input: vec![],
+            call_type,
+        })
+    }
+
+    fn call_result(outcome: Outcome, gas_left: u64) -> Action {
+        Action::CallResult(CallResult {
+            outcome,
+            gas_left: gas_left.into(),
+            return_data: vec![],
+        })
+    }
+
+    #[test]
+    fn test_format_call_tree() {
+        let a = Address::from_low_u64_be(0xa);
+        let b = Address::from_low_u64_be(0xb);
+        let c = Address::from_low_u64_be(0xc);
+        let created = Address::from_low_u64_be(0xd);
+
+        let traces = vec![
+            trace(call(a, b, CallType::Call, 1000)),
+            trace(call(b, c, CallType::DelegateCall, 500)),
+            trace(call_result(Outcome::Reverted, 100)),
+            trace(Action::Create(Create {
+                space: Space::Ethereum,
+                from: b,
+                value: U256::zero(),
+                gas: 300.into(),
+                init: vec![],
+                create_type: CreateType::CREATE2,
+            })),
+            trace(Action::CreateResult(CreateResult {
+                outcome: Outcome::Success,
+                addr: created,
+                gas_left: 50.into(),
+                return_data: vec![],
+            })),
+            trace(call_result(Outcome::Success, 10)),
+        ];
+
+        let lines: Vec<String> = format_call_tree(&traces)
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!(
+                    "Call {:?} -> {:?} value: 0 gas: 1000 gas_left: 10 [success]",
+                    a, b
+                ),
+                format!(
+                    "  DelegateCall {:?} -> {:?} value: 0 gas: 500 gas_left: 100 [reverted]",
+                    b, c
+                ),
+                format!(
+                    "  CREATE2 {:?} value: 0 gas: 300 created: {:?} gas_left: 50 [success]",
+                    b, created
+                ),
+            ]
+        );
+    }
+}
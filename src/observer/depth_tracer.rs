@@ -0,0 +1,87 @@
+use super::{OpcodeObserve, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::U256;
+
+/// Tracks the call depth during execution and records the maximum depth
+/// reached. The top-level call or create of a transaction has depth 1.
+#[derive(Default)]
+pub struct DepthTracer {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl DepthTracer {
+    /// The maximum call depth reached so far.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    fn enter(&mut self) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+
+    fn exit(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+impl StateTracer for DepthTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for DepthTracer {}
+
+impl VmObserve for DepthTracer {
+    fn record_call(&mut self, _: &ActionParams) {
+        self.enter();
+    }
+
+    fn record_call_result(&mut self, _: &VmResult<FrameReturn>) {
+        self.exit();
+    }
+
+    fn record_create(&mut self, _: &ActionParams) {
+        self.enter();
+    }
+
+    fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {
+        self.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DepthTracer;
+    use crate::{
+        observer::VmObserve,
+        vm::{ActionParams, Error},
+    };
+
+    #[test]
+    fn test_max_depth_of_nested_calls() {
+        let mut tracer = DepthTracer::default();
+        let params = ActionParams::default();
+
+        tracer.record_call(&params);
+        tracer.record_call(&params);
+        tracer.record_create(&params);
+        tracer.record_create_result(&Err(Error::OutOfGas));
+        tracer.record_call_result(&Err(Error::OutOfGas));
+        tracer.record_call(&params);
+        tracer.record_call_result(&Err(Error::OutOfGas));
+        tracer.record_call_result(&Err(Error::OutOfGas));
+
+        assert_eq!(tracer.max_depth(), 3);
+    }
+}
@@ -31,6 +31,12 @@ impl ErrorUnwind {
         errors
     }
 
+    /// The frames propagating the uncaught error, from the outermost frame to
+    /// the deepest one where the error happens, with their errors.
+    pub fn outermost_first(&self) -> Vec<(Address, String)> {
+        self.errors.iter().rev().cloned().collect()
+    }
+
     // If contract A calls contract B, contract B returns with an exception (vm
     // error or reverted), but contract A makes another sub-call, we think
     // contract A catches this error and clear the error list.
@@ -94,3 +100,79 @@ impl ErrorUnwind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorUnwind;
+    use crate::{
+        observer::trace::{Action, Call, CallResult, ExecTrace, Outcome},
+        vm::CallType,
+    };
+    use cfx_types::{Address, Space};
+
+    fn trace(action: Action) -> ExecTrace {
+        ExecTrace {
+            action,
+            valid: true,
+        }
+    }
+
+    fn call(from: u64, to: u64) -> ExecTrace {
+        trace(Action::Call(Call {
+            space: Space::Ethereum,
+            from: Address::from_low_u64_be(from),
+            to: Address::from_low_u64_be(to),
+            value: Default::default(),
+            gas: Default::default(),
+            input: vec![],
+            call_type: CallType::Call,
+        }))
+    }
+
+    fn call_result(outcome: Outcome, return_data: &[u8]) -> ExecTrace {
+        trace(Action::CallResult(CallResult {
+            outcome,
+            gas_left: Default::default(),
+            return_data: return_data.to_vec(),
+        }))
+    }
+
+    #[test]
+    fn test_propagated_error() {
+        // 1 calls 2, which calls 3. 3 runs out of gas and the outer frames
+        // revert without data.
+        let traces = vec![
+            call(0, 1),
+            call(1, 2),
+            call(2, 3),
+            call_result(Outcome::Fail, b"OutOfGas"),
+            call_result(Outcome::Reverted, &[]),
+            call_result(Outcome::Reverted, &[]),
+        ];
+        let unwind = ErrorUnwind::from_traces(traces);
+        assert_eq!(
+            unwind.outermost_first(),
+            vec![
+                (Address::from_low_u64_be(1), "Vm reverted. ".to_string()),
+                (Address::from_low_u64_be(2), "Vm reverted. ".to_string()),
+                (Address::from_low_u64_be(3), "OutOfGas".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_caught_error() {
+        // 2 ignores the failure of 3 and returns successfully.
+        let traces = vec![
+            call(0, 1),
+            call(1, 2),
+            call(2, 3),
+            call_result(Outcome::Fail, b"OutOfGas"),
+            call_result(Outcome::Success, &[]),
+            call_result(Outcome::Success, &[]),
+        ];
+        assert!(ErrorUnwind::from_traces(traces)
+            .outermost_first()
+            .is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use super::VmObserve;
+use super::{OpcodeObserve, VmObserve};
 use crate::{
     call_create_frame::FrameReturn,
     vm::{ActionParams, Result as VmResult},
@@ -7,12 +7,26 @@ use cfx_parameters::{
     block::CROSS_SPACE_GAS_RATIO, internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS,
 };
 use cfx_state::tracer::{AddressPocket, StateTracer};
-use cfx_types::U256;
+use cfx_types::{Address, U256};
 
 const EVM_RATIO: (u64, u64) = (64, 63);
 const CROSS_SPACE_RATIO: (u64, u64) = (CROSS_SPACE_GAS_RATIO, 1);
 
+/// The gas used by a frame, in the order the frames are entered.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrameGas {
+    /// The recipient of the call, or the created contract.
+    pub address: Address,
+    /// The depth of the frame, the top-level frame has depth 0.
+    pub depth: usize,
+    /// The gas used by the frame, including its sub-frames.
+    pub gas_used: U256,
+    /// The gas used by the frame itself, excluding its sub-frames.
+    pub self_gas_used: U256,
+}
+
 struct ExecutiveLevel {
+    index: usize,
     init_gas: U256,
     gas_cost_in_subcall: U256,
     gas_limit_for_subcall: U256,
@@ -44,6 +58,7 @@ impl ExecutiveLevel {
 pub struct GasMan {
     gas_limit: U256,
     gas_record: Vec<ExecutiveLevel>,
+    frame_gas: Vec<FrameGas>,
 }
 
 impl GasMan {
@@ -51,8 +66,26 @@ impl GasMan {
         self.gas_limit
     }
 
-    fn record_call_create(&mut self, gas_pass_in: &U256, cross_space_internal: bool) {
+    /// The gas used by each frame, zero for the unfinished ones. The
+    /// `gas_used` of the top-level frame is the gas used by the execution,
+    /// which is also the sum of the `self_gas_used` of all the frames.
+    pub fn frame_gas_report(&self) -> Vec<FrameGas> {
+        self.frame_gas.clone()
+    }
+
+    fn record_call_create(
+        &mut self,
+        address: Address,
+        gas_pass_in: &U256,
+        cross_space_internal: bool,
+    ) {
+        self.frame_gas.push(FrameGas {
+            address,
+            depth: self.gas_record.len(),
+            ..Default::default()
+        });
         self.gas_record.push(ExecutiveLevel {
+            index: self.frame_gas.len() - 1,
             init_gas: gas_pass_in.clone(),
             gas_cost_in_subcall: U256::zero(),
             gas_limit_for_subcall: U256::zero(),
@@ -62,6 +95,9 @@ impl GasMan {
 
     fn record_return(&mut self, gas_left: &U256) {
         let child_level = self.gas_record.pop().unwrap();
+        let frame_gas = &mut self.frame_gas[child_level.index];
+        frame_gas.gas_used = child_level.gas_cost(gas_left);
+        frame_gas.self_gas_used = child_level.gas_cost_this_level(gas_left);
         let ratio = if child_level.cross_space_internal {
             CROSS_SPACE_RATIO
         } else {
@@ -92,10 +128,12 @@ impl StateTracer for GasMan {
     fn revert_to_checkpoint(&mut self) {}
 }
 
+impl OpcodeObserve for GasMan {}
+
 impl VmObserve for GasMan {
     fn record_call(&mut self, params: &ActionParams) {
         let cross_space_internal = params.code_address == *CROSS_SPACE_CONTRACT_ADDRESS;
-        self.record_call_create(&params.gas, cross_space_internal);
+        self.record_call_create(params.address, &params.gas, cross_space_internal);
     }
 
     fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
@@ -104,7 +142,7 @@ impl VmObserve for GasMan {
     }
 
     fn record_create(&mut self, params: &ActionParams) {
-        self.record_call_create(&params.gas, false);
+        self.record_call_create(params.address, &params.gas, false);
     }
 
     fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
@@ -112,3 +150,70 @@ impl VmObserve for GasMan {
         self.record_return(&gas_left);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameGas, GasMan};
+    use crate::{
+        call_create_frame::FrameReturn,
+        observer::VmObserve,
+        vm::{ActionParams, Error, ReturnData},
+    };
+    use cfx_types::{Address, Space, U256};
+
+    fn frame_return(gas_left: u64) -> FrameReturn {
+        FrameReturn {
+            space: Space::Ethereum,
+            gas_left: U256::from(gas_left),
+            apply_state: true,
+            return_data: ReturnData::empty(),
+            create_address: None,
+            substate: None,
+        }
+    }
+
+    fn params(address: u64, gas: u64) -> ActionParams {
+        ActionParams {
+            address: Address::from_low_u64_be(address),
+            gas: U256::from(gas),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_frame_gas_report() {
+        let mut gasman = GasMan::default();
+
+        gasman.record_call(&params(1, 100_000));
+        // The first sub-call uses 30000 gas, 10000 of which by its sub-call.
+        gasman.record_call(&params(2, 50_000));
+        gasman.record_call(&params(3, 20_000));
+        gasman.record_call_result(&Ok(frame_return(10_000)));
+        gasman.record_call_result(&Ok(frame_return(20_000)));
+        // The second sub-call fails and uses all the gas passed in.
+        gasman.record_create(&params(4, 5_000));
+        gasman.record_create_result(&Err(Error::OutOfGas));
+        gasman.record_call_result(&Ok(frame_return(40_000)));
+
+        let report = gasman.frame_gas_report();
+        let frame = |address: u64, depth: usize, gas_used: u64, self_gas_used: u64| FrameGas {
+            address: Address::from_low_u64_be(address),
+            depth,
+            gas_used: U256::from(gas_used),
+            self_gas_used: U256::from(self_gas_used),
+        };
+        assert_eq!(
+            report,
+            vec![
+                frame(1, 0, 60_000, 25_000),
+                frame(2, 1, 30_000, 20_000),
+                frame(3, 2, 10_000, 10_000),
+                frame(4, 1, 5_000, 5_000),
+            ]
+        );
+        let total = report
+            .iter()
+            .fold(U256::zero(), |total, frame| total + frame.self_gas_used);
+        assert_eq!(total, report[0].gas_used);
+    }
+}
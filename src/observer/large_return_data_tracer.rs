@@ -0,0 +1,147 @@
+use super::{OpcodeObserve, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::{Address, U256};
+
+/// A frame returning more data than the threshold of a
+/// `LargeReturnDataTracer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LargeReturnData {
+    /// The account executing the frame.
+    pub address: Address,
+    /// The depth of the frame, the top-level frame has depth 0.
+    pub depth: usize,
+    pub size: usize,
+}
+
+/// Detects return-data bombs, which grief the caller copying the data. Records
+/// the largest return data of a single frame and flags the frames returning
+/// more than `threshold` bytes, in the order the frames finish.
+pub struct LargeReturnDataTracer {
+    threshold: usize,
+    frames: Vec<Address>,
+    max_return_data_size: usize,
+    flagged: Vec<LargeReturnData>,
+}
+
+impl LargeReturnDataTracer {
+    pub fn new(threshold: usize) -> Self {
+        LargeReturnDataTracer {
+            threshold,
+            frames: vec![],
+            max_return_data_size: 0,
+            flagged: vec![],
+        }
+    }
+
+    /// The size of the largest return data of a single frame so far.
+    pub fn max_return_data_size(&self) -> usize {
+        self.max_return_data_size
+    }
+
+    pub fn flagged(&self) -> &[LargeReturnData] {
+        &self.flagged
+    }
+
+    fn exit(&mut self, result: &VmResult<FrameReturn>) {
+        let address = self.frames.pop().unwrap_or_default();
+        let size = result.as_ref().map_or(0, |result| result.return_data.len());
+        self.max_return_data_size = self.max_return_data_size.max(size);
+        if size > self.threshold {
+            self.flagged.push(LargeReturnData {
+                address,
+                depth: self.frames.len(),
+                size,
+            });
+        }
+    }
+}
+
+impl StateTracer for LargeReturnDataTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for LargeReturnDataTracer {}
+
+impl VmObserve for LargeReturnDataTracer {
+    fn record_call(&mut self, params: &ActionParams) {
+        self.frames.push(params.address);
+    }
+
+    fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+
+    fn record_create(&mut self, params: &ActionParams) {
+        self.frames.push(params.address);
+    }
+
+    fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LargeReturnData, LargeReturnDataTracer};
+    use crate::{
+        call_create_frame::FrameReturn,
+        observer::VmObserve,
+        vm::{ActionParams, Error, ReturnData},
+    };
+    use cfx_types::{Address, Space, U256};
+
+    fn frame_return(size: usize) -> FrameReturn {
+        FrameReturn {
+            space: Space::Ethereum,
+            gas_left: U256::zero(),
+            apply_state: true,
+            return_data: ReturnData::new(vec![0; size], 0, size),
+            create_address: None,
+            substate: None,
+        }
+    }
+
+    #[test]
+    fn test_flag_large_return_data() {
+        let caller = Address::from_low_u64_be(1);
+        let bomb = Address::from_low_u64_be(2);
+        let mut tracer = LargeReturnDataTracer::new(1024);
+
+        tracer.record_call(&ActionParams {
+            address: caller,
+            ..Default::default()
+        });
+        // The callee returns a 1 MB buffer.
+        tracer.record_call(&ActionParams {
+            address: bomb,
+            ..Default::default()
+        });
+        tracer.record_call_result(&Ok(frame_return(1 << 20)));
+        // Return data at the threshold is not flagged.
+        tracer.record_create(&ActionParams::default());
+        tracer.record_create_result(&Ok(frame_return(1024)));
+        tracer.record_call(&ActionParams::default());
+        tracer.record_call_result(&Err(Error::OutOfGas));
+        tracer.record_call_result(&Ok(frame_return(32)));
+
+        assert_eq!(tracer.max_return_data_size(), 1 << 20);
+        assert_eq!(
+            tracer.flagged(),
+            &[LargeReturnData {
+                address: bomb,
+                depth: 1,
+                size: 1 << 20,
+            }][..]
+        );
+    }
+}
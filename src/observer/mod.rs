@@ -7,22 +7,69 @@ use crate::{
     vm::{ActionParams, Result as VmResult},
 };
 pub use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::{Address, U256};
 
+pub mod call_tree;
+pub mod depth_tracer;
 pub mod error_unwind;
 pub mod gasman;
+pub mod large_return_data_tracer;
 pub mod multi_observers;
+pub mod prestate_tracer;
+pub mod return_data_tracer;
+pub mod storage_tracer;
+pub mod struct_log_tracer;
 pub mod trace;
 pub mod trace_filter;
 pub mod tracer;
+pub mod transfer_stream;
+pub mod uninitialized_read_tracer;
 
+pub use call_tree::format_call_tree;
+pub use depth_tracer::DepthTracer;
 pub use error_unwind::ErrorUnwind;
-pub use gasman::GasMan;
+pub use gasman::{FrameGas, GasMan};
+pub use large_return_data_tracer::{LargeReturnData, LargeReturnDataTracer};
 pub use multi_observers::MultiObservers;
-pub use tracer::ExecutiveTracer;
+pub use prestate_tracer::{AccountState, PrestateTracer};
+pub use return_data_tracer::ReturnDataTracer;
+pub use storage_tracer::{StorageCollision, StorageWrite, StorageWriteTracer};
+pub use struct_log_tracer::{StructLog, StructLogTracer};
+pub use tracer::{CallTracer, ExecutiveTracer, TransferTracer};
+pub use transfer_stream::StreamingTransferTracer;
+pub use uninitialized_read_tracer::UninitializedReadTracer;
+
+/// An instruction about to be executed, once its gas cost is known.
+pub struct OpcodeStep<'a> {
+    pub pc: usize,
+    pub opcode: u8,
+    /// Mnemonic name of the instruction.
+    pub op: &'static str,
+    /// The gas left before the instruction.
+    pub gas: U256,
+    pub gas_cost: U256,
+    /// The depth of the frame, the top-level frame has depth 0.
+    pub depth: usize,
+    /// The stack from bottom to top.
+    pub stack: &'a [U256],
+    pub memory: &'a [u8],
+}
+
+/// This trait is used by the interpreter to observe each instruction.
+pub trait OpcodeObserve {
+    /// Whether `record_opcode` should be called. The interpreter checks it
+    /// before building an `OpcodeStep`, which is skipped for the observers
+    /// not recording instructions.
+    fn wants_opcodes(&self) -> bool {
+        false
+    }
+
+    fn record_opcode(&mut self, _step: &OpcodeStep) {}
+}
 
 // FIXME(cx): Can the observer do not rely on the tracer?
 /// This trait is used by executive to build traces.
-pub trait VmObserve: StateTracer {
+pub trait VmObserve: StateTracer + OpcodeObserve {
     /// Prepares call trace for given params.
     fn record_call(&mut self, params: &ActionParams);
 
@@ -34,8 +81,26 @@ pub trait VmObserve: StateTracer {
 
     /// Prepares create result trace
     fn record_create_result(&mut self, result: &VmResult<FrameReturn>);
+
+    /// Records a storage write by `SSTORE`. The storage belongs to `address`
+    /// while the code of `code_address` performs the write, they differ
+    /// under `DELEGATECALL` and `CALLCODE`.
+    fn record_storage_write(
+        &mut self,
+        _address: &Address,
+        _code_address: &Address,
+        _key: &[u8],
+        _old_value: &U256,
+        _new_value: &U256,
+    ) {
+    }
+
+    /// Records a storage read of `address` by `SLOAD`.
+    fn record_storage_read(&mut self, _address: &Address, _key: &[u8], _value: &U256) {}
 }
 
+impl OpcodeObserve for () {}
+
 /// Nonoperative observer. Does not trace anything.
 impl VmObserve for () {
     fn record_call(&mut self, _: &ActionParams) {}
@@ -47,6 +112,19 @@ impl VmObserve for () {
     fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {}
 }
 
+impl<T> OpcodeObserve for &mut T
+where
+    T: OpcodeObserve,
+{
+    fn wants_opcodes(&self) -> bool {
+        (**self).wants_opcodes()
+    }
+
+    fn record_opcode(&mut self, step: &OpcodeStep) {
+        (*self).record_opcode(step);
+    }
+}
+
 impl<T> VmObserve for &mut T
 where
     T: VmObserve,
@@ -66,6 +144,36 @@ where
     fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
         (*self).record_create_result(result);
     }
+
+    fn record_storage_write(
+        &mut self,
+        address: &Address,
+        code_address: &Address,
+        key: &[u8],
+        old_value: &U256,
+        new_value: &U256,
+    ) {
+        (*self).record_storage_write(address, code_address, key, old_value, new_value);
+    }
+
+    fn record_storage_read(&mut self, address: &Address, key: &[u8], value: &U256) {
+        (*self).record_storage_read(address, key, value);
+    }
+}
+
+impl<S, T> OpcodeObserve for (S, T)
+where
+    S: OpcodeObserve,
+    T: OpcodeObserve,
+{
+    fn wants_opcodes(&self) -> bool {
+        self.0.wants_opcodes() || self.1.wants_opcodes()
+    }
+
+    fn record_opcode(&mut self, step: &OpcodeStep) {
+        self.0.record_opcode(step);
+        self.1.record_opcode(step);
+    }
 }
 
 impl<S, T> VmObserve for (S, T)
@@ -92,6 +200,95 @@ where
         self.0.record_create_result(result);
         self.1.record_create_result(result);
     }
+
+    fn record_storage_write(
+        &mut self,
+        address: &Address,
+        code_address: &Address,
+        key: &[u8],
+        old_value: &U256,
+        new_value: &U256,
+    ) {
+        self.0
+            .record_storage_write(address, code_address, key, old_value, new_value);
+        self.1
+            .record_storage_write(address, code_address, key, old_value, new_value);
+    }
+
+    fn record_storage_read(&mut self, address: &Address, key: &[u8], value: &U256) {
+        self.0.record_storage_read(address, key, value);
+        self.1.record_storage_read(address, key, value);
+    }
+}
+
+impl<R, S, T> OpcodeObserve for (R, S, T)
+where
+    R: OpcodeObserve,
+    S: OpcodeObserve,
+    T: OpcodeObserve,
+{
+    fn wants_opcodes(&self) -> bool {
+        self.0.wants_opcodes() || self.1.wants_opcodes() || self.2.wants_opcodes()
+    }
+
+    fn record_opcode(&mut self, step: &OpcodeStep) {
+        self.0.record_opcode(step);
+        self.1.record_opcode(step);
+        self.2.record_opcode(step);
+    }
+}
+
+impl<R, S, T> VmObserve for (R, S, T)
+where
+    R: VmObserve,
+    S: VmObserve,
+    T: VmObserve,
+{
+    fn record_call(&mut self, params: &ActionParams) {
+        self.0.record_call(params);
+        self.1.record_call(params);
+        self.2.record_call(params);
+    }
+
+    fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.0.record_call_result(result);
+        self.1.record_call_result(result);
+        self.2.record_call_result(result);
+    }
+
+    fn record_create(&mut self, params: &ActionParams) {
+        self.0.record_create(params);
+        self.1.record_create(params);
+        self.2.record_create(params);
+    }
+
+    fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.0.record_create_result(result);
+        self.1.record_create_result(result);
+        self.2.record_create_result(result);
+    }
+
+    fn record_storage_write(
+        &mut self,
+        address: &Address,
+        code_address: &Address,
+        key: &[u8],
+        old_value: &U256,
+        new_value: &U256,
+    ) {
+        self.0
+            .record_storage_write(address, code_address, key, old_value, new_value);
+        self.1
+            .record_storage_write(address, code_address, key, old_value, new_value);
+        self.2
+            .record_storage_write(address, code_address, key, old_value, new_value);
+    }
+
+    fn record_storage_read(&mut self, address: &Address, key: &[u8], value: &U256) {
+        self.0.record_storage_read(address, key, value);
+        self.1.record_storage_read(address, key, value);
+        self.2.record_storage_read(address, key, value);
+    }
 }
 
 // impl<S, T> VmObserve for (&mut S, &mut T)
@@ -119,3 +316,146 @@ where
 //         self.1.record_create_result(result);
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AddressPocket, CallTracer, GasMan, OpcodeObserve, OpcodeStep, StateTracer, StructLogTracer,
+        VmObserve,
+    };
+    use crate::{
+        call_create_frame::FrameReturn,
+        vm::{ActionParams, Error, Result as VmResult},
+    };
+    use cfx_types::{Address, U256};
+
+    /// Counts the events received.
+    #[derive(Default, Debug, PartialEq)]
+    struct EventCounter {
+        transfers: usize,
+        checkpoints: usize,
+        opcodes: usize,
+        calls: usize,
+        creates: usize,
+        results: usize,
+        storage_writes: usize,
+        storage_reads: usize,
+    }
+
+    impl StateTracer for EventCounter {
+        fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {
+            self.transfers += 1;
+        }
+
+        fn checkpoint(&mut self) {
+            self.checkpoints += 1;
+        }
+
+        fn discard_checkpoint(&mut self) {
+            self.checkpoints += 1;
+        }
+
+        fn revert_to_checkpoint(&mut self) {
+            self.checkpoints += 1;
+        }
+    }
+
+    impl OpcodeObserve for EventCounter {
+        fn wants_opcodes(&self) -> bool {
+            true
+        }
+
+        fn record_opcode(&mut self, _: &OpcodeStep) {
+            self.opcodes += 1;
+        }
+    }
+
+    impl VmObserve for EventCounter {
+        fn record_call(&mut self, _: &ActionParams) {
+            self.calls += 1;
+        }
+
+        fn record_call_result(&mut self, _: &VmResult<FrameReturn>) {
+            self.results += 1;
+        }
+
+        fn record_create(&mut self, _: &ActionParams) {
+            self.creates += 1;
+        }
+
+        fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {
+            self.results += 1;
+        }
+
+        fn record_storage_write(&mut self, _: &Address, _: &Address, _: &[u8], _: &U256, _: &U256) {
+            self.storage_writes += 1;
+        }
+
+        fn record_storage_read(&mut self, _: &Address, _: &[u8], _: &U256) {
+            self.storage_reads += 1;
+        }
+    }
+
+    fn assert_vm_observe<T: VmObserve>() {}
+
+    #[test]
+    fn test_triple_observers() {
+        assert_vm_observe::<(StructLogTracer, GasMan, CallTracer)>();
+
+        let mut observers = (
+            EventCounter::default(),
+            EventCounter::default(),
+            EventCounter::default(),
+        );
+        let address = Address::from_low_u64_be(1);
+        observers.checkpoint();
+        observers.trace_internal_transfer(
+            AddressPocket::MintBurn,
+            AddressPocket::GasPayment,
+            U256::one(),
+        );
+        observers.record_call(&ActionParams::default());
+        observers.record_opcode(&OpcodeStep {
+            pc: 0,
+            opcode: 0x00,
+            op: "STOP",
+            gas: U256::zero(),
+            gas_cost: U256::zero(),
+            depth: 0,
+            stack: &[],
+            memory: &[],
+        });
+        observers.record_storage_read(&address, &[0; 32], &U256::zero());
+        observers.record_storage_write(&address, &address, &[0; 32], &U256::zero(), &U256::one());
+        observers.record_create(&ActionParams::default());
+        observers.record_create_result(&Err(Error::OutOfGas));
+        observers.record_call_result(&Err(Error::Reverted));
+        observers.discard_checkpoint();
+
+        let expected = EventCounter {
+            transfers: 1,
+            checkpoints: 2,
+            opcodes: 1,
+            calls: 1,
+            creates: 1,
+            results: 2,
+            storage_writes: 1,
+            storage_reads: 1,
+        };
+        assert_eq!(observers.0, expected);
+        assert_eq!(observers.1, expected);
+        assert_eq!(observers.2, expected);
+    }
+
+    #[test]
+    fn test_wants_opcodes() {
+        assert!(!(CallTracer::default(), GasMan::default()).wants_opcodes());
+        assert!((GasMan::default(), StructLogTracer::default()).wants_opcodes());
+        assert!((
+            CallTracer::default(),
+            GasMan::default(),
+            StructLogTracer::default()
+        )
+            .wants_opcodes());
+    }
+}
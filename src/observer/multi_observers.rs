@@ -1,33 +1,66 @@
-use super::{gasman::GasMan, tracer::ExecutiveTracer, StateTracer, VmObserve};
+use super::{
+    gasman::GasMan,
+    struct_log_tracer::StructLogTracer,
+    trace::{internal_transfers, ExecTrace, InternalTransferAction},
+    tracer::{ExecutiveTracer, TransferTracer},
+    StateTracer, VmObserve,
+};
 
 pub struct MultiObservers {
     pub tracer: Option<ExecutiveTracer>,
     pub gas_man: Option<GasMan>,
-    _noop: (),
+    pub struct_logger: Option<StructLogTracer>,
+    /// Collects the internal transfers when `tracer` is off.
+    transfers: TransferTracer,
 }
 
 impl MultiObservers {
     pub fn as_vm_observe<'a>(&'a mut self) -> Box<dyn VmObserve + 'a> {
-        match (self.tracer.as_mut(), self.gas_man.as_mut()) {
-            (Some(tracer), Some(gas_man)) => Box::new((tracer, gas_man)),
-            (Some(tracer), None) => Box::new(tracer),
-            (None, Some(gas_man)) => Box::new(gas_man),
-            (None, None) => Box::new(&mut self._noop),
+        match (
+            self.tracer.as_mut(),
+            self.gas_man.as_mut(),
+            self.struct_logger.as_mut(),
+        ) {
+            (Some(tracer), Some(gas_man), Some(logger)) => Box::new(((tracer, gas_man), logger)),
+            (Some(tracer), Some(gas_man), None) => Box::new((tracer, gas_man)),
+            (Some(tracer), None, Some(logger)) => Box::new((tracer, logger)),
+            (Some(tracer), None, None) => Box::new(tracer),
+            (None, Some(gas_man), Some(logger)) => {
+                Box::new(((&mut self.transfers, gas_man), logger))
+            }
+            (None, Some(gas_man), None) => Box::new((&mut self.transfers, gas_man)),
+            (None, None, Some(logger)) => Box::new((&mut self.transfers, logger)),
+            (None, None, None) => Box::new(&mut self.transfers),
         }
     }
 
     pub fn as_state_tracer(&mut self) -> &mut dyn StateTracer {
         match self.tracer.as_mut() {
-            None => &mut self._noop,
+            None => &mut self.transfers,
             Some(tracer) => tracer,
         }
     }
 
+    /// Drains the traces and the internal transfers which are not reverted.
+    /// Without tracing the traces are empty, but the transfers are still
+    /// collected.
+    pub fn drain_traces(&mut self) -> (Vec<ExecTrace>, Vec<InternalTransferAction>) {
+        match self.tracer.take() {
+            Some(tracer) => {
+                let trace = tracer.drain();
+                let transfers = internal_transfers(&trace);
+                (trace, transfers)
+            }
+            None => (vec![], std::mem::take(&mut self.transfers).drain()),
+        }
+    }
+
     pub fn with_tracing() -> Self {
         MultiObservers {
             tracer: Some(ExecutiveTracer::default()),
             gas_man: None,
-            _noop: (),
+            struct_logger: None,
+            transfers: TransferTracer::default(),
         }
     }
 
@@ -35,7 +68,17 @@ impl MultiObservers {
         MultiObservers {
             tracer: None,
             gas_man: None,
-            _noop: (),
+            struct_logger: None,
+            transfers: TransferTracer::default(),
+        }
+    }
+
+    pub fn with_struct_logs() -> Self {
+        MultiObservers {
+            tracer: None,
+            gas_man: None,
+            struct_logger: Some(StructLogTracer::default()),
+            transfers: TransferTracer::default(),
         }
     }
 
@@ -43,7 +86,8 @@ impl MultiObservers {
         MultiObservers {
             tracer: Some(ExecutiveTracer::default()),
             gas_man: Some(GasMan::default()),
-            _noop: (),
+            struct_logger: None,
+            transfers: TransferTracer::default(),
         }
     }
 }
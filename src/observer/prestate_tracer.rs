@@ -0,0 +1,104 @@
+use crate::{
+    bytes::Bytes,
+    hash::KECCAK_EMPTY,
+    state::{AccessObserver, AccessedAccount},
+};
+use cfx_types::{AddressWithSpace, H256, U256};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// An account before the transaction, with the storage slots accessed by the
+/// transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    /// `None` if the transaction never loads the code of the contract.
+    pub code: Option<Bytes>,
+    pub storage: BTreeMap<Vec<u8>, U256>,
+}
+
+#[derive(Default)]
+struct Prestate {
+    /// The accounts on their first access, `None` for the accounts not
+    /// existing.
+    accounts: HashMap<AddressWithSpace, Option<AccountState>>,
+    storage: HashMap<(AddressWithSpace, Vec<u8>), U256>,
+}
+
+/// Records the accounts and storage slots accessed through `State` as they
+/// were before the transaction, like the geth `prestateTracer`. Each account
+/// and slot is recorded on its first access. The tracer is a shared handle,
+/// a clone installed into `State` with `State::set_access_observer` reports
+/// to the original.
+#[derive(Clone, Default)]
+pub struct PrestateTracer {
+    prestate: Arc<Mutex<Prestate>>,
+}
+
+impl PrestateTracer {
+    /// The accounts accessed so far. The accounts not existing before the
+    /// transaction are omitted.
+    pub fn into_prestate(self) -> HashMap<AddressWithSpace, AccountState> {
+        let Prestate { accounts, storage } = std::mem::take(&mut *self.prestate.lock());
+        let mut accounts: HashMap<_, _> = accounts
+            .into_iter()
+            .filter_map(|(address, account)| Some((address, account?)))
+            .collect();
+        for ((address, key), value) in storage {
+            if let Some(account) = accounts.get_mut(&address) {
+                account.storage.insert(key, value);
+            }
+        }
+        accounts
+    }
+}
+
+impl AccessObserver for PrestateTracer {
+    fn on_account_access(&mut self, address: &AddressWithSpace, account: Option<&AccessedAccount>) {
+        let mut prestate = self.prestate.lock();
+        match prestate.accounts.get_mut(address) {
+            None => {
+                let account = account.map(|acc| AccountState {
+                    balance: acc.balance,
+                    nonce: acc.nonce,
+                    code_hash: acc.code_hash,
+                    code: match acc.code_hash == KECCAK_EMPTY {
+                        true => Some(Bytes::new()),
+                        false => acc.code.as_ref().map(|code| (**code).clone()),
+                    },
+                    storage: BTreeMap::new(),
+                });
+                prestate.accounts.insert(*address, account);
+            }
+            // The code of an existing account never changes, so the code
+            // loaded by a later access with the same hash is the code before
+            // the transaction.
+            Some(Some(recorded)) if recorded.code.is_none() => {
+                if let Some(AccessedAccount {
+                    code_hash,
+                    code: Some(code),
+                    ..
+                }) = account
+                {
+                    if *code_hash == recorded.code_hash {
+                        recorded.code = Some((**code).clone());
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn on_storage_access(&mut self, address: &AddressWithSpace, key: &[u8], value: &U256) {
+        self.prestate
+            .lock()
+            .storage
+            .entry((*address, key.to_vec()))
+            .or_insert(*value);
+    }
+}
@@ -0,0 +1,101 @@
+use super::{OpcodeObserve, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::U256;
+
+/// Records the return data of every frame as `(depth, return_data)`, in the
+/// order the frames finish. The top-level frame has depth 0, a failed frame
+/// returns empty data.
+#[derive(Default)]
+pub struct ReturnDataTracer {
+    depth: usize,
+    return_data: Vec<(usize, Vec<u8>)>,
+}
+
+impl ReturnDataTracer {
+    pub fn return_data(&self) -> &[(usize, Vec<u8>)] {
+        &self.return_data
+    }
+
+    pub fn drain(self) -> Vec<(usize, Vec<u8>)> {
+        self.return_data
+    }
+
+    fn exit(&mut self, result: &VmResult<FrameReturn>) {
+        self.depth = self.depth.saturating_sub(1);
+        let data = result
+            .as_ref()
+            .map(|result| result.return_data.to_vec())
+            .unwrap_or_default();
+        self.return_data.push((self.depth, data));
+    }
+}
+
+impl StateTracer for ReturnDataTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for ReturnDataTracer {}
+
+impl VmObserve for ReturnDataTracer {
+    fn record_call(&mut self, _: &ActionParams) {
+        self.depth += 1;
+    }
+
+    fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+
+    fn record_create(&mut self, _: &ActionParams) {
+        self.depth += 1;
+    }
+
+    fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReturnDataTracer;
+    use crate::{
+        call_create_frame::FrameReturn,
+        observer::VmObserve,
+        vm::{ActionParams, ReturnData},
+    };
+    use cfx_types::{Space, U256};
+
+    fn frame_return(data: Vec<u8>) -> FrameReturn {
+        let len = data.len();
+        FrameReturn {
+            space: Space::Ethereum,
+            gas_left: U256::zero(),
+            apply_state: true,
+            return_data: ReturnData::new(data, 0, len),
+            create_address: None,
+            substate: None,
+        }
+    }
+
+    #[test]
+    fn test_return_data_of_nested_call() {
+        let mut tracer = ReturnDataTracer::default();
+        let params = ActionParams::default();
+
+        tracer.record_call(&params);
+        tracer.record_call(&params);
+        tracer.record_call_result(&Ok(frame_return(vec![2, 2])));
+        tracer.record_call_result(&Ok(frame_return(vec![1])));
+
+        assert_eq!(tracer.return_data(), &[(1, vec![2, 2]), (0, vec![1])][..]);
+    }
+}
@@ -0,0 +1,213 @@
+use super::{OpcodeObserve, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::{Address, H256, U256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A storage write observed through the `SSTORE` hook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageWrite {
+    /// The account owning the storage.
+    pub address: Address,
+    /// The contract whose code performs the write.
+    pub code_address: Address,
+    pub key: H256,
+    /// The value before the write.
+    pub old_value: U256,
+    pub new_value: U256,
+}
+
+/// A slot of a proxy written both by the proxy's own code and by the code
+/// of a delegatecalled implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageCollision {
+    /// The proxy owning the storage.
+    pub address: Address,
+    pub key: H256,
+    /// The implementations writing the slot, in ascending order.
+    pub implementations: Vec<Address>,
+}
+
+/// Records all the storage writes of a transaction. Writes in reverted
+/// frames are recorded as well.
+#[derive(Default)]
+pub struct StorageWriteTracer {
+    writes: Vec<StorageWrite>,
+}
+
+impl StorageWriteTracer {
+    pub fn writes(&self) -> &[StorageWrite] {
+        &self.writes
+    }
+
+    pub fn drain(self) -> Vec<StorageWrite> {
+        self.writes
+    }
+
+    /// Reports the writes leaving the slot unchanged, which pay for an
+    /// `SSTORE` without any effect.
+    pub fn redundant_writes(&self) -> Vec<&StorageWrite> {
+        self.writes
+            .iter()
+            .filter(|write| write.old_value == write.new_value)
+            .collect()
+    }
+
+    /// Reports the slots written both by the owner's code and by a
+    /// delegatecalled implementation. The old value of the first write to
+    /// a slot is its value in the prestate.
+    pub fn delegatecall_collisions(&self) -> Vec<StorageCollision> {
+        let mut own_writes = BTreeSet::new();
+        let mut delegated_writes: BTreeMap<_, BTreeSet<Address>> = BTreeMap::new();
+
+        for write in &self.writes {
+            let slot = (write.address, write.key);
+            if write.address == write.code_address {
+                own_writes.insert(slot);
+            } else {
+                delegated_writes
+                    .entry(slot)
+                    .or_default()
+                    .insert(write.code_address);
+            }
+        }
+
+        delegated_writes
+            .into_iter()
+            .filter(|(slot, _)| own_writes.contains(slot))
+            .map(|((address, key), implementations)| StorageCollision {
+                address,
+                key,
+                implementations: implementations.into_iter().collect(),
+            })
+            .collect()
+    }
+}
+
+impl StateTracer for StorageWriteTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for StorageWriteTracer {}
+
+impl VmObserve for StorageWriteTracer {
+    fn record_call(&mut self, _: &ActionParams) {}
+
+    fn record_call_result(&mut self, _: &VmResult<FrameReturn>) {}
+
+    fn record_create(&mut self, _: &ActionParams) {}
+
+    fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {}
+
+    fn record_storage_write(
+        &mut self,
+        address: &Address,
+        code_address: &Address,
+        key: &[u8],
+        old_value: &U256,
+        new_value: &U256,
+    ) {
+        self.writes.push(StorageWrite {
+            address: *address,
+            code_address: *code_address,
+            key: H256::from_slice(key),
+            old_value: *old_value,
+            new_value: *new_value,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StorageCollision, StorageWrite, StorageWriteTracer};
+    use crate::observer::VmObserve;
+    use cfx_types::{Address, H256, U256};
+
+    #[test]
+    fn test_delegatecall_collision() {
+        let proxy = Address::from_low_u64_be(1);
+        let implementation = Address::from_low_u64_be(2);
+        let other = Address::from_low_u64_be(3);
+        let slot = H256::from_low_u64_be(0);
+
+        let mut tracer = StorageWriteTracer::default();
+        // The proxy stores the implementation address in slot 0.
+        tracer.record_storage_write(
+            &proxy,
+            &proxy,
+            slot.as_bytes(),
+            &U256::zero(),
+            &U256::from(2),
+        );
+        // The delegatecalled implementation overwrites slot 0 of the proxy.
+        tracer.record_storage_write(
+            &proxy,
+            &implementation,
+            slot.as_bytes(),
+            &U256::from(2),
+            &U256::from(100),
+        );
+        // Writes to other slots or to its own storage are not collisions.
+        tracer.record_storage_write(
+            &proxy,
+            &implementation,
+            H256::from_low_u64_be(1).as_bytes(),
+            &U256::zero(),
+            &U256::one(),
+        );
+        tracer.record_storage_write(&other, &other, slot.as_bytes(), &U256::zero(), &U256::one());
+
+        assert_eq!(tracer.writes().len(), 4);
+        assert_eq!(
+            tracer.delegatecall_collisions(),
+            vec![StorageCollision {
+                address: proxy,
+                key: slot,
+                implementations: vec![implementation],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_redundant_writes() {
+        let contract = Address::from_low_u64_be(1);
+        let slot = H256::from_low_u64_be(0);
+
+        let mut tracer = StorageWriteTracer::default();
+        tracer.record_storage_write(
+            &contract,
+            &contract,
+            slot.as_bytes(),
+            &U256::zero(),
+            &U256::from(5),
+        );
+        // Writing the value already present.
+        tracer.record_storage_write(
+            &contract,
+            &contract,
+            slot.as_bytes(),
+            &U256::from(5),
+            &U256::from(5),
+        );
+
+        assert_eq!(
+            tracer.redundant_writes(),
+            vec![&StorageWrite {
+                address: contract,
+                code_address: contract,
+                key: slot,
+                old_value: U256::from(5),
+                new_value: U256::from(5),
+            }]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use super::{OpcodeObserve, OpcodeStep, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::U256;
+use rustc_hex::ToHex;
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+
+/// An executed instruction in the shape of a struct log of the geth
+/// `debug_traceTransaction`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: usize,
+    pub op: &'static str,
+    /// The gas left before the instruction.
+    pub gas: U256,
+    pub gas_cost: U256,
+    /// As in geth, the top-level frame has depth 1.
+    pub depth: usize,
+    /// The stack before the instruction, from bottom to top.
+    pub stack: Vec<U256>,
+    /// The memory before the instruction, serialized in 32-byte words.
+    #[serde(serialize_with = "serialize_memory")]
+    pub memory: Vec<u8>,
+}
+
+/// Records every instruction executed by the transaction, including those in
+/// reverted frames.
+#[derive(Default)]
+pub struct StructLogTracer {
+    logs: Vec<StructLog>,
+}
+
+impl StructLogTracer {
+    pub fn logs(&self) -> &[StructLog] {
+        &self.logs
+    }
+
+    pub fn drain(self) -> Vec<StructLog> {
+        self.logs
+    }
+}
+
+impl StateTracer for StructLogTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for StructLogTracer {
+    fn wants_opcodes(&self) -> bool {
+        true
+    }
+
+    fn record_opcode(&mut self, step: &OpcodeStep) {
+        self.logs.push(StructLog {
+            pc: step.pc,
+            op: step.op,
+            gas: step.gas,
+            gas_cost: step.gas_cost,
+            depth: step.depth + 1,
+            stack: step.stack.to_vec(),
+            memory: step.memory.to_vec(),
+        });
+    }
+}
+
+impl VmObserve for StructLogTracer {
+    fn record_call(&mut self, _: &ActionParams) {}
+
+    fn record_call_result(&mut self, _: &VmResult<FrameReturn>) {}
+
+    fn record_create(&mut self, _: &ActionParams) {}
+
+    fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {}
+}
+
+fn serialize_memory<S: Serializer>(memory: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut words = serializer.serialize_seq(Some((memory.len() + 31) / 32))?;
+    for word in memory.chunks(32) {
+        words.serialize_element(&word.to_hex::<String>())?;
+    }
+    words.end()
+}
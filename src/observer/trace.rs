@@ -385,6 +385,18 @@ impl ExecTrace {
     }
 }
 
+/// The internal transfers in `trace`, excluding those in reverted frames.
+pub fn internal_transfers(trace: &[ExecTrace]) -> Vec<InternalTransferAction> {
+    trace
+        .iter()
+        .filter(|t| t.valid)
+        .filter_map(|t| match &t.action {
+            Action::InternalTransferAction(transfer) => Some(transfer.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
 impl Encodable for ExecTrace {
     fn rlp_append(&self, s: &mut RlpStream) {
         s.begin_list(2);
@@ -445,8 +457,8 @@ impl TransactionExecTraces {
     /// Return pairs of (action, result, subtrace_len).
     /// Return `Err` if actions and results do not match.
     ///
-    /// `from_address`, `to_address`, `action_types`, and `space` in `filter`
-    /// are applied.
+    /// `from_address`, `to_address`, `action_types`, `call_types`, and `space`
+    /// in `filter` are applied.
     pub fn filter_trace_pairs(
         self,
         filter: &TraceFilter,
@@ -467,6 +479,7 @@ impl TransactionExecTraces {
                         && filter.from_address.matches(&call.from)
                         && filter.to_address.matches(&call.to)
                         && filter.action_types.matches(&ActionType::Call)
+                        && filter.call_types.matches(&call.call_type)
                     {
                         stack_index.push(Some(trace_pairs.len()));
                         trace_pairs.push((trace, None, 0));
@@ -486,6 +499,7 @@ impl TransactionExecTraces {
                         && filter.from_address.matches(&create.from)
                         // TODO(lpl): openethereum uses `to_address` to filter the contract address.
                         && filter.action_types.matches(&ActionType::Create)
+                        && filter.call_types.matches_all()
                     {
                         stack_index.push(Some(trace_pairs.len()));
                         trace_pairs.push((trace, None, 0));
@@ -523,8 +537,8 @@ impl TransactionExecTraces {
 
     /// Return filtered Native actions with their orders kept.
     ///
-    /// `from_address`, `to_address`, `action_types`, and `space` in `filter`
-    /// are applied.
+    /// `from_address`, `to_address`, `action_types`, `call_types`, and `space`
+    /// in `filter` are applied.
     pub fn filter_traces(self, filter: &TraceFilter) -> Result<Vec<ExecTrace>, String> {
         let mut traces = Vec::new();
         let mut stack = Vec::new();
@@ -535,6 +549,7 @@ impl TransactionExecTraces {
                         && filter.from_address.matches(&call.from)
                         && filter.to_address.matches(&call.to)
                         && filter.action_types.matches(&ActionType::Call)
+                        && filter.call_types.matches(&call.call_type)
                     {
                         stack.push(true);
                         traces.push(trace);
@@ -548,6 +563,7 @@ impl TransactionExecTraces {
                         && filter.from_address.matches(&create.from)
                         // TODO(lpl): openethereum uses `to_address` to filter the contract address.
                         && filter.action_types.matches(&ActionType::Create)
+                        && filter.call_types.matches_all()
                     {
                         stack.push(true);
                         traces.push(trace);
@@ -1,4 +1,7 @@
-use crate::observer::trace::ActionType;
+use crate::{
+    observer::trace::{ActionType, ExecTrace, TransactionExecTraces},
+    vm::CallType,
+};
 use cfx_types::{Address, Space, H256};
 use primitives::EpochNumber;
 
@@ -29,6 +32,12 @@ pub struct TraceFilter {
     /// If specified, trace must match one of these action types.
     pub action_types: ListFilter<ActionType>,
 
+    /// Search call types.
+    ///
+    /// If None, match all.
+    /// If specified, trace must be a call of one of these types.
+    pub call_types: ListFilter<CallType>,
+
     /// The offset trace number.
     pub after: Option<usize>,
 
@@ -49,6 +58,7 @@ impl TraceFilter {
             from_address: Default::default(),
             to_address: Default::default(),
             action_types: Default::default(),
+            call_types: Default::default(),
             after: None,
             count: None,
             space,
@@ -56,6 +66,15 @@ impl TraceFilter {
     }
 }
 
+/// Returns the traces of a transaction matching `from_address`, `to_address`,
+/// `action_types`, `call_types`, and `space` in `filter`, or no traces if the
+/// actions and results do not match.
+pub fn apply_filter(traces: &[ExecTrace], filter: &TraceFilter) -> Vec<ExecTrace> {
+    TransactionExecTraces(traces.to_vec())
+        .filter_traces(filter)
+        .unwrap_or(vec![])
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ListFilter<T: PartialEq> {
     list: Vec<T>,
@@ -84,3 +103,114 @@ impl<T: PartialEq> ListFilter<T> {
         self.list.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_filter, TraceFilter};
+    use crate::{
+        observer::trace::{Action, Call, CallResult, Create, CreateResult, ExecTrace, Outcome},
+        vm::{CallType, CreateType},
+    };
+    use cfx_types::{Address, Space};
+
+    fn address(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn trace(action: Action) -> ExecTrace {
+        ExecTrace {
+            action,
+            valid: true,
+        }
+    }
+
+    fn call(from: u64, to: u64, call_type: CallType) -> ExecTrace {
+        trace(Action::Call(Call {
+            space: Space::Ethereum,
+            from: address(from),
+            to: address(to),
+            value: Default::default(),
+            gas: Default::default(),
+            input: vec![],
+            call_type,
+        }))
+    }
+
+    fn call_result() -> ExecTrace {
+        trace(Action::CallResult(CallResult {
+            outcome: Outcome::Success,
+            gas_left: Default::default(),
+            return_data: vec![],
+        }))
+    }
+
+    /// 1 calls 2, which delegate-calls 3, then 1 creates 4.
+    fn traces() -> Vec<ExecTrace> {
+        vec![
+            call(1, 2, CallType::Call),
+            call(2, 3, CallType::DelegateCall),
+            call_result(),
+            call_result(),
+            trace(Action::Create(Create {
+                space: Space::Ethereum,
+                from: address(1),
+                value: Default::default(),
+                gas: Default::default(),
+                init: vec![],
+                create_type: CreateType::CREATE,
+            })),
+            trace(Action::CreateResult(CreateResult {
+                outcome: Outcome::Success,
+                addr: address(4),
+                gas_left: Default::default(),
+                return_data: vec![],
+            })),
+        ]
+    }
+
+    #[test]
+    fn test_filter_from_address() {
+        let traces = traces();
+        let mut filter = TraceFilter::space_filter(Space::Ethereum);
+        filter.from_address = vec![address(1)].into();
+        let expected = vec![
+            traces[0].clone(),
+            traces[3].clone(),
+            traces[4].clone(),
+            traces[5].clone(),
+        ];
+        assert_eq!(apply_filter(&traces, &filter), expected);
+
+        // Matching any of the listed addresses.
+        filter.from_address = vec![address(1), address(2)].into();
+        assert_eq!(apply_filter(&traces, &filter), traces);
+    }
+
+    #[test]
+    fn test_filter_to_address() {
+        let traces = traces();
+        let mut filter = TraceFilter::space_filter(Space::Ethereum);
+        filter.to_address = vec![address(3)].into();
+        // Creates are not filtered by `to_address`.
+        let expected = vec![
+            traces[1].clone(),
+            traces[2].clone(),
+            traces[4].clone(),
+            traces[5].clone(),
+        ];
+        assert_eq!(apply_filter(&traces, &filter), expected);
+    }
+
+    #[test]
+    fn test_filter_combined() {
+        let traces = traces();
+        let mut filter = TraceFilter::space_filter(Space::Ethereum);
+        filter.from_address = vec![address(1), address(2)].into();
+        filter.to_address = vec![address(2), address(3)].into();
+        filter.call_types = vec![CallType::DelegateCall].into();
+        assert_eq!(
+            apply_filter(&traces, &filter),
+            vec![traces[1].clone(), traces[2].clone()]
+        );
+    }
+}
@@ -1,13 +1,16 @@
-use super::VmObserve;
+use super::{OpcodeObserve, VmObserve};
 use crate::{
+    bytes::Bytes,
     call_create_frame::FrameReturn,
     observer::trace::{
         Action, Call, CallResult, Create, CreateResult, ExecTrace, InternalTransferAction,
     },
-    vm::{ActionParams, Result as VmResult},
+    vm::{ActionParams, CallType, CreateType, Result as VmResult},
 };
 pub use cfx_state::tracer::{AddressPocket, StateTracer};
-use cfx_types::U256;
+use cfx_types::{Address, U256};
+use rustc_hex::ToHex;
+use serde::{Serialize, Serializer};
 
 /// Simple executive tracer. Traces all calls and creates.
 #[derive(Default)]
@@ -37,6 +40,8 @@ impl StateTracer for ExecutiveTracer {
     }
 }
 
+impl OpcodeObserve for ExecutiveTracer {}
+
 impl VmObserve for ExecutiveTracer {
     fn record_call(&mut self, params: &ActionParams) {
         let action = Action::Call(Call::from(params.clone()));
@@ -108,12 +113,212 @@ impl ExecutiveTracer {
     }
 }
 
+/// Collects the internal transfers which are not reverted. `MultiObservers`
+/// installs it when `ExecutiveTracer` is off, so the transfers are reported
+/// without tracing.
+#[derive(Default)]
+pub struct TransferTracer {
+    transfers: CheckpointLog<InternalTransferAction>,
+}
+
+impl TransferTracer {
+    pub fn drain(self) -> Vec<InternalTransferAction> {
+        self.transfers.drain()
+    }
+
+    fn record_result(&mut self, result: &VmResult<FrameReturn>) {
+        let success = matches!(
+            result,
+            Ok(FrameReturn {
+                apply_state: true,
+                ..
+            })
+        );
+        if success {
+            self.transfers.discard_checkpoint();
+        } else {
+            self.transfers.revert_checkpoint();
+        }
+    }
+}
+
+impl StateTracer for TransferTracer {
+    fn trace_internal_transfer(&mut self, from: AddressPocket, to: AddressPocket, value: U256) {
+        self.transfers
+            .push(InternalTransferAction { from, to, value });
+    }
+
+    fn checkpoint(&mut self) {
+        self.transfers.checkpoint();
+    }
+
+    fn discard_checkpoint(&mut self) {
+        self.transfers.discard_checkpoint();
+    }
+
+    fn revert_to_checkpoint(&mut self) {
+        self.transfers.revert_checkpoint();
+    }
+}
+
+impl OpcodeObserve for TransferTracer {}
+
+impl VmObserve for TransferTracer {
+    fn record_call(&mut self, _: &ActionParams) {
+        self.transfers.checkpoint();
+    }
+
+    fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.record_result(result);
+    }
+
+    fn record_create(&mut self, _: &ActionParams) {
+        self.transfers.checkpoint();
+    }
+
+    fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.record_result(result);
+    }
+}
+
+/// A frame in the shape of the geth `callTracer` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CallFrame {
+    #[serde(rename = "type")]
+    call_type: &'static str,
+    from: Address,
+    to: Address,
+    value: U256,
+    gas: U256,
+    gas_used: U256,
+    #[serde(serialize_with = "serialize_hex")]
+    input: Bytes,
+    #[serde(serialize_with = "serialize_hex")]
+    output: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    calls: Vec<CallFrame>,
+}
+
+/// Builds the tree of calls and creates in the format of the geth
+/// `callTracer`. The frames reverted by a caller are kept.
 #[derive(Default)]
+pub struct CallTracer {
+    /// The frames not returned yet, the top-level frame first.
+    open_frames: Vec<CallFrame>,
+    root: Option<CallFrame>,
+}
+
+impl CallTracer {
+    /// The top-level frame as JSON, or `null` if nothing is recorded.
+    pub fn into_json(self) -> serde_json::Value {
+        serde_json::to_value(self.root).expect("serializing a call frame never fails")
+    }
+
+    fn enter(&mut self, call_type: &'static str, params: &ActionParams, input: Bytes) {
+        self.open_frames.push(CallFrame {
+            call_type,
+            from: params.sender,
+            to: params.address,
+            value: params.value.value(),
+            gas: params.gas,
+            gas_used: U256::zero(),
+            input,
+            output: vec![],
+            error: None,
+            calls: vec![],
+        });
+    }
+
+    fn exit(&mut self, result: &VmResult<FrameReturn>) {
+        let mut frame = match self.open_frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        match result {
+            Ok(result) => {
+                frame.gas_used = frame.gas.saturating_sub(result.gas_left);
+                frame.output = result.return_data.to_vec();
+                if !result.apply_state {
+                    frame.error = Some("execution reverted".into());
+                }
+            }
+            Err(err) => {
+                frame.gas_used = frame.gas;
+                frame.error = Some(err.to_string());
+            }
+        }
+        match self.open_frames.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl StateTracer for CallTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for CallTracer {}
+
+impl VmObserve for CallTracer {
+    fn record_call(&mut self, params: &ActionParams) {
+        let call_type = match params.call_type {
+            CallType::None | CallType::Call => "CALL",
+            CallType::CallCode => "CALLCODE",
+            CallType::DelegateCall => "DELEGATECALL",
+            CallType::StaticCall => "STATICCALL",
+        };
+        self.enter(call_type, params, params.data.clone().unwrap_or_default());
+    }
+
+    fn record_call_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+
+    fn record_create(&mut self, params: &ActionParams) {
+        let call_type = match params.create_type {
+            CreateType::CREATE2 => "CREATE2",
+            CreateType::None | CreateType::CREATE => "CREATE",
+        };
+        let init = params
+            .code
+            .as_ref()
+            .map_or_else(Vec::new, |c| (**c).clone());
+        self.enter(call_type, params, init);
+    }
+
+    fn record_create_result(&mut self, result: &VmResult<FrameReturn>) {
+        self.exit(result);
+    }
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", bytes.to_hex::<String>()))
+}
+
 struct CheckpointLog<T> {
     data: Vec<T>,
     checkpoints: Vec<usize>,
 }
 
+impl<T> Default for CheckpointLog<T> {
+    fn default() -> Self {
+        CheckpointLog {
+            data: vec![],
+            checkpoints: vec![],
+        }
+    }
+}
+
 impl<T> CheckpointLog<T> {
     fn push(&mut self, item: T) {
         self.data.push(item);
@@ -136,3 +341,112 @@ impl<T> CheckpointLog<T> {
         self.data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CallTracer, TransferTracer};
+    use crate::{
+        call_create_frame::FrameReturn,
+        observer::{trace::InternalTransferAction, AddressPocket, StateTracer, VmObserve},
+        vm::{ActionParams, ActionValue, CallType, Error, ReturnData},
+    };
+    use cfx_types::{Address, Space, U256};
+    use serde_json::json;
+
+    fn call(from: u64, to: u64, gas: u64) -> ActionParams {
+        ActionParams {
+            sender: Address::from_low_u64_be(from),
+            address: Address::from_low_u64_be(to),
+            gas: U256::from(gas),
+            value: ActionValue::Transfer(U256::zero()),
+            data: Some(vec![0x12, 0x34]),
+            call_type: CallType::Call,
+            ..Default::default()
+        }
+    }
+
+    fn frame_return(gas_left: u64, output: Vec<u8>) -> FrameReturn {
+        let size = output.len();
+        FrameReturn {
+            space: Space::Ethereum,
+            gas_left: U256::from(gas_left),
+            apply_state: true,
+            return_data: ReturnData::new(output, 0, size),
+            create_address: None,
+            substate: None,
+        }
+    }
+
+    #[test]
+    fn test_nested_calls() {
+        let mut tracer = CallTracer::default();
+        // 0x1 calls 0x2, which calls 0x3 twice, the second call fails.
+        tracer.record_call(&call(1, 2, 100_000));
+        tracer.record_call(&call(2, 3, 50_000));
+        tracer.record_call_result(&Ok(frame_return(40_000, vec![0xab])));
+        tracer.record_call(&call(2, 3, 5_000));
+        tracer.record_call_result(&Err(Error::OutOfGas));
+        tracer.record_call_result(&Ok(frame_return(30_000, vec![])));
+
+        assert_eq!(
+            tracer.into_json(),
+            json!({
+                "type": "CALL",
+                "from": "0x0000000000000000000000000000000000000001",
+                "to": "0x0000000000000000000000000000000000000002",
+                "value": "0x0",
+                "gas": "0x186a0",
+                "gasUsed": "0x11170",
+                "input": "0x1234",
+                "output": "0x",
+                "calls": [
+                    {
+                        "type": "CALL",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000003",
+                        "value": "0x0",
+                        "gas": "0xc350",
+                        "gasUsed": "0x2710",
+                        "input": "0x1234",
+                        "output": "0xab",
+                    },
+                    {
+                        "type": "CALL",
+                        "from": "0x0000000000000000000000000000000000000002",
+                        "to": "0x0000000000000000000000000000000000000003",
+                        "value": "0x0",
+                        "gas": "0x1388",
+                        "gasUsed": "0x1388",
+                        "input": "0x1234",
+                        "output": "0x",
+                        "error": "Out of gas",
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_frame() {
+        assert_eq!(CallTracer::default().into_json(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_transfer_tracer_drops_reverted_frames() {
+        let transfer = |value: u64| InternalTransferAction {
+            from: AddressPocket::GasPayment,
+            to: AddressPocket::MintBurn,
+            value: U256::from(value),
+        };
+        let mut tracer = TransferTracer::default();
+        tracer.trace_internal_transfer(transfer(1).from, transfer(1).to, transfer(1).value);
+        tracer.record_call(&call(1, 2, 100_000));
+        tracer.trace_internal_transfer(transfer(2).from, transfer(2).to, transfer(2).value);
+        tracer.record_call(&call(2, 3, 50_000));
+        tracer.trace_internal_transfer(transfer(3).from, transfer(3).to, transfer(3).value);
+        tracer.record_call_result(&Err(Error::Reverted));
+        tracer.record_call_result(&Ok(frame_return(40_000, vec![])));
+
+        assert_eq!(tracer.drain(), vec![transfer(1), transfer(2)]);
+    }
+}
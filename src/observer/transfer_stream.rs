@@ -0,0 +1,103 @@
+use super::trace::InternalTransferAction;
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::U256;
+use std::io::{self, Write};
+
+/// Writes each internal transfer to `sink` as one line of JSON. Transfers
+/// under a checkpoint are buffered until the outermost checkpoint is
+/// discarded, and dropped if their checkpoint is reverted.
+pub struct StreamingTransferTracer<W: Write> {
+    sink: W,
+    /// Lines buffered for each open checkpoint, the innermost last.
+    checkpoints: Vec<Vec<u8>>,
+    /// The first error in writing to the sink, later writes are skipped.
+    error: Option<io::Error>,
+}
+
+impl<W: Write> StreamingTransferTracer<W> {
+    pub fn new(sink: W) -> Self {
+        StreamingTransferTracer {
+            sink,
+            checkpoints: vec![],
+            error: None,
+        }
+    }
+
+    /// Flushes and returns the sink. The lines buffered under open
+    /// checkpoints are dropped.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.sink.flush()?;
+        Ok(self.sink)
+    }
+
+    fn write_to_sink(&mut self, lines: &[u8]) {
+        if self.error.is_none() {
+            if let Err(error) = self.sink.write_all(lines) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl<W: Write + Send> StateTracer for StreamingTransferTracer<W> {
+    fn trace_internal_transfer(&mut self, from: AddressPocket, to: AddressPocket, value: U256) {
+        let action = InternalTransferAction { from, to, value };
+        let mut line = serde_json::to_vec(&action).expect("serializing a transfer should not fail");
+        line.push(b'\n');
+        match self.checkpoints.last_mut() {
+            Some(buffer) => buffer.extend(line),
+            None => self.write_to_sink(&line),
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        self.checkpoints.push(vec![]);
+    }
+
+    fn discard_checkpoint(&mut self) {
+        let lines = self.checkpoints.pop().expect("no checkpoint to discard");
+        match self.checkpoints.last_mut() {
+            Some(buffer) => buffer.extend(lines),
+            None => self.write_to_sink(&lines),
+        }
+    }
+
+    fn revert_to_checkpoint(&mut self) {
+        self.checkpoints.pop().expect("no checkpoint to revert");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamingTransferTracer;
+    use cfx_state::tracer::{AddressPocket, StateTracer};
+    use cfx_types::{Address, AddressSpaceUtil, U256};
+
+    #[test]
+    fn test_reverted_transfers_are_dropped() {
+        let alice = AddressPocket::Balance(Address::from_low_u64_be(1).with_evm_space());
+        let bob = AddressPocket::Balance(Address::from_low_u64_be(2).with_evm_space());
+
+        let mut tracer = StreamingTransferTracer::new(Vec::new());
+        tracer.trace_internal_transfer(alice, AddressPocket::GasPayment, U256::from(1));
+        tracer.checkpoint();
+        tracer.trace_internal_transfer(alice, bob, U256::from(2));
+        tracer.checkpoint();
+        tracer.trace_internal_transfer(bob, alice, U256::from(3));
+        tracer.revert_to_checkpoint();
+        tracer.discard_checkpoint();
+
+        let output = String::from_utf8(tracer.finish().unwrap()).unwrap();
+        let values: Vec<_> = output
+            .lines()
+            .map(|line| {
+                let json: serde_json::Value = serde_json::from_str(line).unwrap();
+                json["value"].as_str().unwrap().to_owned()
+            })
+            .collect();
+        assert_eq!(values, vec!["0x1", "0x2"]);
+    }
+}
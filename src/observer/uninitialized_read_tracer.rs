@@ -0,0 +1,92 @@
+use super::{OpcodeObserve, VmObserve};
+use crate::{
+    call_create_frame::FrameReturn,
+    vm::{ActionParams, Result as VmResult},
+};
+use cfx_state::tracer::{AddressPocket, StateTracer};
+use cfx_types::{Address, H256, U256};
+use std::collections::BTreeSet;
+
+/// Finds the storage slots read as zero and never written in a transaction,
+/// which may reveal a contract relying on uninitialized storage.
+#[derive(Default)]
+pub struct UninitializedReadTracer {
+    zero_reads: BTreeSet<(Address, H256)>,
+    writes: BTreeSet<(Address, H256)>,
+}
+
+impl UninitializedReadTracer {
+    /// The slots read as zero and never written, in ascending order.
+    pub fn uninitialized_reads(&self) -> Vec<(Address, H256)> {
+        self.zero_reads.difference(&self.writes).cloned().collect()
+    }
+}
+
+impl StateTracer for UninitializedReadTracer {
+    fn trace_internal_transfer(&mut self, _: AddressPocket, _: AddressPocket, _: U256) {}
+
+    fn checkpoint(&mut self) {}
+
+    fn discard_checkpoint(&mut self) {}
+
+    fn revert_to_checkpoint(&mut self) {}
+}
+
+impl OpcodeObserve for UninitializedReadTracer {}
+
+impl VmObserve for UninitializedReadTracer {
+    fn record_call(&mut self, _: &ActionParams) {}
+
+    fn record_call_result(&mut self, _: &VmResult<FrameReturn>) {}
+
+    fn record_create(&mut self, _: &ActionParams) {}
+
+    fn record_create_result(&mut self, _: &VmResult<FrameReturn>) {}
+
+    fn record_storage_write(
+        &mut self,
+        address: &Address,
+        _code_address: &Address,
+        key: &[u8],
+        _old_value: &U256,
+        _new_value: &U256,
+    ) {
+        self.writes.insert((*address, H256::from_slice(key)));
+    }
+
+    fn record_storage_read(&mut self, address: &Address, key: &[u8], value: &U256) {
+        if value.is_zero() {
+            self.zero_reads.insert((*address, H256::from_slice(key)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UninitializedReadTracer;
+    use crate::observer::VmObserve;
+    use cfx_types::{Address, H256, U256};
+
+    #[test]
+    fn test_uninitialized_reads() {
+        let contract = Address::from_low_u64_be(1);
+        let unset = H256::from_low_u64_be(0);
+        let set = H256::from_low_u64_be(1);
+        let written = H256::from_low_u64_be(2);
+
+        let mut tracer = UninitializedReadTracer::default();
+        tracer.record_storage_read(&contract, unset.as_bytes(), &U256::zero());
+        tracer.record_storage_read(&contract, set.as_bytes(), &U256::one());
+        // A slot read as zero and then initialized is not reported.
+        tracer.record_storage_read(&contract, written.as_bytes(), &U256::zero());
+        tracer.record_storage_write(
+            &contract,
+            &contract,
+            written.as_bytes(),
+            &U256::zero(),
+            &U256::one(),
+        );
+
+        assert_eq!(tracer.uninitialized_reads(), vec![(contract, unset)]);
+    }
+}
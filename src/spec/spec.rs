@@ -38,7 +38,7 @@ pub struct CommonParams {
     pub transition_heights: TransitionsEpochHeight,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct TransitionsBlockNumber {
     /// CIP43: Introduce Finality via Voting Among Staked
     pub cip43a: BlockNumber,
@@ -66,6 +66,52 @@ pub struct TransitionsBlockNumber {
     /// CIP-105: PoS staking based minimal votes.
     pub cip105: BlockNumber,
     pub cip_sigma_fix: BlockNumber,
+    /// EIP-2681: Limit account nonce to 2^64-1
+    pub eip2681: BlockNumber,
+    /// EIP-2929: Gas cost increases for state access opcodes
+    pub eip2929: BlockNumber,
+    /// EIP-2930: Optional access lists
+    pub eip2930: BlockNumber,
+    /// EIP-4399: Supplant DIFFICULTY opcode with PREVRANDAO
+    pub eip4399: BlockNumber,
+    /// EIP-3860: Limit and meter initcode
+    pub eip3860: BlockNumber,
+    /// EIP-2565: ModExp Gas Cost
+    pub eip2565: BlockNumber,
+    /// EIP-3529: Reduction in refunds
+    pub eip3529: BlockNumber,
+    /// Calls and `EXTCODE*` see no code in the contracts killed earlier in
+    /// the transaction.
+    pub killed_contract_no_code: BlockNumber,
+}
+
+impl Default for TransitionsBlockNumber {
+    fn default() -> Self {
+        TransitionsBlockNumber {
+            cip43a: 0,
+            cip43b: 0,
+            cip62: 0,
+            cip64: 0,
+            cip71: 0,
+            cip78a: 0,
+            cip78b: 0,
+            cip90b: 0,
+            cip92: 0,
+            cip94: 0,
+            cip97: 0,
+            cip98: 0,
+            cip105: 0,
+            cip_sigma_fix: 0,
+            eip2681: BlockNumber::MAX,
+            eip2929: BlockNumber::MAX,
+            eip2930: BlockNumber::MAX,
+            eip4399: BlockNumber::MAX,
+            eip3860: BlockNumber::MAX,
+            eip2565: BlockNumber::MAX,
+            eip3529: BlockNumber::MAX,
+            killed_contract_no_code: BlockNumber::MAX,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone)]
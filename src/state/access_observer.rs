@@ -0,0 +1,29 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_bytes::Bytes;
+use cfx_types::{AddressWithSpace, H256, U256};
+use std::sync::Arc;
+
+/// An account as seen by an access through `State`, before the access
+/// changes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessedAccount {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    /// `None` if the code is not loaded for the access.
+    pub code: Option<Arc<Bytes>>,
+}
+
+/// Observes the accounts and storage slots accessed through `State`.
+pub trait AccessObserver: Send + Sync {
+    /// Called on every access to the account at `address`, with `None` if
+    /// the account does not exist.
+    fn on_account_access(&mut self, address: &AddressWithSpace, account: Option<&AccessedAccount>);
+
+    /// Called on every read of a storage slot. A write always reads the slot
+    /// first.
+    fn on_storage_access(&mut self, address: &AddressWithSpace, key: &[u8], value: &U256);
+}
@@ -8,6 +8,7 @@ use crate::{
     state::{AccountEntryProtectedMethods, State},
 };
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
+use cfx_state::AccountSnapshot;
 use cfx_statedb::{Result as DbResult, StateDb, StateDbExt, StateDbTrait};
 #[cfg(test)]
 use cfx_types::AddressSpaceUtil;
@@ -264,6 +265,19 @@ impl OverlayAccount {
         account
     }
 
+    /// The storage values written since the account is loaded.
+    pub fn storage_changes(&self) -> &HashMap<Vec<u8>, U256> {
+        &self.storage_value_write_cache
+    }
+
+    pub fn snapshot(&self) -> AccountSnapshot {
+        AccountSnapshot {
+            balance: self.balance,
+            nonce: self.nonce,
+            code_hash: self.code_hash,
+        }
+    }
+
     pub fn set_storage(&mut self, key: Vec<u8>, value: U256) {
         Arc::make_mut(&mut self.storage_value_write_cache).insert(key.clone(), value);
     }
@@ -307,6 +321,61 @@ impl OverlayAccount {
         }
     }
 
+    /// Values of several storage keys, the keys missing in the caches are
+    /// read from the database under a single lock of the read cache.
+    pub fn storage_at_many(&self, db: &StateDb, keys: &[Vec<u8>]) -> DbResult<Vec<U256>> {
+        let mut storage_value_read_cache = self.storage_value_read_cache.write();
+        keys.iter()
+            .map(|key| {
+                if let Some(value) = self.storage_value_write_cache.get(key) {
+                    return Ok(*value);
+                }
+                if let Some(value) = storage_value_read_cache.get(key) {
+                    return Ok(*value);
+                }
+                if self.fresh_storage() {
+                    return Ok(U256::zero());
+                }
+                Self::get_and_cache_storage(&mut storage_value_read_cache, db, &self.address, key)
+            })
+            .collect()
+    }
+
+    /// Number of non-zero storage entries, uncommitted writes included. The
+    /// committed number is kept in the database, so only the uncommitted
+    /// writes are visited.
+    pub fn storage_entry_count(&self, db: &StateDb) -> DbResult<u64> {
+        if self.fresh_storage() {
+            return Ok(self
+                .storage_value_write_cache
+                .values()
+                .filter(|value| !value.is_zero())
+                .count() as u64);
+        }
+
+        let mut count = db.get_storage_entry_count(&self.address)?;
+        for (key, value) in self.storage_value_write_cache.iter() {
+            let cached_value = self.storage_value_read_cache.read().get(key).cloned();
+            let committed_value = match cached_value {
+                Some(value) => value,
+                None => Self::get_and_cache_storage(
+                    &mut self.storage_value_read_cache.write(),
+                    db,
+                    &self.address,
+                    key,
+                )?,
+            };
+            // The entries committed before the counting started are not in
+            // the committed number.
+            match (committed_value.is_zero(), value.is_zero()) {
+                (true, false) => count += 1,
+                (false, true) => count = count.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(count)
+    }
+
     fn get_and_cache_storage(
         storage_value_read_cache: &mut HashMap<Vec<u8>, U256>,
         db: &StateDb,
@@ -351,21 +420,34 @@ impl OverlayAccount {
     ) -> DbResult<()> {
         assert_eq!(Arc::strong_count(&self.storage_value_write_cache), 1);
 
+        let storage_entry_count = if state.count_storage_entries {
+            Some(self.storage_entry_count(&state.db)?)
+        } else {
+            None
+        };
         if self.invalidated_storage() {
             state.recycle_storage(vec![self.address], debug_record.as_deref_mut())?;
         }
 
+        let mut storage_writes = Vec::new();
         for (k, v) in Arc::make_mut(&mut self.storage_value_write_cache).drain() {
             let address_key = StateKey::new_storage_key(&self.address, k.as_ref());
             match v.is_zero() {
                 true => state.db.delete(address_key, debug_record.as_deref_mut())?,
-                false => state.db.set::<StorageValue>(
-                    address_key,
-                    &StorageValue { value: v },
-                    debug_record.as_deref_mut(),
-                )?,
+                false => storage_writes.push((
+                    address_key.into_owned(),
+                    Vec::<u8>::from(rlp::encode(&StorageValue { value: v })).into_boxed_slice(),
+                )),
             }
         }
+        state
+            .db
+            .set_raw_batch(storage_writes, debug_record.as_deref_mut())?;
+        if let Some(count) = storage_entry_count {
+            state
+                .db
+                .set_storage_entry_count(&self.address, count, debug_record.as_deref_mut())?;
+        }
 
         if let Some(code_info) = self.code.as_ref() {
             let storage_key = StateKey::new_code_key(&self.address);
@@ -374,12 +456,10 @@ impl OverlayAccount {
                 .set::<CodeInfo>(storage_key, code_info, debug_record.as_deref_mut())?;
         }
 
-        if let Some(_layout) = self.storage_layout_change.clone() {
-            // TODO: storage layout is an unclear feature in Conflux.
-
-            // state
-            //     .db
-            //     .set_storage_layout(&self.address, layout, debug_record.as_deref_mut())?;
+        if let Some(layout) = self.storage_layout_change.as_ref() {
+            state
+                .db
+                .set_storage_layout(&self.address, layout, debug_record.as_deref_mut())?;
         }
 
         state.db.set::<Account>(
@@ -0,0 +1,39 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// A change of the checkpoint stack of `State`, carrying the index of the
+/// checkpoint created, discarded or reverted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointEvent {
+    Checkpoint(usize),
+    Discard(usize),
+    Revert(usize),
+}
+
+/// Observes the checkpoint lifecycle of `State`.
+pub trait CheckpointObserver: Send + Sync {
+    fn on_checkpoint_event(&mut self, event: CheckpointEvent);
+}
+
+/// Records the checkpoint events in order. The recorder is a shared handle,
+/// a clone installed into `State` reports to the original.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointRecorder {
+    events: Arc<Mutex<Vec<CheckpointEvent>>>,
+}
+
+impl CheckpointRecorder {
+    pub fn events(&self) -> Vec<CheckpointEvent> {
+        self.events.lock().clone()
+    }
+}
+
+impl CheckpointObserver for CheckpointRecorder {
+    fn on_checkpoint_event(&mut self, event: CheckpointEvent) {
+        self.events.lock().push(event);
+    }
+}
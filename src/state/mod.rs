@@ -3,7 +3,7 @@
 // See http://www.gnu.org/licenses/
 
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap},
     sync::Arc,
 };
 
@@ -12,28 +12,35 @@ use cfx_internal_common::debug::ComputeEpochDebugRecord;
 use cfx_parameters::internal_contract_addresses::SYSTEM_STORAGE_ADDRESS;
 use cfx_state::{
     state_trait::{AsStateOpsTrait, CheckpointTrait, StateOpsTrait},
-    CleanupMode, StateTrait,
+    AccountDiff, CleanupMode, StateDiff, StateTrait,
 };
 use cfx_statedb::{
     ErrorKind as DbErrorKind, Result as DbResult, StateDb, StateDbExt, StateDbTrait,
 };
-use cfx_types::{AddressSpaceUtil, AddressWithSpace, H256, U256};
-use parking_lot::{MappedRwLockWriteGuard, RwLock, RwLockUpgradableReadGuard, RwLockWriteGuard};
+use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, H256, U256, U512};
+use parking_lot::{
+    MappedRwLockWriteGuard, Mutex, RwLock, RwLockUpgradableReadGuard, RwLockWriteGuard,
+};
 #[cfg(test)]
 use primitives::storage::STORAGE_LAYOUT_REGULAR_V0;
-use primitives::{Account, EpochId, StateKey, StorageLayout};
+use primitives::{encode_state_key, Account, EpochId, StateKey, StorageLayout};
+use rlp::RlpStream;
 
-use crate::hash::KECCAK_EMPTY;
+use crate::hash::{keccak, KECCAK_EMPTY};
 
 use self::account_entry::{AccountEntry, AccountState};
 pub use self::{
+    access_observer::{AccessObserver, AccessedAccount},
     account_entry::OverlayAccount,
+    checkpoint_observer::{CheckpointEvent, CheckpointObserver, CheckpointRecorder},
     substate::{cleanup_mode, FrameStackInfo, Substate},
 };
 
+mod access_observer;
 mod account_entry;
 #[cfg(test)]
 mod account_entry_tests;
+mod checkpoint_observer;
 #[cfg(test)]
 mod state_tests;
 mod substate;
@@ -44,6 +51,14 @@ pub enum RequireCache {
     Code,
 }
 
+/// Whether an address holds an externally owned account or a contract.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccountKind {
+    NonExistent,
+    Eoa,
+    Contract,
+}
+
 #[derive(Copy, Clone, Debug)]
 struct WorldStatistics {
     // This is the total number of tokens issued.
@@ -66,6 +81,18 @@ pub struct State<'a> {
     // Checkpoint to the changes.
     world_statistics_checkpoints: RwLock<Vec<WorldStatistics>>,
     checkpoints: RwLock<Vec<HashMap<AddressWithSpace, Option<AccountEntry>>>>,
+
+    // EIP-1153 transient storage, only holding the non-zero values, and the
+    // old values of its entries updated after each checkpoint.
+    transient_storage: HashMap<(AddressWithSpace, Vec<u8>), U256>,
+    transient_storage_checkpoints: Vec<HashMap<(AddressWithSpace, Vec<u8>), Option<U256>>>,
+
+    checkpoint_observer: Option<Box<dyn CheckpointObserver>>,
+    access_observer: Option<Mutex<Box<dyn AccessObserver>>>,
+
+    // Whether the number of non-zero storage entries of each account is
+    // maintained in the database on commit.
+    count_storage_entries: bool,
 }
 
 impl<'a> StateTrait for State<'a> {
@@ -198,7 +225,19 @@ impl<'a> StateOpsTrait for State<'a> {
         &mut self,
         address: &AddressWithSpace,
         account_start_nonce: &U256,
+        eip2681: bool,
     ) -> DbResult<()> {
+        // EIP-2681: the nonce of a contract can not be increased beyond
+        // 2^64-1.
+        if eip2681
+            && self.ensure_account_loaded(address, RequireCache::None, |acc| {
+                acc.map_or(false, |acc| {
+                    acc.is_contract() && *acc.nonce() >= U256::from(u64::MAX)
+                })
+            })?
+        {
+            bail!(DbErrorKind::NonceOverflow(address.address));
+        }
         self.require_or_new_basic_account(address, account_start_nonce)
             .map(|mut x| x.inc_nonce())
     }
@@ -289,10 +328,20 @@ impl<'a> StateOpsTrait for State<'a> {
     }
 
     fn storage_at(&self, address: &AddressWithSpace, key: &[u8]) -> DbResult<U256> {
-        self.ensure_account_loaded(address, RequireCache::None, |acc| {
+        let value = self.ensure_account_loaded(address, RequireCache::None, |acc| {
             acc.map_or(Ok(U256::zero()), |account| {
                 account.storage_at(&self.db, key)
             })
+        })??;
+        if let Some(observer) = self.access_observer.as_ref() {
+            observer.lock().on_storage_access(address, key, &value);
+        }
+        Ok(value)
+    }
+
+    fn storage_entry_count(&self, address: &AddressWithSpace) -> DbResult<u64> {
+        self.ensure_account_loaded(address, RequireCache::None, |acc| {
+            acc.map_or(Ok(0), |account| account.storage_entry_count(&self.db))
         })?
     }
 
@@ -315,6 +364,34 @@ impl<'a> StateOpsTrait for State<'a> {
     fn get_system_storage(&self, key: &[u8]) -> DbResult<U256> {
         self.storage_at(&SYSTEM_STORAGE_ADDRESS.with_evm_space(), key)
     }
+
+    fn transient_storage_at(&self, address: &AddressWithSpace, key: &[u8]) -> U256 {
+        self.transient_storage
+            .get(&(*address, key.to_vec()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn set_transient_storage(&mut self, address: &AddressWithSpace, key: Vec<u8>, value: U256) {
+        let entry = (*address, key);
+        let old_value = if value.is_zero() {
+            self.transient_storage.remove(&entry)
+        } else {
+            self.transient_storage.insert(entry.clone(), value)
+        };
+        if let Some(checkpoint) = self.transient_storage_checkpoints.last_mut() {
+            checkpoint.entry(entry).or_insert(old_value);
+        }
+    }
+
+    fn clear_transient_storage(&mut self) {
+        let storage = std::mem::take(&mut self.transient_storage);
+        if let Some(checkpoint) = self.transient_storage_checkpoints.last_mut() {
+            for (entry, old_value) in storage {
+                checkpoint.entry(entry).or_insert(Some(old_value));
+            }
+        }
+    }
 }
 
 impl<'a> CheckpointTrait for State<'a> {
@@ -329,6 +406,8 @@ impl<'a> CheckpointTrait for State<'a> {
         let checkpoints = self.checkpoints.get_mut();
         let index = checkpoints.len();
         checkpoints.push(HashMap::new());
+        self.transient_storage_checkpoints.push(HashMap::new());
+        self.notify_checkpoint_event(CheckpointEvent::Checkpoint(index));
         index
     }
 
@@ -341,6 +420,17 @@ impl<'a> CheckpointTrait for State<'a> {
         let last = self.checkpoints.get_mut().pop();
         if let Some(mut checkpoint) = last {
             self.world_statistics_checkpoints.get_mut().pop();
+            let index = self.checkpoints.get_mut().len();
+            self.notify_checkpoint_event(CheckpointEvent::Discard(index));
+            let transient_checkpoint = self
+                .transient_storage_checkpoints
+                .pop()
+                .expect("transient_storage_checkpoint should exist");
+            if let Some(prev) = self.transient_storage_checkpoints.last_mut() {
+                for (k, v) in transient_checkpoint {
+                    prev.entry(k).or_insert(v);
+                }
+            }
             if let Some(ref mut prev) = self.checkpoints.get_mut().last_mut() {
                 if prev.is_empty() {
                     **prev = checkpoint;
@@ -361,6 +451,18 @@ impl<'a> CheckpointTrait for State<'a> {
                 .get_mut()
                 .pop()
                 .expect("staking_state_checkpoint should exist");
+            let index = self.checkpoints.get_mut().len();
+            self.notify_checkpoint_event(CheckpointEvent::Revert(index));
+            let transient_checkpoint = self
+                .transient_storage_checkpoints
+                .pop()
+                .expect("transient_storage_checkpoint should exist");
+            for (k, v) in transient_checkpoint {
+                match v {
+                    Some(v) => self.transient_storage.insert(k, v),
+                    None => self.transient_storage.remove(&k),
+                };
+            }
             for (k, v) in checkpoint.drain() {
                 match v {
                     Some(v) => match self.cache.get_mut().entry(k) {
@@ -382,6 +484,64 @@ impl<'a> CheckpointTrait for State<'a> {
             }
         }
     }
+
+    fn diff_since_checkpoint(&self) -> DbResult<StateDiff> {
+        let checkpoints = self.checkpoints.read();
+        let checkpoint = match checkpoints.last() {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(StateDiff::default()),
+        };
+        let cache = self.cache.read();
+
+        let mut diff = StateDiff::default();
+        for (address, old_entry) in checkpoint {
+            let loaded;
+            let before = match old_entry {
+                Some(entry) => entry.account.as_ref(),
+                // The account was not cached, so it is unchanged in the db.
+                None => {
+                    loaded = self
+                        .db
+                        .get_account(address)?
+                        .map(|acc| OverlayAccount::from_loaded(address, acc));
+                    loaded.as_ref()
+                }
+            };
+            let after = cache
+                .get(address)
+                .and_then(|entry| entry.account.as_ref())
+                .filter(|acc| !acc.removed_without_update());
+
+            let mut storage = BTreeMap::new();
+            let storage_changes = after.into_iter().flat_map(|acc| acc.storage_changes());
+            for (key, value) in storage_changes {
+                let old_value = match before {
+                    Some(acc) => acc.storage_at(&self.db, key)?,
+                    None => U256::zero(),
+                };
+                if old_value != *value {
+                    storage.insert(key.clone(), (old_value, *value));
+                }
+            }
+
+            let before = before
+                .filter(|acc| !acc.removed_without_update())
+                .map(OverlayAccount::snapshot);
+            let after = after.map(OverlayAccount::snapshot);
+            if before != after || !storage.is_empty() {
+                diff.accounts.insert(
+                    *address,
+                    AccountDiff {
+                        address: *address,
+                        before,
+                        after,
+                        storage,
+                    },
+                );
+            }
+        }
+        Ok(diff)
+    }
 }
 
 impl<'a> AsStateOpsTrait for State<'a> {
@@ -409,9 +569,97 @@ impl<'a> State<'a> {
             checkpoints: Default::default(),
             world_statistics,
             accounts_to_notify: Default::default(),
+            checkpoint_observer: None,
+            access_observer: None,
+            transient_storage: Default::default(),
+            transient_storage_checkpoints: Default::default(),
+            count_storage_entries: false,
         })
     }
 
+    /// Restores the state committed with `epoch_id`, dropping the uncommitted
+    /// changes and the epochs committed after it.
+    pub fn rollback_to_epoch(&mut self, epoch_id: &EpochId) -> DbResult<()> {
+        self.db.rollback_to_epoch(epoch_id)?;
+        self.clear_uncommitted()
+    }
+
+    /// Forgets the epochs committed before `epoch_id`, which can no longer
+    /// be rolled back to.
+    pub fn prune_before(&mut self, epoch_id: &EpochId) -> DbResult<()> {
+        self.db.prune_before(epoch_id)
+    }
+
+    /// Drops every change since the last commit, together with all the
+    /// checkpoints, so subsequent reads see the committed state.
+    pub fn clear_uncommitted(&mut self) -> DbResult<()> {
+        self.world_statistics = WorldStatistics {
+            total_issued_tokens: self.db.get_total_issued_tokens()?,
+        };
+        self.cache.get_mut().clear();
+        self.checkpoints.get_mut().clear();
+        self.world_statistics_checkpoints.get_mut().clear();
+        self.transient_storage.clear();
+        self.transient_storage_checkpoints.clear();
+        Ok(())
+    }
+
+    /// Maintains the number of non-zero storage entries of each account in
+    /// the database on commit. It is required by
+    /// `Spec::max_storage_entries_per_account`, and should be enabled from
+    /// the genesis, since the entries committed without it are not counted.
+    pub fn set_count_storage_entries(&mut self, enabled: bool) {
+        self.count_storage_entries = enabled;
+    }
+
+    /// Installs an observer notified on every checkpoint creation, discard
+    /// and revert. Returns the previously installed observer.
+    pub fn set_checkpoint_observer(
+        &mut self,
+        observer: Option<Box<dyn CheckpointObserver>>,
+    ) -> Option<Box<dyn CheckpointObserver>> {
+        std::mem::replace(&mut self.checkpoint_observer, observer)
+    }
+
+    fn notify_checkpoint_event(&mut self, event: CheckpointEvent) {
+        if let Some(observer) = self.checkpoint_observer.as_mut() {
+            observer.on_checkpoint_event(event);
+        }
+    }
+
+    /// Installs an observer notified on every account and storage access.
+    /// Returns the previously installed observer.
+    pub fn set_access_observer(
+        &mut self,
+        observer: Option<Box<dyn AccessObserver>>,
+    ) -> Option<Box<dyn AccessObserver>> {
+        std::mem::replace(&mut self.access_observer, observer.map(Mutex::new))
+            .map(Mutex::into_inner)
+    }
+
+    fn notify_account_access(&self, address: &AddressWithSpace, account: Option<&OverlayAccount>) {
+        if let Some(observer) = self.access_observer.as_ref() {
+            let accessed = account.map(|acc| AccessedAccount {
+                balance: *acc.balance(),
+                nonce: *acc.nonce(),
+                code_hash: acc.code_hash(),
+                code: acc.code(),
+            });
+            observer
+                .lock()
+                .on_account_access(address, accessed.as_ref());
+        }
+    }
+
+    /// Like `new`, but fails with `NotInitialized` if the database has never
+    /// been committed.
+    pub fn new_checked(db: StateDb<'a>) -> DbResult<Self> {
+        if !db.is_initialized()? {
+            bail!(DbErrorKind::NotInitialized);
+        }
+        Self::new(db)
+    }
+
     fn needs_update(require: RequireCache, account: &OverlayAccount) -> bool {
         trace!("update_account_cache account={:?}", account);
         match require {
@@ -444,6 +692,96 @@ impl<'a> State<'a> {
         Ok(())
     }
 
+    /// Returns the accounts with a non-zero balance in ascending order,
+    /// uncommitted changes included. Every key in the database is scanned to
+    /// find the accounts, so the cost is O(accounts + storage entries). The
+    /// accounts read from the database are not cached.
+    pub fn funded_accounts(&self) -> DbResult<Vec<(AddressWithSpace, U256)>> {
+        let mut funded_accounts = Vec::new();
+        for address in self.all_addresses()? {
+            if let Some(account) = self.peek_account(&address)? {
+                if !account.balance.is_zero() {
+                    funded_accounts.push((address, account.balance));
+                }
+            }
+        }
+        Ok(funded_accounts)
+    }
+
+    /// Returns a commitment over the EVM-space accounts: the keccak hash of
+    /// the RLP list of `(address, account)` in ascending order of address,
+    /// uncommitted changes included. Storage and code are not covered, only
+    /// their hashes in the accounts. Every key in the database is scanned, as
+    /// in `funded_accounts`; all the accounts are in the EVM space.
+    pub fn evm_space_root(&self) -> DbResult<H256> {
+        let mut accounts = Vec::new();
+        for address in self.all_addresses()? {
+            if let Some(account) = self.peek_account(&address)? {
+                accounts.push((address.address, account));
+            }
+        }
+
+        let mut stream = RlpStream::new_list(accounts.len());
+        for (address, account) in &accounts {
+            stream.begin_list(2).append(address).append(account);
+        }
+        Ok(keccak(stream.out()))
+    }
+
+    /// The addresses in the cache or in the database, in ascending order.
+    fn all_addresses(&self) -> DbResult<BTreeSet<AddressWithSpace>> {
+        let mut addresses: BTreeSet<_> = self.cache.read().keys().cloned().collect();
+        for (key, _) in self.db.scan_prefix(&[])? {
+            // The key of an account is its address without any suffix.
+            if key.len() == Address::len_bytes() {
+                addresses.insert(Address::from_slice(&key).with_evm_space());
+            }
+        }
+        Ok(addresses)
+    }
+
+    /// The account at `address`, uncommitted changes included. Unlike
+    /// `ensure_account_loaded`, an account read from the database is neither
+    /// cached nor reported to the access observer.
+    fn peek_account(&self, address: &AddressWithSpace) -> DbResult<Option<Account>> {
+        if let Some(entry) = self.cache.read().get(address) {
+            return Ok(entry
+                .account
+                .as_ref()
+                .filter(|acc| !acc.removed_without_update())
+                .map(OverlayAccount::as_account));
+        }
+        self.db.get_account(address)
+    }
+
+    /// Values of several storage keys of one account, loading the account
+    /// only once.
+    pub fn storage_at_many(
+        &self,
+        address: &AddressWithSpace,
+        keys: &[Vec<u8>],
+    ) -> DbResult<Vec<U256>> {
+        self.ensure_account_loaded(address, RequireCache::None, |acc| match acc {
+            Some(account) => account.storage_at_many(&self.db, keys),
+            None => Ok(vec![U256::zero(); keys.len()]),
+        })?
+    }
+
+    /// Returns the accounts recorded in each open checkpoint, from the
+    /// outermost to the innermost, for debugging unbalanced checkpoints.
+    /// The accounts of a checkpoint are in ascending order.
+    pub fn checkpoint_debug(&self) -> Vec<Vec<AddressWithSpace>> {
+        self.checkpoints
+            .read()
+            .iter()
+            .map(|checkpoint| {
+                let mut addresses: Vec<_> = checkpoint.keys().cloned().collect();
+                addresses.sort();
+                addresses
+            })
+            .collect()
+    }
+
     /// Assume that only contract with zero `collateral_for_storage` will be
     /// killed.
     pub fn recycle_storage(
@@ -453,14 +791,29 @@ impl<'a> State<'a> {
     ) -> DbResult<()> {
         // TODO: Think about kill_dust and collateral refund.
         for address in &killed_addresses {
-            // self.db.delete_all::<access_mode::Write>(
-            //     StorageKey::new_storage_root_key(&address.address).with_space(address.space),
-            //     debug_record.as_deref_mut(),
-            // )?;
-            // self.db.delete_all::<access_mode::Write>(
-            //     StorageKey::new_code_root_key(&address.address).with_space(address.space),
-            //     debug_record.as_deref_mut(),
-            // )?;
+            let storage_prefix: Vec<u8> =
+                encode_state_key(&StateKey::new_storage_key(address, &[]).into_owned());
+            let keys: Vec<_> = self
+                .db
+                .scan_prefix(&storage_prefix)?
+                .map(|(key, _)| key)
+                .collect();
+            for key in keys {
+                self.db.delete(
+                    StateKey::new_storage_key(address, &key[storage_prefix.len()..]),
+                    debug_record.as_deref_mut(),
+                )?;
+            }
+            self.db
+                .delete(StateKey::new_code_key(address), debug_record.as_deref_mut())?;
+            self.db.delete(
+                StateKey::new_storage_layout_key(address),
+                debug_record.as_deref_mut(),
+            )?;
+            self.db.delete(
+                StateKey::new_storage_entry_count_key(address),
+                debug_record.as_deref_mut(),
+            )?;
             self.db.delete(
                 StateKey::new_account_key(&address),
                 debug_record.as_deref_mut(),
@@ -497,6 +850,54 @@ impl<'a> State<'a> {
         }
     }
 
+    /// The balance left for value transfers after reserving the gas cost of a
+    /// transaction with `gas_limit` and `gas_price`, zero if the balance
+    /// cannot cover the gas cost.
+    pub fn spendable_balance(
+        &self,
+        address: &AddressWithSpace,
+        gas_limit: &U256,
+        gas_price: &U256,
+    ) -> DbResult<U256> {
+        let balance = U512::from(self.balance(address)?);
+        let gas_cost = gas_limit.full_mul(*gas_price);
+        // The difference is at most the balance, which fits in U256.
+        Ok(balance
+            .saturating_sub(gas_cost)
+            .try_into()
+            .expect("no overflow"))
+    }
+
+    /// Classifies the account by its code hash, without loading its code.
+    pub fn account_kind(&self, address: &AddressWithSpace) -> DbResult<AccountKind> {
+        self.ensure_account_loaded(address, RequireCache::None, |acc| match acc {
+            None => AccountKind::NonExistent,
+            Some(acc) if acc.code_hash() == KECCAK_EMPTY => AccountKind::Eoa,
+            Some(_) => AccountKind::Contract,
+        })
+    }
+
+    /// Loads the accounts not cached yet with a single read from the
+    /// database, e.g. before executing a block touching many accounts.
+    pub fn warm_accounts(&self, addresses: &[AddressWithSpace]) -> DbResult<()> {
+        let missing: Vec<_> = {
+            let cache = self.cache.read();
+            addresses
+                .iter()
+                .filter(|address| !cache.contains_key(address))
+                .cloned()
+                .collect()
+        };
+        let accounts = self.db.get_accounts(&missing)?;
+
+        let mut cache = self.cache.write();
+        for (address, account) in missing.iter().zip(accounts) {
+            let account = account.map(|account| OverlayAccount::from_loaded(address, account));
+            Self::insert_cache_if_fresh_account(&mut *cache, address, account);
+        }
+        Ok(())
+    }
+
     pub fn ensure_account_loaded<F, U>(
         &self,
         address: &AddressWithSpace,
@@ -511,9 +912,11 @@ impl<'a> State<'a> {
             if let Some(account) = &maybe_acc.account {
                 let needs_update = Self::needs_update(require, account);
                 if !needs_update {
+                    self.notify_account_access(address, Some(account));
                     return Ok(f(Some(account)));
                 }
             } else {
+                self.notify_account_access(address, None);
                 return Ok(f(None));
             }
         }
@@ -554,9 +957,9 @@ impl<'a> State<'a> {
             }
         }
 
-        Ok(f(cache
-            .get(address)
-            .and_then(|entry| entry.account.as_ref())))
+        let account = cache.get(address).and_then(|entry| entry.account.as_ref());
+        self.notify_account_access(address, account);
+        Ok(f(account))
     }
 
     fn require_exists(
@@ -612,6 +1015,10 @@ impl<'a> State<'a> {
         } else {
             cache = self.cache.write();
         };
+        self.notify_account_access(
+            address,
+            cache.get(address).and_then(|entry| entry.account.as_ref()),
+        );
 
         // Save the value before modification into the checkpoint.
         if let Some(ref mut checkpoint) = self.checkpoints.write().last_mut() {
@@ -2,7 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use super::{CleanupMode, CollateralCheckResult, State, Substate};
+use super::{AccountKind, CleanupMode, CollateralCheckResult, State, Substate};
 use crate::{
     spec::genesis::DEV_GENESIS_KEY_PAIR, test_helpers::get_state_for_genesis_write, vm::Spec,
 };
@@ -12,13 +12,14 @@ use cfx_state::{
     substate_trait::SubstateMngTrait,
     StateTrait,
 };
-use cfx_statedb::StateDb;
+use cfx_statedb::{StateDb, StateDbExt, StateDbTrait};
 use cfx_storage::{
     tests::new_state_manager_for_unit_test, StateIndex, StorageManager, StorageManagerTrait,
 };
-use cfx_types::{address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, U256};
+use cfx_types::{address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, H256, U256};
 use keccak_hash::{keccak, KECCAK_EMPTY};
-use primitives::{EpochId, StorageKey, StorageLayout};
+use primitives::{EpochId, StateKey, StorageKey, StorageLayout};
+use rlp::RlpStream;
 use std::sync::Arc;
 
 #[cfg(test)]
@@ -499,6 +500,7 @@ fn checkpoint_get_storage_at() {
         .inc_nonce(
             &contract_a_s,
             &Spec::new_spec_for_test().account_start_nonce,
+            false,
         )
         .unwrap();
     assert_eq!(
@@ -1846,3 +1848,606 @@ fn test_automatic_collateral_contract_account() {
     assert_eq!(state.total_storage_tokens(), U256::from(0));
     assert_eq!(state.secondary_reward(), U256::from(0));
 }
+
+#[test]
+fn test_funded_accounts() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let funded = [
+        (
+            Address::from_low_u64_be(1).with_evm_space(),
+            U256::from(100),
+        ),
+        (
+            Address::from_low_u64_be(2).with_evm_space(),
+            U256::from(200),
+        ),
+    ];
+    let unfunded = Address::from_low_u64_be(3).with_evm_space();
+    for (address, balance) in &funded {
+        state
+            .add_balance(address, balance, CleanupMode::NoEmpty, U256::zero())
+            .unwrap();
+    }
+    state
+        .add_balance(
+            &unfunded,
+            &U256::zero(),
+            CleanupMode::ForceCreate,
+            U256::zero(),
+        )
+        .unwrap();
+    assert!(state.exists(&unfunded).unwrap());
+
+    assert_eq!(state.funded_accounts().unwrap(), funded.to_vec());
+    state.commit(EpochId::default(), None).unwrap();
+
+    // Reading the committed accounts leaves the cache and the checkpoint
+    // untouched.
+    state.checkpoint();
+    assert_eq!(state.funded_accounts().unwrap(), funded.to_vec());
+    assert!(state.cache.read().is_empty());
+    assert_eq!(state.checkpoint_debug(), vec![vec![]]);
+}
+
+#[test]
+fn test_checkpoint_debug() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let b = Address::from_low_u64_be(2).with_evm_space();
+
+    state.checkpoint();
+    state
+        .add_balance(&a, &U256::from(100), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    state.checkpoint();
+    state
+        .add_balance(&b, &U256::from(200), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    assert_eq!(state.checkpoint_debug(), vec![vec![a], vec![b]]);
+
+    state.discard_checkpoint();
+    assert_eq!(state.checkpoint_debug(), vec![vec![a, b]]);
+    state.revert_to_checkpoint();
+    assert!(state.checkpoint_debug().is_empty());
+}
+
+#[test]
+fn test_evm_space_root() {
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let b = Address::from_low_u64_be(2).with_evm_space();
+
+    let mut state = crate::test_helpers::new_state_for_test();
+    let empty_root = state.evm_space_root().unwrap();
+    state
+        .add_balance(&a, &U256::from(100), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    state
+        .add_balance(&b, &U256::from(200), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    let root = state.evm_space_root().unwrap();
+    assert_ne!(root, empty_root);
+
+    // The order of changes does not matter.
+    let mut other_state = crate::test_helpers::new_state_for_test();
+    other_state
+        .add_balance(&b, &U256::from(200), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    other_state
+        .add_balance(&a, &U256::from(100), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    assert_eq!(other_state.evm_space_root().unwrap(), root);
+
+    // The storage, code and layout keys in the database are not accounts,
+    // the root only covers the two accounts.
+    state
+        .new_contract(
+            &a,
+            U256::from(100),
+            U256::one(),
+            Some(StorageLayout::Regular(0)),
+        )
+        .unwrap();
+    state.init_code(&a, vec![0x00]).unwrap();
+    state.set_storage(&a, vec![0; 32], U256::one()).unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+
+    let mut stream = RlpStream::new_list(2);
+    for address in [&a, &b] {
+        let account = state.db.get_account(address).unwrap().unwrap();
+        stream
+            .begin_list(2)
+            .append(&address.address)
+            .append(&account);
+    }
+    assert_eq!(state.evm_space_root().unwrap(), keccak(stream.out()));
+    assert!(state.cache.read().is_empty());
+}
+
+#[test]
+fn test_storage_at_many() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    let keys: Vec<Vec<u8>> = (0..5u64)
+        .map(|i| H256::from_low_u64_be(i).as_bytes().to_vec())
+        .collect();
+    for (i, key) in keys.iter().enumerate().take(3) {
+        state
+            .set_storage(&contract, key.clone(), U256::from(i + 1))
+            .unwrap();
+    }
+    state.commit(EpochId::default(), None).unwrap();
+    // One committed value is overwritten in the cache.
+    state
+        .set_storage(&contract, keys[2].clone(), U256::from(10))
+        .unwrap();
+
+    assert_eq!(
+        state.storage_at_many(&contract, &keys).unwrap(),
+        vec![
+            U256::from(1),
+            U256::from(2),
+            U256::from(10),
+            U256::zero(),
+            U256::zero()
+        ]
+    );
+    assert_eq!(
+        state
+            .storage_at_many(&Address::from_low_u64_be(2).with_evm_space(), &keys)
+            .unwrap(),
+        vec![U256::zero(); 5]
+    );
+}
+
+#[test]
+fn test_commit_storage_with_debug_record() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    state
+        .set_storage(&contract, vec![1; 32], U256::from(1))
+        .unwrap();
+    state
+        .set_storage(&contract, vec![2; 32], U256::from(2))
+        .unwrap();
+
+    // The storage entries written one by one for the record are all stored.
+    let mut debug_record = cfx_internal_common::debug::ComputeEpochDebugRecord;
+    state
+        .commit(EpochId::default(), Some(&mut debug_record))
+        .unwrap();
+    assert_eq!(
+        state
+            .storage_at_many(&contract, &[vec![1; 32], vec![2; 32]])
+            .unwrap(),
+        vec![U256::from(1), U256::from(2)]
+    );
+}
+
+#[test]
+fn test_new_checked() {
+    let error = State::new_checked(StateDb::new(cfx_storage::InMemoryDb::new()))
+        .err()
+        .expect("the database is not initialized");
+    assert!(matches!(
+        error.kind(),
+        cfx_statedb::ErrorKind::NotInitialized
+    ));
+
+    let mut db = StateDb::new(cfx_storage::InMemoryDb::new());
+    db.set_total_issued_tokens(&U256::zero(), None).unwrap();
+    assert!(State::new_checked(db).is_ok());
+}
+
+#[test]
+fn test_commit_many_storage_slots() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    let keys: Vec<Vec<u8>> = (0..1000u64)
+        .map(|i| H256::from_low_u64_be(i).as_bytes().to_vec())
+        .collect();
+    for (i, key) in keys.iter().enumerate() {
+        state
+            .set_storage(&contract, key.clone(), U256::from(i + 1))
+            .unwrap();
+    }
+    state.commit(EpochId::default(), None).unwrap();
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(state.storage_at(&contract, key).unwrap(), U256::from(i + 1));
+    }
+}
+
+#[test]
+fn test_scan_prefix() {
+    let mut db = StateDb::new(cfx_storage::InMemoryDb::new());
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let b = Address::from_low_u64_be(2).with_evm_space();
+    for (address, value) in [(&a, 1u8), (&b, 2u8)] {
+        for i in 0..3u64 {
+            let key = H256::from_low_u64_be(i);
+            db.set_raw(
+                primitives::StateKey::new_storage_key(address, key.as_bytes()),
+                vec![value].into_boxed_slice(),
+                None,
+            )
+            .unwrap();
+        }
+    }
+
+    let prefix =
+        primitives::encode_state_key(&primitives::StateKey::new_storage_key(&a, &[]).into_owned());
+    let entries: Vec<_> = db.scan_prefix(&prefix).unwrap().collect();
+    assert_eq!(entries.len(), 3);
+    assert!(entries
+        .iter()
+        .all(|(key, value)| key.starts_with(&prefix) && value[..] == [1u8]));
+}
+
+#[test]
+fn test_recycle_storage_of_killed_contract() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    state
+        .new_contract(&contract, U256::zero(), U256::zero(), None)
+        .unwrap();
+    state.init_code(&contract, vec![0x00]).unwrap();
+    let key = H256::zero().as_bytes().to_vec();
+    state
+        .set_storage(&contract, key.clone(), U256::one())
+        .unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+
+    state.remove_contract(&contract).unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+
+    assert_eq!(state.storage_at(&contract, &key).unwrap(), U256::zero());
+    let prefix: Vec<u8> = contract.address.as_bytes().to_vec();
+    assert!(state.db.scan_prefix(&prefix).unwrap().next().is_none());
+}
+
+#[test]
+fn test_recreated_contract_has_no_stale_storage() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    let keys: Vec<Vec<u8>> = (0..3u64)
+        .map(|i| H256::from_low_u64_be(i).as_bytes().to_vec())
+        .collect();
+
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    for key in &keys {
+        state
+            .set_storage(&contract, key.clone(), U256::from(0xff))
+            .unwrap();
+    }
+    state.commit(EpochId::default(), None).unwrap();
+
+    // SELFDESTRUCT, then CREATE2 at the same address.
+    state.remove_contract(&contract).unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+
+    for key in &keys {
+        assert_eq!(state.storage_at(&contract, key).unwrap(), U256::zero());
+    }
+}
+
+#[test]
+fn test_storage_entry_count_across_commits() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    state.set_count_storage_entries(true);
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    let keys: Vec<Vec<u8>> = (0..3u64)
+        .map(|i| H256::from_low_u64_be(i).as_bytes().to_vec())
+        .collect();
+
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    for key in &keys {
+        state
+            .set_storage(&contract, key.clone(), U256::one())
+            .unwrap();
+    }
+    assert_eq!(state.storage_entry_count(&contract).unwrap(), 3);
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(state.db.get_storage_entry_count(&contract).unwrap(), 3);
+
+    // Overwriting an entry keeps the count, clearing one decrements it.
+    state
+        .set_storage(&contract, keys[0].clone(), U256::from(2))
+        .unwrap();
+    state
+        .set_storage(&contract, keys[1].clone(), U256::zero())
+        .unwrap();
+    assert_eq!(state.storage_entry_count(&contract).unwrap(), 2);
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(state.db.get_storage_entry_count(&contract).unwrap(), 2);
+
+    state.remove_contract(&contract).unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(state.db.get_storage_entry_count(&contract).unwrap(), 0);
+    assert_eq!(state.storage_entry_count(&contract).unwrap(), 0);
+}
+
+#[test]
+fn test_storage_layout_round_trip() {
+    let mut db = StateDb::new(cfx_storage::InMemoryDb::new());
+    let address = Address::from_low_u64_be(1).with_evm_space();
+    assert_eq!(db.get_storage_layout(&address).unwrap(), None);
+
+    db.set_storage_layout(&address, &StorageLayout::Regular(0), None)
+        .unwrap();
+    assert_eq!(
+        db.get_storage_layout(&address).unwrap(),
+        Some(StorageLayout::Regular(0))
+    );
+
+    // A contract created with a layout stores it on commit.
+    let mut state = crate::test_helpers::new_state_for_test();
+    state
+        .new_contract(
+            &address,
+            U256::zero(),
+            U256::one(),
+            Some(StorageLayout::Regular(0)),
+        )
+        .unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(
+        state.db.get_storage_layout(&address).unwrap(),
+        Some(StorageLayout::Regular(0))
+    );
+}
+
+#[test]
+fn test_warm_accounts() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let b = Address::from_low_u64_be(2).with_evm_space();
+    let missing = Address::from_low_u64_be(3).with_evm_space();
+    for address in [&a, &b] {
+        state
+            .add_balance(
+                address,
+                &U256::from(100),
+                CleanupMode::NoEmpty,
+                U256::zero(),
+            )
+            .unwrap();
+    }
+    state.commit(EpochId::default(), None).unwrap();
+    // An uncommitted change is not overwritten by the warm-up.
+    state
+        .add_balance(&b, &U256::from(1), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+
+    state.warm_accounts(&[a, b, missing]).unwrap();
+    assert_eq!(state.cache.read().len(), 3);
+    assert_eq!(state.balance(&a).unwrap(), U256::from(100));
+    assert_eq!(state.balance(&b).unwrap(), U256::from(101));
+    assert_eq!(state.balance(&missing).unwrap(), U256::zero());
+}
+
+#[test]
+fn test_clear_uncommitted() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let b = Address::from_low_u64_be(2).with_evm_space();
+    state
+        .add_balance(&a, &U256::from(100), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    state.add_total_issued(U256::from(100));
+    state.commit(EpochId::default(), None).unwrap();
+
+    state
+        .add_balance(&a, &U256::from(1), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    state
+        .add_balance(&b, &U256::from(2), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    state.set_storage(&a, vec![0; 32], U256::one()).unwrap();
+    state.add_total_issued(U256::from(3));
+    state.checkpoint();
+    state.inc_nonce(&a, &U256::zero(), false).unwrap();
+
+    state.clear_uncommitted().unwrap();
+    assert!(state.checkpoints.get_mut().is_empty());
+    assert_eq!(state.balance(&a).unwrap(), U256::from(100));
+    assert_eq!(state.nonce(&a).unwrap(), U256::zero());
+    assert_eq!(state.storage_at(&a, &vec![0; 32]).unwrap(), U256::zero());
+    assert!(!state.exists(&b).unwrap());
+    assert_eq!(state.total_issued_tokens(), U256::from(100));
+}
+
+#[test]
+fn test_transient_storage_checkpoint() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let key = vec![0; 32];
+
+    state.set_transient_storage(&a, key.clone(), U256::one());
+    state.checkpoint();
+    state.set_transient_storage(&a, key.clone(), U256::from(2));
+    state.checkpoint();
+    state.set_transient_storage(&a, vec![1; 32], U256::from(3));
+    assert_eq!(state.transient_storage_at(&a, &[1; 32]), U256::from(3));
+
+    // Reverted TSTOREs disappear.
+    state.revert_to_checkpoint();
+    assert_eq!(state.transient_storage_at(&a, &[1; 32]), U256::zero());
+    assert_eq!(state.transient_storage_at(&a, &key), U256::from(2));
+    state.revert_to_checkpoint();
+    assert_eq!(state.transient_storage_at(&a, &key), U256::one());
+
+    // Transient storage is never written to the db.
+    assert_eq!(state.storage_at(&a, &key).unwrap(), U256::zero());
+
+    state.checkpoint();
+    state.clear_transient_storage();
+    assert_eq!(state.transient_storage_at(&a, &key), U256::zero());
+    state.revert_to_checkpoint();
+    assert_eq!(state.transient_storage_at(&a, &key), U256::one());
+}
+
+#[test]
+fn test_account_kind() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let eoa = Address::from_low_u64_be(1).with_evm_space();
+    let contract = Address::from_low_u64_be(2);
+    let missing = Address::from_low_u64_be(3).with_evm_space();
+
+    state
+        .add_balance(&eoa, &U256::one(), CleanupMode::NoEmpty, U256::zero())
+        .unwrap();
+    crate::test_helpers::deploy_code_for_test(&mut state, &contract, vec![0x00]);
+    state.commit(EpochId::default(), None).unwrap();
+
+    assert_eq!(state.account_kind(&eoa).unwrap(), AccountKind::Eoa);
+    assert_eq!(
+        state.account_kind(&contract.with_evm_space()).unwrap(),
+        AccountKind::Contract
+    );
+    assert_eq!(
+        state.account_kind(&missing).unwrap(),
+        AccountKind::NonExistent
+    );
+}
+
+#[test]
+fn test_rollback_to_epoch() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let a = Address::from_low_u64_be(1).with_evm_space();
+    let epochs: Vec<EpochId> = (1..=3).map(EpochId::from_low_u64_be).collect();
+
+    for epoch_id in &epochs {
+        state
+            .add_balance(&a, &U256::one(), CleanupMode::NoEmpty, U256::zero())
+            .unwrap();
+        state.add_total_issued(U256::one());
+        state.commit(*epoch_id, None).unwrap();
+    }
+    assert_eq!(state.balance(&a).unwrap(), U256::from(3));
+
+    state.set_storage(&a, vec![0; 32], U256::one()).unwrap();
+    state.rollback_to_epoch(&epochs[0]).unwrap();
+    assert_eq!(state.balance(&a).unwrap(), U256::one());
+    assert_eq!(state.total_issued_tokens(), U256::one());
+    assert_eq!(state.storage_at(&a, &vec![0; 32]).unwrap(), U256::zero());
+
+    // The epochs committed after the first one are forgotten.
+    assert!(state.rollback_to_epoch(&epochs[2]).is_err());
+    assert!(state.rollback_to_epoch(&EpochId::default()).is_err());
+}
+
+#[test]
+fn test_spendable_balance() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let address = Address::from_low_u64_be(1).with_evm_space();
+    state
+        .add_balance(
+            &address,
+            &U256::from(1_000_000),
+            CleanupMode::NoEmpty,
+            U256::zero(),
+        )
+        .unwrap();
+
+    // 21000 gas at price 10 reserves 210000.
+    assert_eq!(
+        state
+            .spendable_balance(&address, &U256::from(21000), &U256::from(10))
+            .unwrap(),
+        U256::from(790_000)
+    );
+    assert_eq!(
+        state
+            .spendable_balance(&address, &U256::from(21000), &U256::from(100))
+            .unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        state
+            .spendable_balance(&address, &U256::MAX, &U256::MAX)
+            .unwrap(),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_inc_nonce_at_cap() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    let eoa = Address::from_low_u64_be(2).with_evm_space();
+    let cap = U256::from(u64::MAX);
+    state
+        .new_contract(&contract, U256::zero(), cap - 1, None)
+        .unwrap();
+    state.set_nonce(&eoa, &cap).unwrap();
+
+    state.inc_nonce(&contract, &U256::zero(), true).unwrap();
+    assert_eq!(state.nonce(&contract).unwrap(), cap);
+    let error = state.inc_nonce(&contract, &U256::zero(), true).unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        cfx_statedb::ErrorKind::NonceOverflow(address) if *address == contract.address
+    ));
+    assert_eq!(state.nonce(&contract).unwrap(), cap);
+
+    // The cap only applies to contracts, and only with EIP-2681.
+    state.inc_nonce(&contract, &U256::zero(), false).unwrap();
+    assert_eq!(state.nonce(&contract).unwrap(), cap + 1);
+    state.inc_nonce(&eoa, &U256::zero(), true).unwrap();
+    assert_eq!(state.nonce(&eoa).unwrap(), cap + 1);
+}
+
+#[test]
+fn test_storage_entry_count_of_uncounted_entries() {
+    let mut state = crate::test_helpers::new_state_for_test();
+    let contract = Address::from_low_u64_be(1).with_evm_space();
+    let keys: Vec<Vec<u8>> = (0..2u64)
+        .map(|i| H256::from_low_u64_be(i).as_bytes().to_vec())
+        .collect();
+
+    // Without counting, no number is written.
+    state
+        .new_contract(&contract, U256::zero(), U256::one(), None)
+        .unwrap();
+    state
+        .set_storage(&contract, keys[0].clone(), U256::one())
+        .unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+    assert!(state
+        .db
+        .get::<u64>(StateKey::new_storage_entry_count_key(&contract))
+        .unwrap()
+        .is_none());
+
+    // Deleting the uncounted entry does not underflow.
+    state.set_count_storage_entries(true);
+    state
+        .set_storage(&contract, keys[0].clone(), U256::zero())
+        .unwrap();
+    assert_eq!(state.storage_entry_count(&contract).unwrap(), 0);
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(state.db.get_storage_entry_count(&contract).unwrap(), 0);
+
+    state
+        .set_storage(&contract, keys[1].clone(), U256::one())
+        .unwrap();
+    state.commit(EpochId::default(), None).unwrap();
+    assert_eq!(state.db.get_storage_entry_count(&contract).unwrap(), 1);
+}
@@ -4,7 +4,7 @@
 
 use super::CleanupMode;
 use crate::evm::{CleanDustMode, Spec};
-use cfx_types::AddressWithSpace;
+use cfx_types::{Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256};
 use primitives::LogEntry;
 use std::collections::{HashMap, HashSet};
 
@@ -12,6 +12,15 @@ use std::collections::{HashMap, HashSet};
 pub struct FrameStackInfo {
     call_stack_recipient_addresses: Vec<(AddressWithSpace, bool)>,
     address_counter: HashMap<AddressWithSpace, u32>,
+    /// Addresses of all the creations in the transaction, reverted ones
+    /// included.
+    created_addresses: HashSet<AddressWithSpace>,
+    /// The contracts killed by each frame in the call stack, including those
+    /// killed by its finished sub-frames.
+    suicides: Vec<HashSet<AddressWithSpace>>,
+    /// Values of the storage slots written in the transaction before their
+    /// first write, reverted writes included.
+    original_storage: HashMap<(AddressWithSpace, Vec<u8>), U256>,
 }
 
 impl FrameStackInfo {
@@ -19,13 +28,20 @@ impl FrameStackInfo {
         FrameStackInfo {
             call_stack_recipient_addresses: Vec::default(),
             address_counter: HashMap::default(),
+            created_addresses: HashSet::default(),
+            suicides: Vec::default(),
+            original_storage: HashMap::default(),
         }
     }
 
     pub fn push(&mut self, address: AddressWithSpace, is_create: bool) {
         self.call_stack_recipient_addresses
             .push((address.clone(), is_create));
+        if is_create {
+            self.created_addresses.insert(address);
+        }
         *self.address_counter.entry(address).or_insert(0) += 1;
+        self.suicides.push(HashSet::default());
     }
 
     pub fn pop(&mut self) -> Option<(AddressWithSpace, bool)> {
@@ -39,6 +55,10 @@ impl FrameStackInfo {
             if *poped_address_cnt == 0 {
                 self.address_counter.remove(address);
             }
+            let suicides = self.suicides.pop().unwrap_or_default();
+            if let Some(parent) = self.suicides.last_mut() {
+                parent.extend(suicides);
+            }
         }
         maybe_address
     }
@@ -52,6 +72,47 @@ impl FrameStackInfo {
     pub fn contains_key(&self, key: &AddressWithSpace) -> bool {
         self.address_counter.contains_key(key)
     }
+
+    pub fn created_in_transaction(&self, address: &AddressWithSpace) -> bool {
+        self.created_addresses.contains(address)
+    }
+
+    /// Records a contract killed by the current frame.
+    pub fn record_suicide(&mut self, address: AddressWithSpace) {
+        if let Some(suicides) = self.suicides.last_mut() {
+            suicides.insert(address);
+        }
+    }
+
+    /// Forgets the contracts killed by the current frame, which is reverted.
+    pub fn revert_suicides(&mut self) {
+        if let Some(suicides) = self.suicides.last_mut() {
+            suicides.clear();
+        }
+    }
+
+    /// Whether the contract has been killed in the transaction by a frame
+    /// which is not reverted so far.
+    pub fn suicided_in_transaction(&self, address: &AddressWithSpace) -> bool {
+        self.suicides
+            .iter()
+            .any(|suicides| suicides.contains(address))
+    }
+
+    /// The value of a storage slot at the beginning of the transaction. The
+    /// caller passes the current value, which is the original value if the
+    /// slot has not been written in the transaction.
+    pub fn original_storage_at(
+        &mut self,
+        address: &AddressWithSpace,
+        key: &[u8],
+        current: U256,
+    ) -> U256 {
+        *self
+            .original_storage
+            .entry((*address, key.to_vec()))
+            .or_insert(current)
+    }
 }
 
 /// State changes which should be applied in finalize,
@@ -69,6 +130,17 @@ pub struct Substate {
     pub logs: Vec<LogEntry>,
     /// Created contracts.
     pub contracts_created: Vec<AddressWithSpace>,
+    /// Storage keys read or written.
+    pub accessed_storage_keys: HashSet<(AddressWithSpace, H256)>,
+    /// Accounts accessed by `BALANCE`, `EXTCODE*`, `SELFDESTRUCT` and the
+    /// `CALL` family.
+    pub accessed_addresses: HashSet<AddressWithSpace>,
+    /// Gas refunded for clearing storage slots.
+    pub sstore_clears_refund: i64,
+    /// The changes of `sstore_clears_refund` by slot, in execution order:
+    /// the refund for clearing a slot, negative if a cleared slot is set
+    /// again.
+    pub refund_details: Vec<(AddressWithSpace, H256, i64)>,
 }
 
 impl Substate {
@@ -77,11 +149,52 @@ impl Substate {
         self.touched.extend(s.touched);
         self.logs.extend(s.logs);
         self.contracts_created.extend(s.contracts_created);
+        self.accessed_storage_keys.extend(s.accessed_storage_keys);
+        self.accessed_addresses.extend(s.accessed_addresses);
+        self.sstore_clears_refund += s.sstore_clears_refund;
+        self.refund_details.extend(s.refund_details);
     }
 
     pub fn new() -> Self {
         Substate::default()
     }
+
+    /// Marks the accounts and storage keys in the access list of a
+    /// transaction as accessed.
+    pub fn mark_access_list_accessed(
+        &mut self,
+        access_list: &[(Address, Vec<H256>)],
+        space: Space,
+    ) {
+        for (address, keys) in access_list {
+            let address = address.with_space(space);
+            self.accessed_addresses.insert(address);
+            self.accessed_storage_keys
+                .extend(keys.iter().map(|key| (address, *key)));
+        }
+    }
+
+    /// Starts with the accounts and storage keys accessed by the frame
+    /// calling this one, so that they are warm in this frame. They are
+    /// accrued back to the caller only if this frame succeeds.
+    pub fn inherit_accessed(&mut self, caller: &Substate) {
+        self.accessed_storage_keys
+            .extend(caller.accessed_storage_keys.iter().cloned());
+        self.accessed_addresses
+            .extend(caller.accessed_addresses.iter().cloned());
+    }
+
+    pub fn add_refund(&mut self, address: &AddressWithSpace, key: &[u8], value: usize) {
+        self.sstore_clears_refund += value as i64;
+        self.refund_details
+            .push((*address, H256::from_slice(key), value as i64));
+    }
+
+    pub fn sub_refund(&mut self, address: &AddressWithSpace, key: &[u8], value: usize) {
+        self.sstore_clears_refund -= value as i64;
+        self.refund_details
+            .push((*address, H256::from_slice(key), -(value as i64)));
+    }
 }
 
 /// Get the cleanup mode object from this.
@@ -0,0 +1,71 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::{
+    machine::{new_machine_with_builtin, Machine},
+    state::State,
+    vm_factory::VmFactory,
+};
+use cfx_state::{state_trait::StateOpsTrait, CleanupMode};
+use cfx_statedb::StateDb;
+use cfx_storage::InMemoryDb;
+use cfx_types::{Address, AddressSpaceUtil, U256};
+use cfxkey::{Generator, KeyPair, Random};
+use primitives::{
+    storage::STORAGE_LAYOUT_REGULAR_V0, Action, Eip155Transaction, SignedTransaction, Transaction,
+};
+
+pub fn new_state_for_test() -> State<'static> {
+    State::new(StateDb::new(InMemoryDb::new())).expect("no db error")
+}
+
+pub fn new_machine_for_test() -> Machine {
+    new_machine_with_builtin(Default::default(), VmFactory::new(1024 * 32))
+}
+
+pub fn new_funded_account(state: &mut State, balance: U256) -> KeyPair {
+    let key = Random.generate().unwrap();
+    state
+        .add_balance(
+            &key.address().with_evm_space(),
+            &balance,
+            CleanupMode::NoEmpty,
+            U256::zero(),
+        )
+        .expect("no db error");
+    key
+}
+
+pub fn deploy_code_for_test(state: &mut State, address: &Address, code: Vec<u8>) {
+    let address = address.with_evm_space();
+    state
+        .new_contract(
+            &address,
+            U256::zero(),
+            U256::one(),
+            Some(STORAGE_LAYOUT_REGULAR_V0),
+        )
+        .expect("no db error");
+    state.init_code(&address, code).expect("no db error");
+}
+
+pub fn make_signed_transaction(
+    key: &KeyPair,
+    nonce: U256,
+    action: Action,
+    value: U256,
+    gas: U256,
+    data: Vec<u8>,
+) -> SignedTransaction {
+    Transaction::from(Eip155Transaction {
+        nonce,
+        gas_price: U256::one(),
+        gas,
+        value,
+        action,
+        chain_id: Some(1),
+        data,
+    })
+    .sign(&key.secret())
+}
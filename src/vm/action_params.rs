@@ -106,13 +106,12 @@ impl Default for ActionParams {
     /// Returns default ActionParams initialized with zeros
     fn default() -> ActionParams {
         ActionParams {
-            space: Space::Native,
+            space: Space::Ethereum,
             code_address: Address::default(),
             code_hash: Some(KECCAK_EMPTY),
             address: Address::default(),
             sender: Address::default(),
             original_sender: Address::default(),
-            storage_owner: Address::default(),
             gas: U256::zero(),
             gas_price: U256::zero(),
             value: ActionValue::Transfer(U256::zero()),
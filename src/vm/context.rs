@@ -79,6 +79,18 @@ pub trait Context {
     /// Stores a value for given key.
     fn set_storage(&mut self, key: Vec<u8>, value: U256) -> Result<()>;
 
+    /// Marks the given key of the current contract as accessed.
+    fn mark_storage_accessed(&mut self, _key: &[u8]) {}
+
+    /// Whether the given key of the current contract is accessed earlier in
+    /// the transaction.
+    fn is_storage_warm(&self, _key: &[u8]) -> bool {
+        false
+    }
+
+    /// Marks the given account as accessed.
+    fn mark_address_accessed(&mut self, _address: &Address) {}
+
     /// Determine whether an account exists.
     fn exists(&self, address: &Address) -> Result<bool>;
 
@@ -39,6 +39,9 @@ pub struct Env {
     pub timestamp: u64,
     /// The block difficulty.
     pub difficulty: U256,
+    /// The randomness of the block, returned by `PREVRANDAO` in place of the
+    /// difficulty once the merge is active.
+    pub random: H256,
     /// The block gas limit.
     pub gas_limit: U256,
     /// The last block hash.
@@ -47,6 +50,8 @@ pub struct Env {
     pub accumulated_gas_used: U256,
     /// The epoch height.
     pub epoch_height: u64,
+    /// The EIP-1559 base fee per gas.
+    pub base_fee: U256,
 }
 
 #[cfg(test)]
@@ -61,6 +66,7 @@ mod tests {
         assert_eq!(default_env.author, Address::default());
         assert_eq!(default_env.timestamp, 0);
         assert_eq!(default_env.difficulty, 0.into());
+        assert_eq!(default_env.random, H256::zero());
         assert_eq!(default_env.gas_limit, 0.into());
         assert_eq!(default_env.last_hash, H256::zero());
         assert_eq!(default_env.accumulated_gas_used, 0.into());
@@ -121,6 +121,15 @@ pub enum Error {
     InvalidAddress(Address),
     /// Create a contract on an address with existing contract
     ConflictAddress(Address),
+    /// The nonce of the contract performing CREATE has reached the upper
+    /// bound (EIP-2681)
+    NonceOverflow(Address),
+    /// A storage write would make the number of storage entries of the
+    /// account exceed `max_storage_entries_per_account`
+    ExceedStorageEntryLimit(Address),
+    /// The number of sub-calls and sub-creates in the transaction exceeds
+    /// `max_sub_calls`
+    ExceedSubCallLimit,
 }
 
 #[derive(Debug)]
@@ -193,6 +202,11 @@ impl fmt::Display for Error {
             ConflictAddress(ref addr) => {
                 write!(f, "Contract creation on an existing address: {}", addr)
             }
+            NonceOverflow(ref addr) => write!(f, "Nonce overflow on address: {}", addr),
+            ExceedStorageEntryLimit(ref addr) => {
+                write!(f, "Exceed storage entry limit on address: {}", addr)
+            }
+            ExceedSubCallLimit => write!(f, "Exceed sub-call limit"),
         }
     }
 }
@@ -0,0 +1,161 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Pluggable gas cost model for the EVM interpreter.
+
+use super::{Error, Result, Spec};
+use cfx_types::{Space, U256};
+use std::fmt;
+
+/// An overflow in the gas arithmetic runs out of gas.
+fn checked(gas: Option<usize>) -> Result<usize> {
+    gas.ok_or(Error::OutOfGas)
+}
+
+/// Gas cost function consulted by the interpreter, one method per category
+/// of instructions. Every method defaults to the standard schedule in
+/// `Spec`, so a custom model only needs to override the categories it
+/// changes. A method computing its cost returns `Error::OutOfGas` if the
+/// computation overflows.
+///
+/// Memory expansion still adds the quadratic term on top of
+/// `memory_gas`, and the gas passed to a sub-call or sub-create is still
+/// charged in addition to the cost returned here.
+pub trait GasModel: fmt::Debug + Send + Sync {
+    /// Instructions priced only by their tier (arithmetic, stack, env...).
+    fn tier_gas(&self, spec: &Spec, tier: usize) -> usize {
+        spec.tier_step_gas[tier]
+    }
+
+    /// `JUMPDEST`.
+    fn jumpdest_gas(&self, spec: &Spec) -> usize {
+        spec.jumpdest_gas
+    }
+
+    /// `SLOAD`, `is_warm` is set if the slot is accessed earlier in the
+    /// transaction.
+    fn sload_gas(&self, spec: &Spec, is_warm: bool) -> usize {
+        match (spec.eip2929, is_warm) {
+            (false, _) => spec.sload_gas,
+            (true, true) => spec.warm_storage_read_gas,
+            (true, false) => spec.cold_sload_gas,
+        }
+    }
+
+    /// `SSTORE` overwriting `current` with `new`.
+    fn sstore_gas(&self, spec: &Spec, current: &U256, new: &U256) -> Result<usize> {
+        if current.is_zero() && !new.is_zero() {
+            checked(spec.sstore_set_gas.checked_mul(spec.evm_gas_ratio))
+        } else {
+            Ok(spec.sstore_reset_gas)
+        }
+    }
+
+    /// `BALANCE`.
+    fn balance_gas(&self, spec: &Spec) -> usize {
+        spec.balance_gas
+    }
+
+    /// `EXTCODESIZE`.
+    fn extcodesize_gas(&self, spec: &Spec) -> usize {
+        spec.extcodesize_gas
+    }
+
+    /// `EXTCODEHASH`.
+    fn extcodehash_gas(&self, spec: &Spec) -> usize {
+        spec.extcodehash_gas
+    }
+
+    /// Base cost of `EXTCODECOPY`, the copied words are priced by
+    /// `copy_gas`.
+    fn extcodecopy_base_gas(&self, spec: &Spec) -> usize {
+        spec.extcodecopy_base_gas
+    }
+
+    /// `SUICIDE`, `to_new_account` is set if the refund creates an account.
+    fn suicide_gas(&self, spec: &Spec, space: Space, to_new_account: bool) -> Result<usize> {
+        let ratio = match space {
+            Space::Ethereum => spec.evm_gas_ratio,
+        };
+        match to_new_account {
+            true => {
+                let new_account_gas = checked(spec.suicide_to_new_account_cost.checked_mul(ratio))?;
+                checked(spec.suicide_gas.checked_add(new_account_gas))
+            }
+            false => Ok(spec.suicide_gas),
+        }
+    }
+
+    /// `SHA3`, returns the base cost and the cost per hashed word.
+    fn sha3_gas(&self, spec: &Spec) -> (usize, usize) {
+        (spec.sha3_gas, spec.sha3_word_gas)
+    }
+
+    /// Cost per word copied into memory by the `*COPY` instructions.
+    fn copy_gas(&self, spec: &Spec) -> usize {
+        spec.copy_gas
+    }
+
+    /// Linear cost per word of memory expansion.
+    fn memory_gas(&self, spec: &Spec) -> usize {
+        spec.memory_gas
+    }
+
+    /// `LOG*`, returns the base cost with topics and the cost per data byte.
+    fn log_gas(&self, spec: &Spec, topics: usize) -> Result<(usize, usize)> {
+        let topic_gas = checked(spec.log_topic_gas.checked_mul(topics))?;
+        Ok((
+            checked(spec.log_gas.checked_add(topic_gas))?,
+            spec.log_data_gas,
+        ))
+    }
+
+    /// `*CALL*`, excluding the gas passed to the callee.
+    fn call_gas(
+        &self,
+        spec: &Spec,
+        space: Space,
+        to_new_account: bool,
+        value_transfer: bool,
+    ) -> Result<usize> {
+        let ratio = match space {
+            Space::Ethereum => spec.evm_gas_ratio,
+        };
+        let mut gas = spec.call_gas;
+        if to_new_account {
+            let new_account_gas = checked(spec.call_new_account_gas.checked_mul(ratio))?;
+            gas = checked(gas.checked_add(new_account_gas))?;
+        }
+        if value_transfer {
+            gas = checked(gas.checked_add(spec.call_value_transfer_gas))?;
+        }
+        Ok(gas)
+    }
+
+    /// `CREATE` and `CREATE2`, returns the base cost and the cost per word
+    /// of init code, excluding the gas passed to the new contract.
+    fn create_gas(&self, spec: &Spec, space: Space, is_create2: bool) -> (usize, usize) {
+        match (space, is_create2) {
+            (Space::Ethereum, false) => (spec.create_gas, 0),
+            _ => (spec.create_gas, spec.sha3_word_gas),
+        }
+    }
+
+    /// `EXP` with an exponent of `exponent_bytes` bytes.
+    fn exp_gas(&self, spec: &Spec, exponent_bytes: usize) -> Result<usize> {
+        let byte_gas = checked(spec.exp_byte_gas.checked_mul(exponent_bytes))?;
+        checked(spec.exp_gas.checked_add(byte_gas))
+    }
+
+    /// `BLOCKHASH`.
+    fn blockhash_gas(&self, spec: &Spec) -> usize {
+        spec.blockhash_gas
+    }
+}
+
+/// The gas schedule defined by `Spec`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StandardGasModel;
+
+impl GasModel for StandardGasModel {}
@@ -7,6 +7,7 @@ mod call_create_type;
 mod context;
 mod env;
 mod error;
+mod gas_model;
 mod return_data;
 mod spec;
 
@@ -22,6 +23,7 @@ pub use self::{
         separate_out_db_error, Error, ExecTrapError, ExecTrapResult, Result, TrapError, TrapKind,
         TrapResult,
     },
+    gas_model::{GasModel, StandardGasModel},
     return_data::{GasLeft, ReturnData},
     spec::{CleanDustMode, Spec, WasmCosts},
 };
@@ -20,9 +20,11 @@
 
 //! Cost spec and other parameterisations for the EVM.
 
+use super::gas_model::{GasModel, StandardGasModel};
 use crate::spec::CommonParams;
 use cfx_types::{address_util::AddressUtil, Address, U256};
 use primitives::BlockNumber;
+use std::sync::Arc;
 
 /// Definition of the cost spec and other parameterisations for the VM.
 #[derive(Debug, Clone)]
@@ -45,6 +47,12 @@ pub struct Spec {
     pub sha3_word_gas: usize,
     /// Gas price for loading from storage
     pub sload_gas: usize,
+    /// Gas price for loading a storage slot not accessed yet in the
+    /// transaction, with EIP-2929
+    pub cold_sload_gas: usize,
+    /// Gas price for loading a storage slot accessed earlier in the
+    /// transaction, with EIP-2929
+    pub warm_storage_read_gas: usize,
     /// Gas price for setting new value to storage (`storage==0`, `new!=0`)
     pub sstore_set_gas: usize,
     /// Gas price for altering value in storage
@@ -87,6 +95,15 @@ pub struct Spec {
     pub tx_data_zero_gas: usize,
     /// Aditional cost for non-empty data transaction
     pub tx_data_non_zero_gas: usize,
+    /// Additional cost for each address in the access list of a
+    /// transaction, with EIP-2930
+    pub access_list_address_gas: usize,
+    /// Additional cost for each storage key in the access list of a
+    /// transaction, with EIP-2930
+    pub access_list_storage_key_gas: usize,
+    /// Additional cost for each 32-byte word of the init code of a `CREATE`
+    /// transaction, with EIP-3860
+    pub init_code_word_gas: usize,
     /// Gas price for copying memory
     pub copy_gas: usize,
     /// Price of EXTCODESIZE
@@ -128,7 +145,7 @@ pub struct Spec {
     pub keep_unsigned_nonce: bool,
     /// Wasm extra specs, if wasm activated
     pub wasm: Option<WasmCosts>,
-    /// Start nonce for a new contract
+    /// Start nonce for a new contract, 1 under EIP-161 and 0 before it.
     pub contract_start_nonce: U256,
     /// Start nonce for a new account
     pub account_start_nonce: U256,
@@ -159,6 +176,55 @@ pub struct Spec {
     /// CIP-105: Minimal DAO votes requirement based on PoS votes.
     pub cip105: bool,
     pub cip_sigma_fix: bool,
+    /// EIP-2681: Limit account nonce to 2^64-1
+    pub eip2681: bool,
+    /// EIP-2929: `SLOAD` costs `cold_sload_gas` for a storage slot not
+    /// accessed yet in the transaction, including the slots in its access
+    /// list, and `warm_storage_read_gas` otherwise. The costs of accessing
+    /// accounts are unchanged.
+    pub eip2929: bool,
+    /// EIP-2930: The access list of a transaction is charged
+    /// `access_list_address_gas` per address and
+    /// `access_list_storage_key_gas` per storage key, and its entries are
+    /// accessed before the execution.
+    pub eip2930: bool,
+    /// EIP-4399: `DIFFICULTY` becomes `PREVRANDAO`, returning the block
+    /// randomness `Env::random`
+    pub eip4399: bool,
+    /// EIP-3860: Charge for the init code of a `CREATE` transaction per word
+    /// and limit its size to twice `create_data_limit`
+    pub eip3860: bool,
+    /// EIP-3529: Refund `sstore_refund_gas` for clearing a storage slot,
+    /// accounted against the value of the slot at the beginning of the
+    /// transaction. There is no refund before.
+    pub eip3529: bool,
+    /// A contract killed by a frame which is not reverted has no code for
+    /// `CALL*`, `EXTCODESIZE`, `EXTCODEHASH` and `EXTCODECOPY` until the end
+    /// of the transaction, when it is removed.
+    pub killed_contract_no_code: bool,
+    /// Contract creation fails if the new address has a non-zero nonce, code
+    /// or a non-zero balance.
+    pub strict_create_collision: bool,
+    /// EIP-6780: `SELFDESTRUCT` only destroys a contract created in the same
+    /// transaction. Otherwise it only moves the balance to the beneficiary,
+    /// and a contract being its own beneficiary keeps its balance.
+    pub cancun_selfdestruct: bool,
+    /// Gas cost model consulted by the interpreter. The schedule defined by
+    /// the fields above is used if not set.
+    pub gas_model: Option<Arc<dyn GasModel>>,
+    /// Maximum number of non-zero storage entries of an account, unlimited if
+    /// `None`. The state must count the entries, see
+    /// `State::set_count_storage_entries`.
+    pub max_storage_entries_per_account: Option<u64>,
+    /// Maximum number of sub-calls and sub-creates in a transaction,
+    /// unlimited if `None`.
+    pub max_sub_calls: Option<u64>,
+    /// Maximum size of the data returned by a frame, unlimited if `None`. A
+    /// frame returning more fails with out of gas.
+    pub max_return_data_size: Option<usize>,
+    /// Percentage of the refunded gas fee credited to the block author
+    /// instead of the sender, at most 100.
+    pub refund_to_author_percent: u64,
 }
 
 /// Wasm cost table
@@ -243,6 +309,8 @@ impl Spec {
             sha3_gas: 30,
             sha3_word_gas: 6,
             sload_gas: 200,
+            cold_sload_gas: 2100,
+            warm_storage_read_gas: 100,
             sstore_set_gas: 20000,
             sstore_reset_gas: 5000,
             sstore_refund_gas: 15000,
@@ -264,6 +332,9 @@ impl Spec {
             tx_create_gas: 53000,
             tx_data_zero_gas: 4,
             tx_data_non_zero_gas: 68,
+            access_list_address_gas: 2400,
+            access_list_storage_key_gas: 1900,
+            init_code_word_gas: 2,
             copy_gas: 3,
             extcodesize_gas: 700,
             extcodecopy_base_gas: 700,
@@ -300,6 +371,20 @@ impl Spec {
             cip98: false,
             cip105: false,
             cip_sigma_fix: false,
+            eip2681: false,
+            eip2929: false,
+            eip2930: false,
+            eip4399: false,
+            eip3860: false,
+            eip3529: false,
+            killed_contract_no_code: false,
+            strict_create_collision: false,
+            cancun_selfdestruct: false,
+            gas_model: None,
+            max_storage_entries_per_account: None,
+            max_sub_calls: None,
+            max_return_data_size: None,
+            refund_to_author_percent: 0,
         }
     }
 
@@ -320,9 +405,53 @@ impl Spec {
         spec.cip98 = number >= params.transition_numbers.cip98;
         spec.cip105 = number >= params.transition_numbers.cip105;
         spec.cip_sigma_fix = number >= params.transition_numbers.cip_sigma_fix;
+        spec.eip2681 = number >= params.transition_numbers.eip2681;
+        spec.eip2929 = number >= params.transition_numbers.eip2929;
+        spec.eip2930 = number >= params.transition_numbers.eip2930;
+        spec.eip4399 = number >= params.transition_numbers.eip4399;
+        spec.eip3860 = number >= params.transition_numbers.eip3860;
+        spec.eip3529 = number >= params.transition_numbers.eip3529;
+        spec.killed_contract_no_code = number >= params.transition_numbers.killed_contract_no_code;
+        if spec.eip3529 {
+            spec.sstore_refund_gas = 4800;
+        }
         spec
     }
 
+    /// Replaces the intrinsic gas of a transaction and of a `CREATE`
+    /// transaction, e.g. for an L2 with modified intrinsic gas.
+    pub fn with_base_gas(mut self, tx_gas: usize, tx_create_gas: usize) -> Spec {
+        self.tx_gas = tx_gas;
+        self.tx_create_gas = tx_create_gas;
+        self
+    }
+
+    /// Replaces the base cost and the cost per word of `SHA3`.
+    pub fn with_sha3_gas(mut self, sha3_gas: usize, sha3_word_gas: usize) -> Spec {
+        self.sha3_gas = sha3_gas;
+        self.sha3_word_gas = sha3_word_gas;
+        self
+    }
+
+    /// Replaces the linear cost per word of memory expansion.
+    pub fn with_memory_gas(mut self, memory_gas: usize) -> Spec {
+        self.memory_gas = memory_gas;
+        self
+    }
+
+    /// Whether the opcode `0x44` is `PREVRANDAO` instead of `DIFFICULTY`.
+    pub fn is_merge_active(&self) -> bool {
+        self.eip4399
+    }
+
+    /// The gas cost model consulted by the interpreter.
+    pub fn gas_model(&self) -> &dyn GasModel {
+        match &self.gas_model {
+            Some(gas_model) => &**gas_model,
+            None => &StandardGasModel,
+        }
+    }
+
     #[cfg(test)]
     pub fn new_spec_for_test() -> Spec {
         Self::genesis_spec()
@@ -41,3 +41,9 @@ impl IsDefault for bool {
         !self
     }
 }
+
+impl IsDefault for u64 {
+    fn is_default(&self) -> bool {
+        *self == 0
+    }
+}
@@ -42,8 +42,10 @@ pub use crate::{
     storage::{MptValue, NodeMerkleTriplet, StorageLayout, StorageRoot, StorageValue},
     storage_key::*,
     transaction::{
-        Action, Eip155Transaction, NativeTransaction, SignedTransaction, Transaction,
+        AccessList, AccessListItem, Action, Eip1559Transaction, Eip155Transaction,
+        Eip2930Transaction, NativeTransaction, SignedTransaction, Transaction,
         TransactionWithSignature, TransactionWithSignatureSerializePart, TxPropagateId,
+        EIP1559_TYPE, EIP2930_TYPE,
     },
     transaction_index::TransactionIndex,
 };
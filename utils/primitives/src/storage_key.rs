@@ -14,6 +14,8 @@ pub enum StateKey<'a> {
         storage_key: &'a [u8],
     },
     CodeKey(&'a AddressWithSpace),
+    StorageLayoutKey(&'a AddressWithSpace),
+    StorageEntryCountKey(&'a AddressWithSpace),
 }
 
 impl<'a> StateKey<'a> {
@@ -32,6 +34,14 @@ impl<'a> StateKey<'a> {
         StateKey::CodeKey(address)
     }
 
+    pub fn new_storage_layout_key(address: &'a AddressWithSpace) -> Self {
+        StateKey::StorageLayoutKey(address)
+    }
+
+    pub fn new_storage_entry_count_key(address: &'a AddressWithSpace) -> Self {
+        StateKey::StorageEntryCountKey(address)
+    }
+
     pub fn into_owned(self) -> OwnedStateKey {
         match self {
             StateKey::AccountKey(address) => OwnedStateKey::AccountKey(address.clone()),
@@ -43,6 +53,10 @@ impl<'a> StateKey<'a> {
                 storage_key: storage_key.to_vec(),
             },
             StateKey::CodeKey(address) => OwnedStateKey::CodeKey(address.clone()),
+            StateKey::StorageLayoutKey(address) => OwnedStateKey::StorageLayoutKey(address.clone()),
+            StateKey::StorageEntryCountKey(address) => {
+                OwnedStateKey::StorageEntryCountKey(address.clone())
+            }
         }
     }
 }
@@ -55,20 +69,73 @@ pub enum OwnedStateKey {
         storage_key: Vec<u8>,
     },
     CodeKey(AddressWithSpace),
+    StorageLayoutKey(AddressWithSpace),
+    StorageEntryCountKey(AddressWithSpace),
+}
+
+/// The raw key of `key` in the storage: the address followed by a prefix
+/// naming the kind of entry, if the key is not an account key.
+pub fn encode_state_key(key: &OwnedStateKey) -> Vec<u8> {
+    const STORAGE_PREFIX: [u8; 5] = *b"store";
+    const CODE_PREFIX: [u8; 4] = *b"code";
+    const STORAGE_LAYOUT_PREFIX: [u8; 6] = *b"layout";
+    const STORAGE_ENTRY_COUNT_PREFIX: [u8; 5] = *b"count";
+
+    match key {
+        OwnedStateKey::AccountKey(address) => address.address.0.to_vec(),
+        OwnedStateKey::StorageKey {
+            address,
+            storage_key,
+        } => [&address.address.0[..], &STORAGE_PREFIX, storage_key].concat(),
+        OwnedStateKey::CodeKey(address) => [&address.address.0[..], &CODE_PREFIX].concat(),
+        OwnedStateKey::StorageLayoutKey(address) => {
+            [&address.address.0[..], &STORAGE_LAYOUT_PREFIX].concat()
+        }
+        OwnedStateKey::StorageEntryCountKey(address) => {
+            [&address.address.0[..], &STORAGE_ENTRY_COUNT_PREFIX].concat()
+        }
+    }
 }
 
 impl<'a> From<OwnedStateKey> for Vec<u8> {
     fn from(key: OwnedStateKey) -> Self {
-        const STORAGE_PREFIX: [u8; 5] = *b"store";
-        const CODE_PREFIX: [u8; 4] = *b"code";
+        encode_state_key(&key)
+    }
+}
 
-        match key {
-            OwnedStateKey::AccountKey(address) => [&address.address.0[..]].concat(),
-            OwnedStateKey::StorageKey {
-                address,
-                storage_key,
-            } => [&address.address.0[..], &STORAGE_PREFIX, &storage_key].concat(),
-            OwnedStateKey::CodeKey(address) => [&address.address.0[..], &CODE_PREFIX].concat(),
+#[cfg(test)]
+mod tests {
+    use super::{encode_state_key, StateKey};
+    use cfx_types::{Address, AddressSpaceUtil};
+
+    #[test]
+    fn test_encode_state_key() {
+        let address = Address::from_low_u64_be(1).with_evm_space();
+        let raw_address = address.address.0.to_vec();
+
+        let cases = vec![
+            (StateKey::new_account_key(&address), raw_address.clone()),
+            (
+                StateKey::new_storage_key(&address, &[1, 2]),
+                [&raw_address[..], b"store", &[1, 2]].concat(),
+            ),
+            (
+                StateKey::new_code_key(&address),
+                [&raw_address[..], b"code"].concat(),
+            ),
+            (
+                StateKey::new_storage_layout_key(&address),
+                [&raw_address[..], b"layout"].concat(),
+            ),
+            (
+                StateKey::new_storage_entry_count_key(&address),
+                [&raw_address[..], b"count"].concat(),
+            ),
+        ];
+        for (key, expected) in cases {
+            let owned = key.into_owned();
+            assert_eq!(encode_state_key(&owned), expected);
+            assert_eq!(Vec::<u8>::from(owned), expected);
         }
     }
 }
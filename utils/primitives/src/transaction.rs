@@ -365,6 +365,73 @@ impl Encodable for Eip155Transaction {
     }
 }
 
+/// The EIP-2718 type of an EIP-2930 transaction.
+pub const EIP2930_TYPE: u8 = 0x01;
+/// The EIP-2718 type of an EIP-1559 transaction.
+pub const EIP1559_TYPE: u8 = 0x02;
+
+/// An account and the storage keys of it declared in the access list of a
+/// transaction.
+#[derive(
+    Default, Debug, Clone, Eq, PartialEq, RlpEncodable, RlpDecodable, Serialize, Deserialize,
+)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+pub type AccessList = Vec<AccessListItem>;
+
+/// EIP-2930 transaction, which declares the accounts and the storage keys it
+/// accesses. It is always replay protected.
+#[derive(
+    Default, Debug, Clone, Eq, PartialEq, RlpEncodable, RlpDecodable, Serialize, Deserialize,
+)]
+pub struct Eip2930Transaction {
+    /// The chain id of the transaction
+    pub chain_id: u32,
+    /// Nonce.
+    pub nonce: U256,
+    /// Gas price.
+    pub gas_price: U256,
+    /// Gas paid up front for transaction execution.
+    pub gas: U256,
+    /// Action, can be either call or contract create.
+    pub action: Action,
+    /// Transferred value.
+    pub value: U256,
+    /// Transaction data.
+    pub data: Bytes,
+    /// The accounts and storage keys accessed by the transaction.
+    pub access_list: Vec<AccessListItem>,
+}
+
+/// EIP-1559 transaction, which pays the base fee of the block plus a priority
+/// fee per gas, up to a max fee per gas.
+#[derive(
+    Default, Debug, Clone, Eq, PartialEq, RlpEncodable, RlpDecodable, Serialize, Deserialize,
+)]
+pub struct Eip1559Transaction {
+    /// The chain id of the transaction
+    pub chain_id: u32,
+    /// Nonce.
+    pub nonce: U256,
+    /// Max priority fee per gas, paid to the block author.
+    pub max_priority_fee_per_gas: U256,
+    /// Max fee per gas, including the base fee.
+    pub max_fee_per_gas: U256,
+    /// Gas paid up front for transaction execution.
+    pub gas: U256,
+    /// Action, can be either call or contract create.
+    pub action: Action,
+    /// Transferred value.
+    pub value: U256,
+    /// Transaction data.
+    pub data: Bytes,
+    /// The accounts and storage keys accessed by the transaction.
+    pub access_list: Vec<AccessListItem>,
+}
+
 // impl Decodable for Eip155Transaction {
 //     fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
 //         if !(rlp.at(7)?.is_empty() && rlp.at(8)?.is_empty()) {
@@ -387,6 +454,8 @@ impl Encodable for Eip155Transaction {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Transaction {
     Ethereum(Eip155Transaction),
+    Eip2930(Eip2930Transaction),
+    Eip1559(Eip1559Transaction),
 }
 
 impl Default for Transaction {
@@ -401,11 +470,25 @@ impl From<Eip155Transaction> for Transaction {
     }
 }
 
+impl From<Eip2930Transaction> for Transaction {
+    fn from(tx: Eip2930Transaction) -> Self {
+        Self::Eip2930(tx)
+    }
+}
+
+impl From<Eip1559Transaction> for Transaction {
+    fn from(tx: Eip1559Transaction) -> Self {
+        Self::Eip1559(tx)
+    }
+}
+
 macro_rules! access_common_ref {
     ($field: ident, $ty: ident) => {
         pub fn $field(&self) -> &$ty {
             match self {
                 Transaction::Ethereum(tx) => &tx.$field,
+                Transaction::Eip2930(tx) => &tx.$field,
+                Transaction::Eip1559(tx) => &tx.$field,
             }
         }
     };
@@ -417,6 +500,8 @@ macro_rules! access_common {
         pub fn $field(&self) -> $ty {
             match self {
                 Transaction::Ethereum(tx) => tx.$field,
+                Transaction::Eip2930(tx) => tx.$field,
+                Transaction::Eip1559(tx) => tx.$field,
             }
         }
     };
@@ -424,8 +509,6 @@ macro_rules! access_common {
 impl Transaction {
     access_common_ref!(gas, U256);
 
-    access_common_ref!(gas_price, U256);
-
     access_common_ref!(data, Bytes);
 
     access_common_ref!(nonce, U256);
@@ -437,12 +520,43 @@ impl Transaction {
     pub fn chain_id(&self) -> Option<u32> {
         match self {
             Transaction::Ethereum(tx) => tx.chain_id,
+            Transaction::Eip2930(tx) => Some(tx.chain_id),
+            Transaction::Eip1559(tx) => Some(tx.chain_id),
+        }
+    }
+
+    /// The gas price, or the max fee per gas of an EIP-1559 transaction.
+    pub fn gas_price(&self) -> &U256 {
+        match self {
+            Transaction::Ethereum(tx) => &tx.gas_price,
+            Transaction::Eip2930(tx) => &tx.gas_price,
+            Transaction::Eip1559(tx) => &tx.max_fee_per_gas,
+        }
+    }
+
+    /// The max priority fee per gas of an EIP-1559 transaction, `None` for
+    /// the transactions paying a flat gas price.
+    pub fn max_priority_fee_per_gas(&self) -> Option<&U256> {
+        match self {
+            Transaction::Ethereum(_) | Transaction::Eip2930(_) => None,
+            Transaction::Eip1559(tx) => Some(&tx.max_priority_fee_per_gas),
+        }
+    }
+
+    /// The access list of a typed transaction, `None` for a legacy one.
+    pub fn access_list(&self) -> Option<&[AccessListItem]> {
+        match self {
+            Transaction::Ethereum(_) => None,
+            Transaction::Eip2930(tx) => Some(&tx.access_list),
+            Transaction::Eip1559(tx) => Some(&tx.access_list),
         }
     }
 
     pub fn nonce_mut(&mut self) -> &mut U256 {
         match self {
             Transaction::Ethereum(tx) => &mut tx.nonce,
+            Transaction::Eip2930(tx) => &mut tx.nonce,
+            Transaction::Eip1559(tx) => &mut tx.nonce,
         }
     }
 }
@@ -457,13 +571,23 @@ impl Transaction {
             Transaction::Ethereum(tx) => {
                 s.append(tx);
             }
+            Transaction::Eip2930(tx) => {
+                s.append_raw(&[EIP2930_TYPE], 0);
+                s.append(tx);
+            }
+            Transaction::Eip1559(tx) => {
+                s.append_raw(&[EIP1559_TYPE], 0);
+                s.append(tx);
+            }
         }
         keccak(s.as_raw())
     }
 
     pub fn space(&self) -> Space {
         match self {
-            Transaction::Ethereum(_) => Space::Ethereum,
+            Transaction::Ethereum(_) | Transaction::Eip2930(_) | Transaction::Eip1559(_) => {
+                Space::Ethereum
+            }
         }
     }
 
@@ -541,12 +665,122 @@ impl Encodable for TransactionWithSignatureSerializePart {
                 s.append(&self.r);
                 s.append(&self.s);
             }
+            // A typed transaction in a list is the byte string of its
+            // EIP-2718 envelope.
+            Transaction::Eip2930(_) | Transaction::Eip1559(_) => {
+                s.append(&self.typed_envelope().expect("typed transaction; qed"));
+            }
         }
     }
 }
 
+impl TransactionWithSignatureSerializePart {
+    /// The EIP-2718 envelope of a typed transaction: the type followed by the
+    /// RLP list of the fields and the signature. `None` for a legacy
+    /// transaction.
+    fn typed_envelope(&self) -> Option<Vec<u8>> {
+        match self.unsigned {
+            Transaction::Ethereum(_) => None,
+            Transaction::Eip2930(ref tx) => {
+                let mut s = RlpStream::new();
+                s.append_raw(&[EIP2930_TYPE], 0);
+                s.begin_list(11);
+                s.append(&tx.chain_id);
+                s.append(&tx.nonce);
+                s.append(&tx.gas_price);
+                s.append(&tx.gas);
+                s.append(&tx.action);
+                s.append(&tx.value);
+                s.append(&tx.data);
+                s.append_list(&tx.access_list);
+                s.append(&self.v);
+                s.append(&self.r);
+                s.append(&self.s);
+                Some(s.out().to_vec())
+            }
+            Transaction::Eip1559(ref tx) => {
+                let mut s = RlpStream::new();
+                s.append_raw(&[EIP1559_TYPE], 0);
+                s.begin_list(12);
+                s.append(&tx.chain_id);
+                s.append(&tx.nonce);
+                s.append(&tx.max_priority_fee_per_gas);
+                s.append(&tx.max_fee_per_gas);
+                s.append(&tx.gas);
+                s.append(&tx.action);
+                s.append(&tx.value);
+                s.append(&tx.data);
+                s.append_list(&tx.access_list);
+                s.append(&self.v);
+                s.append(&self.r);
+                s.append(&self.s);
+                Some(s.out().to_vec())
+            }
+        }
+    }
+
+    /// Decodes the EIP-2718 envelope of a typed transaction.
+    fn decode_typed_envelope(raw: &[u8]) -> Result<Self, DecoderError> {
+        let (tx_type, payload) = raw.split_first().ok_or(DecoderError::RlpIsTooShort)?;
+        let rlp = Rlp::new(payload);
+        if rlp.payload_info()?.total() != payload.len() {
+            return Err(DecoderError::RlpInconsistentLengthAndData);
+        }
+        let (unsigned, signature_index) = match *tx_type {
+            EIP2930_TYPE => {
+                if rlp.item_count()? != 11 {
+                    return Err(DecoderError::RlpIncorrectListLen);
+                }
+                let tx = Eip2930Transaction {
+                    chain_id: rlp.val_at(0)?,
+                    nonce: rlp.val_at(1)?,
+                    gas_price: rlp.val_at(2)?,
+                    gas: rlp.val_at(3)?,
+                    action: rlp.val_at(4)?,
+                    value: rlp.val_at(5)?,
+                    data: rlp.val_at(6)?,
+                    access_list: rlp.list_at(7)?,
+                };
+                (Transaction::Eip2930(tx), 8)
+            }
+            EIP1559_TYPE => {
+                if rlp.item_count()? != 12 {
+                    return Err(DecoderError::RlpIncorrectListLen);
+                }
+                let tx = Eip1559Transaction {
+                    chain_id: rlp.val_at(0)?,
+                    nonce: rlp.val_at(1)?,
+                    max_priority_fee_per_gas: rlp.val_at(2)?,
+                    max_fee_per_gas: rlp.val_at(3)?,
+                    gas: rlp.val_at(4)?,
+                    action: rlp.val_at(5)?,
+                    value: rlp.val_at(6)?,
+                    data: rlp.val_at(7)?,
+                    access_list: rlp.list_at(8)?,
+                };
+                (Transaction::Eip1559(tx), 9)
+            }
+            _ => return Err(DecoderError::Custom("Unknown transaction type")),
+        };
+        let v: u8 = rlp.val_at(signature_index)?;
+        if v > 1 {
+            return Err(DecoderError::Custom("Invalid signature parity"));
+        }
+
+        Ok(TransactionWithSignatureSerializePart {
+            unsigned,
+            v,
+            r: rlp.val_at(signature_index + 1)?,
+            s: rlp.val_at(signature_index + 2)?,
+        })
+    }
+}
+
 impl Decodable for TransactionWithSignatureSerializePart {
     fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.is_data() {
+            return Self::decode_typed_envelope(rlp.data()?);
+        }
         match rlp.item_count()? {
             9 => {
                 let nonce: U256 = rlp.val_at(0)?;
@@ -630,6 +864,9 @@ impl DerefMut for TransactionWithSignature {
 
 impl Decodable for TransactionWithSignature {
     fn decode(d: &Rlp) -> Result<Self, DecoderError> {
+        if d.is_data() {
+            return Self::from_typed_envelope(d.data()?);
+        }
         let hash = keccak(d.as_raw());
         let rlp_size = Some(d.as_raw().len());
         // Check item count of TransactionWithSignatureSerializePart
@@ -665,9 +902,35 @@ impl TransactionWithSignature {
         }
     }
 
+    /// Decodes a raw transaction: the RLP list of a legacy transaction or
+    /// the EIP-2718 envelope of a typed transaction.
+    pub fn from_raw(raw: &[u8]) -> Result<Self, DecoderError> {
+        match raw.first() {
+            Some(tx_type) if *tx_type <= 0x7f => Self::from_typed_envelope(raw),
+            _ => rlp::decode(raw),
+        }
+    }
+
+    fn from_typed_envelope(raw: &[u8]) -> Result<Self, DecoderError> {
+        Ok(TransactionWithSignature {
+            transaction: TransactionWithSignatureSerializePart::decode_typed_envelope(raw)?,
+            hash: keccak(raw),
+            rlp_size: Some(raw.len()),
+        })
+    }
+
+    /// The canonical encoding of the transaction, whose hash is the
+    /// transaction hash: the RLP list of a legacy transaction or the EIP-2718
+    /// envelope of a typed transaction.
+    pub fn encoded(&self) -> Vec<u8> {
+        self.transaction
+            .typed_envelope()
+            .unwrap_or_else(|| self.rlp_bytes().to_vec())
+    }
+
     /// Used to compute hash of created transactions
     fn compute_hash(mut self) -> TransactionWithSignature {
-        let hash = keccak(&*self.rlp_bytes());
+        let hash = keccak(&self.encoded());
         self.hash = hash;
         self
     }
@@ -703,7 +966,7 @@ impl TransactionWithSignature {
     }
 
     pub fn rlp_size(&self) -> usize {
-        self.rlp_size.unwrap_or_else(|| self.rlp_bytes().len())
+        self.rlp_size.unwrap_or_else(|| self.encoded().len())
     }
 }
 